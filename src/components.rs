@@ -7,6 +7,10 @@ pub struct Dwarf {
     pub health: f32,
     pub hunger: f32,
     pub happiness: f32,
+    /// 疲劳度 0-100：醒着时上升，睡觉时按睡眠质量恢复
+    pub fatigue: f32,
+    /// 口渴度 0-100：随时间上升，喝酒（没有酒时喝水）解渴
+    pub thirst: f32,
 }
 
 impl Dwarf {
@@ -16,6 +20,1292 @@ impl Dwarf {
             health: 100.0,
             hunger: 50.0,
             happiness: 75.0,
+            fatigue: 0.0,
+            thirst: 30.0,
+        }
+    }
+}
+
+/// 作息班次：白班夜里睡觉，夜班白天睡觉、夜里工作
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Shift {
+    #[default]
+    Day,
+    Night,
+}
+
+impl Shift {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Shift::Day => "白班",
+            Shift::Night => "夜班",
+        }
+    }
+
+    /// 该时刻是否是这一班的睡觉时间
+    pub fn is_sleep_time(&self, hour: u32) -> bool {
+        match self {
+            Shift::Day => !(6..22).contains(&hour),
+            Shift::Night => (10..18).contains(&hour),
+        }
+    }
+
+    pub fn toggled(&self) -> Shift {
+        match self {
+            Shift::Day => Shift::Night,
+            Shift::Night => Shift::Day,
+        }
+    }
+}
+
+/// 技能种类
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SkillKind {
+    Mining,
+    Woodcutting,
+    Farming,
+    Crafting,
+    Building,
+    Fighting,
+    Hauling,
+    Fishing,
+}
+
+impl SkillKind {
+    pub const ALL: [SkillKind; 8] = [
+        SkillKind::Mining,
+        SkillKind::Woodcutting,
+        SkillKind::Farming,
+        SkillKind::Crafting,
+        SkillKind::Building,
+        SkillKind::Fighting,
+        SkillKind::Hauling,
+        SkillKind::Fishing,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SkillKind::Mining => "采矿",
+            SkillKind::Woodcutting => "伐木",
+            SkillKind::Farming => "农耕",
+            SkillKind::Crafting => "工艺",
+            SkillKind::Building => "建造",
+            SkillKind::Fighting => "战斗",
+            SkillKind::Hauling => "搬运",
+            SkillKind::Fishing => "钓鱼",
+        }
+    }
+
+    fn index(&self) -> usize {
+        SkillKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap_or(0)
+    }
+}
+
+/// 技能升级所需经验的基数（升到 n+1 级需要 n+1 倍）
+const XP_PER_LEVEL: u32 = 100;
+/// 技能等级上限
+pub const MAX_SKILL_LEVEL: u32 = 10;
+
+/// 矮人技能等级与经验
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Skills {
+    levels: [u32; 8],
+    /// 当前等级内累积的经验
+    xp: [u32; 8],
+}
+
+impl Skills {
+    pub fn level(&self, kind: SkillKind) -> u32 {
+        self.levels[kind.index()]
+    }
+
+    pub fn set_level(&mut self, kind: SkillKind, level: u32) {
+        self.levels[kind.index()] = level.min(MAX_SKILL_LEVEL);
+    }
+
+    pub fn xp(&self, kind: SkillKind) -> u32 {
+        self.xp[kind.index()]
+    }
+
+    /// 升到下一级所需的经验（已满级为 None）
+    pub fn xp_to_next(&self, kind: SkillKind) -> Option<u32> {
+        let level = self.level(kind);
+        (level < MAX_SKILL_LEVEL).then(|| (level + 1) * XP_PER_LEVEL)
+    }
+
+    /// 获得经验，返回是否升级
+    pub fn add_xp(&mut self, kind: SkillKind, amount: u32) -> bool {
+        let index = kind.index();
+        self.xp[index] += amount;
+        let mut leveled = false;
+        while let Some(needed) = self.xp_to_next(kind) {
+            if self.xp[index] < needed {
+                break;
+            }
+            self.xp[index] -= needed;
+            self.levels[index] += 1;
+            leveled = true;
+        }
+        if self.levels[index] >= MAX_SKILL_LEVEL {
+            self.xp[index] = 0;
+        }
+        leveled
+    }
+
+    /// 工作速度倍率：每级 +15%
+    pub fn work_speed(&self, kind: SkillKind) -> f32 {
+        1.0 + 0.15 * self.level(kind) as f32
+    }
+
+    /// 产出倍率：每级 +10%
+    pub fn yield_multiplier(&self, kind: SkillKind) -> f32 {
+        1.0 + 0.1 * self.level(kind) as f32
+    }
+}
+
+/// 劳动许可：矮人可以自动承担哪些工作（默认全部允许）
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Labours {
+    enabled: [bool; 8],
+}
+
+impl Default for Labours {
+    fn default() -> Self {
+        Self { enabled: [true; 8] }
+    }
+}
+
+impl Labours {
+    pub fn allows(&self, kind: SkillKind) -> bool {
+        self.enabled[kind.index()]
+    }
+
+    pub fn set(&mut self, kind: SkillKind, enabled: bool) {
+        self.enabled[kind.index()] = enabled;
+    }
+
+    /// 切换一项劳动，返回切换后是否允许
+    pub fn toggle(&mut self, kind: SkillKind) -> bool {
+        let enabled = !self.allows(kind);
+        self.set(kind, enabled);
+        enabled
+    }
+}
+
+/// 性格特质
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PersonalityTrait {
+    Cheerful,    // 乐天：心情基线更高，坏事影响更小
+    Gloomy,      // 忧郁：心情基线更低，坏事影响更大
+    Hardworking, // 勤劳：工作更快
+    Lazy,        // 懒散：工作更慢
+    HotTempered, // 暴躁：心情不好时更容易发脾气
+    Calm,        // 沉着：很难被逼到发脾气
+}
+
+impl PersonalityTrait {
+    /// 互相对立的特质，每名矮人每组最多一个
+    const PAIRS: [(PersonalityTrait, PersonalityTrait); 3] = [
+        (PersonalityTrait::Cheerful, PersonalityTrait::Gloomy),
+        (PersonalityTrait::Hardworking, PersonalityTrait::Lazy),
+        (PersonalityTrait::HotTempered, PersonalityTrait::Calm),
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PersonalityTrait::Cheerful => "乐天",
+            PersonalityTrait::Gloomy => "忧郁",
+            PersonalityTrait::Hardworking => "勤劳",
+            PersonalityTrait::Lazy => "懒散",
+            PersonalityTrait::HotTempered => "暴躁",
+            PersonalityTrait::Calm => "沉着",
+        }
+    }
+}
+
+/// 材料种类（用于喜好）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaterialKind {
+    Stone,
+    Wood,
+    Metal,
+}
+
+impl MaterialKind {
+    pub const ALL: [MaterialKind; 3] = [MaterialKind::Stone, MaterialKind::Wood, MaterialKind::Metal];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MaterialKind::Stone => "石头",
+            MaterialKind::Wood => "木材",
+            MaterialKind::Metal => "金属",
+        }
+    }
+
+    /// 散落在地上时显示的字符
+    pub fn glyph(&self) -> char {
+        match self {
+            MaterialKind::Stone => '*',
+            MaterialKind::Wood => '=',
+            MaterialKind::Metal => '£',
+        }
+    }
+}
+
+/// 食物原料种类（也用于喜好）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FoodKind {
+    Meat,
+    Fish,
+    Plant,
+    Crop,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 4] = [FoodKind::Meat, FoodKind::Fish, FoodKind::Plant, FoodKind::Crop];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoodKind::Meat => "肉",
+            FoodKind::Fish => "鱼",
+            FoodKind::Plant => "野果",
+            FoodKind::Crop => "谷物",
+        }
+    }
+
+    /// 散落在地上时显示的字符
+    pub fn glyph(&self) -> char {
+        match self {
+            FoodKind::Meat => '%',
+            FoodKind::Fish => 'α',
+            FoodKind::Plant => '•',
+            FoodKind::Crop => '"',
+        }
+    }
+}
+
+/// 饭菜档次：由厨房一次用到的原料种类数决定
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MealKind {
+    Simple,
+    Fine,
+    Lavish,
+}
+
+impl MealKind {
+    /// 从高档到低档
+    pub const ALL: [MealKind; 3] = [MealKind::Lavish, MealKind::Fine, MealKind::Simple];
+
+    /// 用到几种原料做出的饭菜
+    pub fn from_ingredients(kinds: usize) -> Self {
+        match kinds {
+            0 | 1 => MealKind::Simple,
+            2 => MealKind::Fine,
+            _ => MealKind::Lavish,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MealKind::Simple => "家常饭",
+            MealKind::Fine => "丰盛饭菜",
+            MealKind::Lavish => "珍馐",
+        }
+    }
+
+    /// 吃下后产生的想法
+    pub fn thought(&self) -> ThoughtKind {
+        match self {
+            MealKind::Simple => ThoughtKind::AteMeal,
+            MealKind::Fine => ThoughtKind::AteFineMeal,
+            MealKind::Lavish => ThoughtKind::AteLavishMeal,
+        }
+    }
+}
+
+/// 饮料种类：酒坊用谷物酿麦酒、用野果酿果酒
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DrinkKind {
+    Ale,
+    Wine,
+}
+
+impl DrinkKind {
+    pub const ALL: [DrinkKind; 2] = [DrinkKind::Ale, DrinkKind::Wine];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DrinkKind::Ale => "麦酒",
+            DrinkKind::Wine => "果酒",
+        }
+    }
+
+    /// 酿造所用的原料
+    pub fn ingredient(&self) -> FoodKind {
+        match self {
+            DrinkKind::Ale => FoodKind::Crop,
+            DrinkKind::Wine => FoodKind::Plant,
+        }
+    }
+}
+
+/// 食物分类，库存界面按此分组显示
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoodCategory {
+    Raw,
+    Meal,
+    Drink,
+}
+
+impl FoodCategory {
+    pub const ALL: [FoodCategory; 3] = [FoodCategory::Raw, FoodCategory::Meal, FoodCategory::Drink];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoodCategory::Raw => "原料",
+            FoodCategory::Meal => "饭菜",
+            FoodCategory::Drink => "饮料",
+        }
+    }
+}
+
+/// 仓库里的一份食物：原料、饭菜（记下主料）或饮料
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FoodItem {
+    Raw(FoodKind),
+    Meal(MealKind, FoodKind),
+    Drink(DrinkKind),
+}
+
+impl FoodItem {
+    pub fn category(&self) -> FoodCategory {
+        match self {
+            FoodItem::Raw(_) => FoodCategory::Raw,
+            FoodItem::Meal(..) => FoodCategory::Meal,
+            FoodItem::Drink(_) => FoodCategory::Drink,
+        }
+    }
+
+    /// 在分类内显示的名称：饭菜按档次合并显示
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoodItem::Raw(kind) => kind.label(),
+            FoodItem::Meal(meal, _) => meal.label(),
+            FoodItem::Drink(drink) => drink.label(),
+        }
+    }
+
+    /// 食物的原料（饭菜取主料），用于判断是否合矮人的口味
+    pub fn ingredient(&self) -> Option<FoodKind> {
+        match self {
+            FoodItem::Raw(kind) | FoodItem::Meal(_, kind) => Some(*kind),
+            FoodItem::Drink(_) => None,
+        }
+    }
+
+    /// 入库后多少游戏小时腐烂；酒不会坏
+    pub fn shelf_life_hours(&self) -> Option<u32> {
+        match self {
+            FoodItem::Raw(FoodKind::Fish) => Some(48),
+            FoodItem::Raw(FoodKind::Meat) => Some(72),
+            FoodItem::Raw(FoodKind::Plant) => Some(120),
+            FoodItem::Raw(FoodKind::Crop) => Some(480),
+            FoodItem::Meal(..) => Some(240),
+            FoodItem::Drink(_) => None,
+        }
+    }
+}
+
+/// 天气
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Sunny,
+    Cloudy,
+    Rain,
+    Snow,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 4] = [Weather::Sunny, Weather::Cloudy, Weather::Rain, Weather::Snow];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Weather::Sunny => "晴",
+            Weather::Cloudy => "阴",
+            Weather::Rain => "雨",
+            Weather::Snow => "雪",
+        }
+    }
+
+    /// 是否是会淋湿/冻着人的坏天气
+    pub fn is_harsh(&self) -> bool {
+        matches!(self, Weather::Rain | Weather::Snow)
+    }
+}
+
+/// 矮人性格：特质和喜好
+#[derive(Component, Clone, Debug)]
+pub struct Personality {
+    pub traits: Vec<PersonalityTrait>,
+    pub favourite_material: MaterialKind,
+    pub favourite_food: FoodKind,
+    pub favourite_weather: Weather,
+}
+
+impl Personality {
+    /// 随机生成性格：从三组对立特质中挑选一到两个，再随机挑选喜好
+    pub fn random(rng: &mut impl rand::Rng) -> Self {
+        let mut traits = Vec::new();
+        let trait_count = rng.gen_range(1..=2);
+        let mut pairs = PersonalityTrait::PAIRS.to_vec();
+        for _ in 0..trait_count {
+            let (a, b) = pairs.remove(rng.gen_range(0..pairs.len()));
+            traits.push(if rng.gen_bool(0.5) { a } else { b });
+        }
+        Self {
+            traits,
+            favourite_material: MaterialKind::ALL[rng.gen_range(0..MaterialKind::ALL.len())],
+            favourite_food: FoodKind::ALL[rng.gen_range(0..FoodKind::ALL.len())],
+            favourite_weather: Weather::ALL[rng.gen_range(0..Weather::ALL.len())],
+        }
+    }
+
+    pub fn has(&self, personality_trait: PersonalityTrait) -> bool {
+        self.traits.contains(&personality_trait)
+    }
+
+    /// 性格对工作速度的影响
+    pub fn work_speed(&self) -> f32 {
+        if self.has(PersonalityTrait::Hardworking) {
+            1.1
+        } else if self.has(PersonalityTrait::Lazy) {
+            0.9
+        } else {
+            1.0
+        }
+    }
+
+    /// 两人性格是否合得来：相同特质和喜好加分，对立特质减分
+    pub fn compatibility(&self, other: &Personality) -> f32 {
+        let mut score = 0.0;
+        for personality_trait in &self.traits {
+            if other.has(*personality_trait) {
+                score += 1.0;
+            }
+            let opposite = PersonalityTrait::PAIRS.iter().find_map(|(a, b)| {
+                if a == personality_trait {
+                    Some(*b)
+                } else if b == personality_trait {
+                    Some(*a)
+                } else {
+                    None
+                }
+            });
+            if opposite.is_some_and(|opposite| other.has(opposite)) {
+                score -= 1.0;
+            }
+        }
+        if self.favourite_material == other.favourite_material {
+            score += 0.5;
+        }
+        if self.favourite_food == other.favourite_food {
+            score += 0.5;
+        }
+        if self.favourite_weather == other.favourite_weather {
+            score += 0.5;
+        }
+        score
+    }
+
+    /// 心情低于该值时可能发脾气
+    pub fn tantrum_threshold(&self) -> f32 {
+        if self.has(PersonalityTrait::HotTempered) {
+            30.0
+        } else if self.has(PersonalityTrait::Calm) {
+            10.0
+        } else {
+            20.0
+        }
+    }
+}
+
+/// 想法种类：事件留下的情绪，强度随时间衰减
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThoughtKind {
+    AteMeal,
+    AteFavouriteFood,
+    Hungry,
+    HandledFavouriteMaterial,
+    SkillImproved,
+    NiceWeather,
+    CaughtInBadWeather,
+    WitnessedTantrum,
+    VentedAnger,
+    ChildBorn,
+    ChattedWithFriend,
+    Argued,
+    FellInLove,
+    GotMarried,
+    LostLovedOne,
+    SawCorpse,
+    SleptOutside,
+    AdmiredBuilding,
+    SleptInGoodBedroom,
+    AteAtTable,
+    SleptInCold,
+    Exhausted,
+    AteRawFood,
+    AteFineMeal,
+    AteLavishMeal,
+    HadDrink,
+    DrankWater,
+}
+
+impl ThoughtKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThoughtKind::AteMeal => "吃了一顿饭",
+            ThoughtKind::AteFavouriteFood => "吃到了最爱的食物",
+            ThoughtKind::Hungry => "饿着肚子",
+            ThoughtKind::HandledFavouriteMaterial => "摆弄了喜爱的材料",
+            ThoughtKind::SkillImproved => "技艺有所精进",
+            ThoughtKind::NiceWeather => "享受了喜欢的天气",
+            ThoughtKind::CaughtInBadWeather => "被坏天气折腾",
+            ThoughtKind::WitnessedTantrum => "目睹了别人发脾气",
+            ThoughtKind::VentedAnger => "发泄完怒气",
+            ThoughtKind::ChildBorn => "孩子出生了",
+            ThoughtKind::ChattedWithFriend => "和人聊得很投机",
+            ThoughtKind::Argued => "和别人吵了一架",
+            ThoughtKind::FellInLove => "坠入爱河",
+            ThoughtKind::GotMarried => "结婚了",
+            ThoughtKind::LostLovedOne => "失去了亲近的人",
+            ThoughtKind::SawCorpse => "看到了尸体",
+            ThoughtKind::SleptOutside => "露天睡觉",
+            ThoughtKind::AdmiredBuilding => "欣赏了一座漂亮的建筑",
+            ThoughtKind::SleptInGoodBedroom => "在舒适的卧室里睡了个好觉",
+            ThoughtKind::AteAtTable => "在餐桌旁好好吃了顿饭",
+            ThoughtKind::SleptInCold => "睡觉时冻得发抖",
+            ThoughtKind::Exhausted => "累得筋疲力尽",
+            ThoughtKind::AteRawFood => "只能生吃食材",
+            ThoughtKind::AteFineMeal => "吃了一顿丰盛的饭菜",
+            ThoughtKind::AteLavishMeal => "享用了一顿珍馐美味",
+            ThoughtKind::HadDrink => "喝了一杯好酒",
+            ThoughtKind::DrankWater => "没有酒喝，只能喝水",
+        }
+    }
+
+    /// 初始强度（心情点数，正为好心情）
+    pub fn strength(&self) -> f32 {
+        match self {
+            ThoughtKind::AteMeal => 5.0,
+            ThoughtKind::AteFavouriteFood => 15.0,
+            ThoughtKind::Hungry => -15.0,
+            ThoughtKind::HandledFavouriteMaterial => 8.0,
+            ThoughtKind::SkillImproved => 10.0,
+            ThoughtKind::NiceWeather => 6.0,
+            ThoughtKind::CaughtInBadWeather => -6.0,
+            ThoughtKind::WitnessedTantrum => -5.0,
+            ThoughtKind::VentedAnger => 25.0,
+            ThoughtKind::ChildBorn => 30.0,
+            ThoughtKind::ChattedWithFriend => 8.0,
+            ThoughtKind::Argued => -10.0,
+            ThoughtKind::FellInLove => 20.0,
+            ThoughtKind::GotMarried => 30.0,
+            ThoughtKind::LostLovedOne => -35.0,
+            ThoughtKind::SawCorpse => -25.0,
+            ThoughtKind::SleptOutside => -8.0,
+            ThoughtKind::AdmiredBuilding => 10.0,
+            ThoughtKind::SleptInGoodBedroom => 12.0,
+            ThoughtKind::AteAtTable => 6.0,
+            ThoughtKind::SleptInCold => -6.0,
+            ThoughtKind::Exhausted => -10.0,
+            ThoughtKind::AteRawFood => -4.0,
+            ThoughtKind::AteFineMeal => 10.0,
+            ThoughtKind::AteLavishMeal => 18.0,
+            ThoughtKind::HadDrink => 6.0,
+            ThoughtKind::DrankWater => -6.0,
+        }
+    }
+
+    /// 持续时间（秒，10秒为1游戏小时）
+    pub fn duration(&self) -> f32 {
+        match self {
+            ThoughtKind::AteMeal => 120.0,
+            ThoughtKind::AteFavouriteFood => 240.0,
+            ThoughtKind::Hungry => 60.0,
+            ThoughtKind::HandledFavouriteMaterial => 120.0,
+            ThoughtKind::SkillImproved => 240.0,
+            ThoughtKind::NiceWeather => 60.0,
+            ThoughtKind::CaughtInBadWeather => 60.0,
+            ThoughtKind::WitnessedTantrum => 120.0,
+            ThoughtKind::VentedAnger => 180.0,
+            ThoughtKind::ChildBorn => 960.0,
+            ThoughtKind::ChattedWithFriend => 120.0,
+            ThoughtKind::Argued => 120.0,
+            ThoughtKind::FellInLove => 480.0,
+            ThoughtKind::GotMarried => 960.0,
+            ThoughtKind::LostLovedOne => 1200.0,
+            ThoughtKind::SawCorpse => 480.0,
+            ThoughtKind::SleptOutside => 240.0,
+            ThoughtKind::AdmiredBuilding => 240.0,
+            ThoughtKind::SleptInGoodBedroom => 480.0,
+            ThoughtKind::AteAtTable => 120.0,
+            ThoughtKind::SleptInCold => 240.0,
+            ThoughtKind::Exhausted => 60.0,
+            ThoughtKind::AteRawFood => 120.0,
+            ThoughtKind::AteFineMeal => 240.0,
+            ThoughtKind::AteLavishMeal => 360.0,
+            ThoughtKind::HadDrink => 120.0,
+            ThoughtKind::DrankWater => 120.0,
+        }
+    }
+}
+
+/// 单条想法
+#[derive(Clone, Debug)]
+pub struct Thought {
+    pub kind: ThoughtKind,
+    /// 剩余时间（秒）
+    pub remaining: f32,
+}
+
+impl Thought {
+    /// 当前强度：随剩余时间线性衰减
+    pub fn current_strength(&self) -> f32 {
+        self.kind.strength() * (self.remaining / self.kind.duration())
+    }
+}
+
+/// 最多同时记住的想法数量
+const MAX_THOUGHTS: usize = 12;
+
+/// 矮人最近的想法，心情由此计算
+#[derive(Component, Clone, Debug, Default)]
+pub struct Thoughts {
+    entries: Vec<Thought>,
+}
+
+impl Thoughts {
+    /// 添加想法；同类想法已存在时刷新持续时间而不叠加
+    pub fn add(&mut self, kind: ThoughtKind) {
+        if let Some(existing) = self.entries.iter_mut().find(|t| t.kind == kind) {
+            existing.remaining = kind.duration();
+            return;
+        }
+        if self.entries.len() >= MAX_THOUGHTS {
+            // 淡忘影响最小的一条
+            if let Some(weakest) = self
+                .entries
+                .iter()
+                .enumerate()
+                .min_by(|a, b| a.1.current_strength().abs().total_cmp(&b.1.current_strength().abs()))
+                .map(|(index, _)| index)
+            {
+                self.entries.remove(weakest);
+            }
+        }
+        self.entries.push(Thought {
+            kind,
+            remaining: kind.duration(),
+        });
+    }
+
+    /// 推进时间，移除已淡忘的想法
+    pub fn tick(&mut self, delta: f32) {
+        for thought in self.entries.iter_mut() {
+            thought.remaining -= delta;
+        }
+        self.entries.retain(|t| t.remaining > 0.0);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Thought> {
+        self.entries.iter()
+    }
+
+    /// 由想法和性格计算心情（0-100，50为平静）
+    pub fn mood(&self, personality: &Personality) -> f32 {
+        let mut mood = 50.0;
+        if personality.has(PersonalityTrait::Cheerful) {
+            mood += 8.0;
+        }
+        if personality.has(PersonalityTrait::Gloomy) {
+            mood -= 8.0;
+        }
+        for thought in &self.entries {
+            let strength = thought.current_strength();
+            mood += if strength >= 0.0 {
+                strength
+            } else if personality.has(PersonalityTrait::Cheerful) {
+                strength * 0.75
+            } else if personality.has(PersonalityTrait::Gloomy) {
+                strength * 1.25
+            } else {
+                strength
+            };
+        }
+        mood.clamp(0.0, 100.0)
+    }
+}
+
+/// 心情等级
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoodLevel {
+    Ecstatic,
+    Content,
+    Fine,
+    Unhappy,
+    Miserable,
+}
+
+impl MoodLevel {
+    pub fn from_mood(mood: f32) -> Self {
+        if mood >= 80.0 {
+            MoodLevel::Ecstatic
+        } else if mood >= 60.0 {
+            MoodLevel::Content
+        } else if mood >= 40.0 {
+            MoodLevel::Fine
+        } else if mood >= 20.0 {
+            MoodLevel::Unhappy
+        } else {
+            MoodLevel::Miserable
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MoodLevel::Ecstatic => "欣喜",
+            MoodLevel::Content => "满足",
+            MoodLevel::Fine => "平静",
+            MoodLevel::Unhappy => "不快",
+            MoodLevel::Miserable => "痛苦",
+        }
+    }
+
+    /// 心情对工作速度的影响
+    pub fn work_speed(&self) -> f32 {
+        match self {
+            MoodLevel::Ecstatic => 1.15,
+            MoodLevel::Content => 1.05,
+            MoodLevel::Fine => 1.0,
+            MoodLevel::Unhappy => 0.85,
+            MoodLevel::Miserable => 0.7,
+        }
+    }
+}
+
+/// 发脾气中：拒绝工作、四处乱走，结束后移除
+#[derive(Component, Clone, Debug)]
+pub struct Tantrum {
+    pub remaining: f32,
+}
+
+/// 矮人所属的文化（决定起名用的语言）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameCulture {
+    /// 山地氏族
+    Mountain,
+    /// 深厅氏族
+    DeepHalls,
+    /// 丘陵氏族
+    Hills,
+}
+
+impl NameCulture {
+    pub const ALL: [NameCulture; 3] = [NameCulture::Mountain, NameCulture::DeepHalls, NameCulture::Hills];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NameCulture::Mountain => "山地氏族",
+            NameCulture::DeepHalls => "深厅氏族",
+            NameCulture::Hills => "丘陵氏族",
+        }
+    }
+}
+
+/// 矮人的身份：名字、家族、绰号、外貌和生日（由名字生成器按世界种子确定性生成）
+#[derive(Component, Clone, Debug)]
+pub struct Identity {
+    pub culture: NameCulture,
+    /// 名（音译）
+    pub given_name: String,
+    /// 家族名（本族语）
+    pub family_name: String,
+    /// 家族名的意译
+    pub family_meaning: String,
+    /// 绰号（意译）
+    pub epithet: String,
+    /// 外貌描述
+    pub appearance: Vec<String>,
+    /// 出生日（以要塞历法计，要塞建立当天为第0天，可为负数）
+    pub born_day: i64,
+}
+
+impl Identity {
+    /// 完整姓名，同时作为矮人的 `Dwarf::name`
+    pub fn full_name(&self) -> String {
+        format!("{}·{}", self.given_name, self.family_name)
+    }
+
+    /// 指定日子的年龄
+    pub fn age_on(&self, day: u32) -> Age {
+        Age {
+            days: (day as i64 - self.born_day).max(0) as u32,
+        }
+    }
+
+    /// 生日描述，例如"第3年夏第2天"或"建立前45年冬第1天"
+    pub fn birth_date_label(&self) -> String {
+        use crate::resources::{DAYS_PER_SEASON, DAYS_PER_YEAR};
+        let year = self.born_day.div_euclid(DAYS_PER_YEAR as i64);
+        let day_of_year = self.born_day.rem_euclid(DAYS_PER_YEAR as i64) as u32;
+        let season = ["春", "夏", "秋", "冬"][(day_of_year / DAYS_PER_SEASON) as usize];
+        let day = day_of_year % DAYS_PER_SEASON + 1;
+        if year >= 0 {
+            format!("第{}年{}第{}天", year + 1, season, day)
+        } else {
+            format!("建立前{}年{}第{}天", -year, season, day)
+        }
+    }
+}
+
+/// 成年年龄（岁）
+pub const ADULT_AGE: u32 = 12;
+/// 超过该年龄后开始有寿终的可能（岁）
+pub const OLD_AGE: u32 = 130;
+
+/// 年龄（以游戏日计，每年 DAYS_PER_YEAR 天）
+#[derive(Component, Clone, Debug, Default)]
+pub struct Age {
+    pub days: u32,
+}
+
+impl Age {
+    pub fn years(&self) -> u32 {
+        self.days / crate::resources::DAYS_PER_YEAR
+    }
+
+    /// 儿童不工作、不谈恋爱
+    pub fn is_child(&self) -> bool {
+        self.years() < ADULT_AGE
+    }
+}
+
+/// 好感度达到该值视为朋友
+pub const FRIEND_AFFINITY: f32 = 40.0;
+/// 好感度低于该值视为对头
+pub const RIVAL_AFFINITY: f32 = -40.0;
+
+/// 特殊关系纽带（恋爱与家庭），不随好感度自动变化
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Bond {
+    #[default]
+    None,
+    Lover,
+    Spouse,
+    Parent,
+    Child,
+    Sibling,
+}
+
+/// 对另一名矮人的关系
+#[derive(Clone, Debug, Default)]
+pub struct Relationship {
+    /// 好感度（-100 到 100）
+    pub affinity: f32,
+    pub bond: Bond,
+}
+
+impl Relationship {
+    pub fn label(&self) -> &'static str {
+        match self.bond {
+            Bond::Lover => "恋人",
+            Bond::Spouse => "配偶",
+            Bond::Parent => "父母",
+            Bond::Child => "子女",
+            Bond::Sibling => "兄弟姐妹",
+            Bond::None if self.affinity >= FRIEND_AFFINITY => "朋友",
+            Bond::None if self.affinity <= RIVAL_AFFINITY => "对头",
+            Bond::None => "相识",
+        }
+    }
+
+    /// 关系有多亲密/重要，用于排序
+    pub fn closeness(&self) -> f32 {
+        let bond_weight = match self.bond {
+            Bond::Spouse | Bond::Lover => 300.0,
+            Bond::Parent | Bond::Child | Bond::Sibling => 200.0,
+            Bond::None => 0.0,
+        };
+        bond_weight + self.affinity.abs()
+    }
+
+    /// 去世时是否会让对方悲痛
+    pub fn grieves(&self) -> bool {
+        self.bond != Bond::None || self.affinity >= FRIEND_AFFINITY
+    }
+}
+
+/// 矮人的社会关系网，以对方姓名为键（随存档保存）
+#[derive(Component, Clone, Debug, Default)]
+pub struct Relationships {
+    entries: std::collections::HashMap<String, Relationship>,
+}
+
+impl Relationships {
+    pub fn get(&self, name: &str) -> Option<&Relationship> {
+        self.entries.get(name)
+    }
+
+    /// 调整好感度，初次接触时建立关系
+    pub fn adjust(&mut self, name: &str, delta: f32) {
+        let relationship = self.entries.entry(name.to_string()).or_default();
+        relationship.affinity = (relationship.affinity + delta).clamp(-100.0, 100.0);
+    }
+
+    pub fn set_bond(&mut self, name: &str, bond: Bond) {
+        self.entries.entry(name.to_string()).or_default().bond = bond;
+    }
+
+    /// 恋人或配偶的名字
+    pub fn partner(&self) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(_, r)| matches!(r.bond, Bond::Lover | Bond::Spouse))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// 具有指定纽带的所有人
+    pub fn with_bond(&self, bond: Bond) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter(move |(_, r)| r.bond == bond)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// 按亲密程度排序的前 n 个关系
+    pub fn closest(&self, n: usize) -> Vec<(&str, &Relationship)> {
+        let mut sorted: Vec<_> = self.entries.iter().map(|(name, r)| (name.as_str(), r)).collect();
+        sorted.sort_by(|a, b| b.1.closeness().total_cmp(&a.1.closeness()));
+        sorted.truncate(n);
+        sorted
+    }
+
+    /// 移除与某人的关系（对方离开或去世）
+    pub fn forget(&mut self, name: &str) -> Option<Relationship> {
+        self.entries.remove(name)
+    }
+}
+
+/// 物品材质
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemMaterial {
+    Wood,
+    Stone,
+    Copper,
+    Iron,
+}
+
+impl ItemMaterial {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemMaterial::Wood => "木",
+            ItemMaterial::Stone => "石",
+            ItemMaterial::Copper => "铜",
+            ItemMaterial::Iron => "铁",
+        }
+    }
+
+    /// 材质品质：工具速度、武器威力和护甲防护都按此缩放
+    pub fn quality(&self) -> f32 {
+        match self {
+            ItemMaterial::Wood => 0.5,
+            ItemMaterial::Stone => 0.7,
+            ItemMaterial::Copper => 1.0,
+            ItemMaterial::Iron => 1.3,
+        }
+    }
+}
+
+/// 装备栏位
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EquipSlot {
+    Hands,
+    Body,
+    Back,
+}
+
+impl EquipSlot {
+    pub const ALL: [EquipSlot; 3] = [EquipSlot::Hands, EquipSlot::Body, EquipSlot::Back];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EquipSlot::Hands => "手持",
+            EquipSlot::Body => "身穿",
+            EquipSlot::Back => "背负",
+        }
+    }
+}
+
+/// 物品种类
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Pick,
+    Axe,
+    Weapon,
+    Armour,
+    Backpack,
+}
+
+impl ItemKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemKind::Pick => "镐",
+            ItemKind::Axe => "斧",
+            ItemKind::Weapon => "战锤",
+            ItemKind::Armour => "甲",
+            ItemKind::Backpack => "背篓",
+        }
+    }
+}
+
+impl SkillKind {
+    /// 从事该劳动必须手持的工具
+    pub fn required_tool(&self) -> Option<ItemKind> {
+        match self {
+            SkillKind::Mining => Some(ItemKind::Pick),
+            SkillKind::Woodcutting => Some(ItemKind::Axe),
+            _ => None,
+        }
+    }
+}
+
+/// 一件物品
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub material: ItemMaterial,
+}
+
+impl Item {
+    pub fn new(kind: ItemKind, material: ItemMaterial) -> Self {
+        Self { kind, material }
+    }
+
+    pub fn name(&self) -> String {
+        format!("{}{}", self.material.label(), self.kind.label())
+    }
+}
+
+/// 不背背篓时能携带的资源数量
+pub const BASE_CARRY_CAPACITY: u32 = 5;
+/// 背篓额外增加的携带量
+pub const BACKPACK_CAPACITY: u32 = 10;
+/// 仓库没有工具时，就地打制一把石制工具所需的石头
+pub const STONE_TOOL_COST: u32 = 2;
+/// 距仓库多少格以内即可卸货
+pub const STOCKPILE_ARRIVAL_RADIUS: i32 = 1;
+/// 货物达到携带量的这个比例时，空闲的矮人就动身运回仓库
+pub const HAUL_LOAD_FRACTION: f32 = 0.5;
+
+/// 矮人随身携带、尚未运回仓库的资源
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cargo {
+    pub wood: u32,
+    pub stone: u32,
+    /// 采集到的食物原料及数量
+    pub food: Vec<(FoodKind, u32)>,
+    pub metal: u32,
+}
+
+impl Cargo {
+    pub fn total(&self) -> u32 {
+        self.wood + self.stone + self.food_total() + self.metal
+    }
+
+    pub fn food_total(&self) -> u32 {
+        self.food.iter().map(|(_, amount)| amount).sum()
+    }
+
+    pub fn add_food(&mut self, kind: FoodKind, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        match self.food.iter_mut().find(|(existing, _)| *existing == kind) {
+            Some((_, stored)) => *stored += amount,
+            None => self.food.push((kind, amount)),
+        }
+    }
+
+    pub fn add_material(&mut self, kind: MaterialKind, amount: u32) {
+        match kind {
+            MaterialKind::Wood => self.wood += amount,
+            MaterialKind::Stone => self.stone += amount,
+            MaterialKind::Metal => self.metal += amount,
+        }
+    }
+
+    /// 如"木材 3 金属 2"，空手时为"无"
+    pub fn summary(&self) -> String {
+        let mut entries = vec![("木材", self.wood), ("石头", self.stone)];
+        entries.extend(self.food.iter().map(|(kind, amount)| (kind.label(), *amount)));
+        entries.push(("金属", self.metal));
+        let parts: Vec<String> = entries
+            .iter()
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(label, amount)| format!("{} {}", label, amount))
+            .collect();
+        if parts.is_empty() {
+            "无".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+/// 矮人的装备栏与随身携带的资源（随存档保存）
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Equipment {
+    pub hands: Option<Item>,
+    pub body: Option<Item>,
+    pub back: Option<Item>,
+    pub cargo: Cargo,
+}
+
+impl Equipment {
+    /// 首批移民和移民潮的标准行装：一只背篓，外加与专长相符的装备
+    pub fn for_specialty(skill: SkillKind) -> Self {
+        let mut equipment = Self {
+            back: Some(Item::new(ItemKind::Backpack, ItemMaterial::Wood)),
+            ..default()
+        };
+        match skill {
+            SkillKind::Mining => equipment.hands = Some(Item::new(ItemKind::Pick, ItemMaterial::Iron)),
+            SkillKind::Woodcutting => equipment.hands = Some(Item::new(ItemKind::Axe, ItemMaterial::Iron)),
+            SkillKind::Fighting => {
+                equipment.hands = Some(Item::new(ItemKind::Weapon, ItemMaterial::Iron));
+                equipment.body = Some(Item::new(ItemKind::Armour, ItemMaterial::Copper));
+            }
+            _ => {}
+        }
+        equipment
+    }
+
+    pub fn slot(&self, slot: EquipSlot) -> Option<&Item> {
+        match slot {
+            EquipSlot::Hands => self.hands.as_ref(),
+            EquipSlot::Body => self.body.as_ref(),
+            EquipSlot::Back => self.back.as_ref(),
+        }
+    }
+
+    pub fn carry_capacity(&self) -> u32 {
+        match self.back {
+            Some(Item {
+                kind: ItemKind::Backpack,
+                ..
+            }) => BASE_CARRY_CAPACITY + BACKPACK_CAPACITY,
+            _ => BASE_CARRY_CAPACITY,
+        }
+    }
+
+    pub fn free_capacity(&self) -> u32 {
+        self.carry_capacity().saturating_sub(self.cargo.total())
+    }
+
+    /// 把材料装进货物，返回装不下的数量
+    pub fn stow_material(&mut self, kind: MaterialKind, amount: u32) -> u32 {
+        let stowed = amount.min(self.free_capacity());
+        self.cargo.add_material(kind, stowed);
+        amount - stowed
+    }
+
+    /// 把食物原料装进货物，返回装不下的数量
+    pub fn stow_food(&mut self, kind: FoodKind, amount: u32) -> u32 {
+        let stowed = amount.min(self.free_capacity());
+        self.cargo.add_food(kind, stowed);
+        amount - stowed
+    }
+
+    pub fn holds_weapon(&self) -> bool {
+        matches!(self.hands, Some(item) if item.kind == ItemKind::Weapon)
+    }
+
+    /// 手上是否拿着从事该劳动所需的工具（不需要工具的劳动总是满足）
+    pub fn has_tool_for(&self, labour: SkillKind) -> bool {
+        match labour.required_tool() {
+            Some(tool) => matches!(self.hands, Some(item) if item.kind == tool),
+            None => true,
+        }
+    }
+
+    /// 工具带来的工作速度倍率：需要工具的劳动按工具材质计算
+    pub fn tool_speed(&self, labour: SkillKind) -> f32 {
+        match (labour.required_tool(), self.hands) {
+            (Some(tool), Some(item)) if item.kind == tool => item.material.quality(),
+            _ => 1.0,
+        }
+    }
+
+    /// 战斗中的攻击倍率：武器最好，镐和斧也能凑合
+    pub fn attack_multiplier(&self) -> f32 {
+        match self.hands {
+            Some(item) if item.kind == ItemKind::Weapon => 1.0 + 0.8 * item.material.quality(),
+            Some(item) if matches!(item.kind, ItemKind::Pick | ItemKind::Axe) => 1.0 + 0.3 * item.material.quality(),
+            _ => 1.0,
+        }
+    }
+}
+
+/// 敌对生物种类
+#[allow(dead_code)] // 敌对生物由今后的野兽和袭击生成，小队的击杀命令以它们为目标
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostileKind {
+    Wolf,
+    GoblinRaider,
+}
+
+impl HostileKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HostileKind::Wolf => "狼",
+            HostileKind::GoblinRaider => "哥布林劫掠者",
+        }
+    }
+
+    pub fn max_health(&self) -> f32 {
+        match self {
+            HostileKind::Wolf => 30.0,
+            HostileKind::GoblinRaider => 50.0,
+        }
+    }
+
+    /// 杀死后能屠宰出的肉
+    pub fn meat(&self) -> u32 {
+        match self {
+            HostileKind::Wolf => 4,
+            HostileKind::GoblinRaider => 0,
+        }
+    }
+}
+
+/// 敌对生物：小队击杀命令的目标
+#[derive(Component, Clone, Debug)]
+pub struct Hostile {
+    pub kind: HostileKind,
+    pub health: f32,
+}
+
+impl Hostile {
+    #[allow(dead_code)]
+    pub fn new(kind: HostileKind) -> Self {
+        Self {
+            kind,
+            health: kind.max_health(),
         }
     }
 }
@@ -46,6 +1336,27 @@ pub struct WorkState {
     pub task_duration: f32,           // 当前任务持续时间（防止卡住）
 }
 
+/// 寻路请求：矮人正在等待路径（"思考中"），结果返回前保留
+#[derive(Component, Clone, Debug)]
+pub struct PathRequest {
+    pub start: (i32, i32),
+    pub goal: (i32, i32),
+}
+
+/// 寻路结果（由工作系统读取后移除）
+#[derive(Component, Clone, Debug)]
+pub struct PathResult {
+    pub goal: (i32, i32),
+    /// 不包含起点的路径；不可达时为 None
+    pub path: Option<Vec<(i32, i32)>>,
+}
+
+/// 沿共享流场前往目的地（移动系统据此决定方向，代替 cached_path）
+#[derive(Component, Clone, Debug)]
+pub struct FollowFlowField {
+    pub goal: (i32, i32),
+}
+
 /// UI标记组件
 #[derive(Component)]
 pub struct ResourceDisplay;
@@ -91,6 +1402,14 @@ pub struct MainMenuUI;
 #[derive(Component)]
 pub struct StartButton;
 
+/// 主菜单创建世界按钮
+#[derive(Component)]
+pub struct CreateWorldButton;
+
+/// 主菜单传说按钮
+#[derive(Component)]
+pub struct LegendsMenuButton;
+
 /// 暂停菜单UI标记
 #[derive(Component)]
 pub struct PauseMenuUI;
@@ -142,21 +1461,214 @@ pub struct GridLine;
 #[derive(Clone, Debug, PartialEq)]
 pub enum Task {
     Mining(GridPosition),
-    #[allow(dead_code)] // 保留用于未来建筑系统
-    Building(GridPosition, BuildingType),
+    Building(GridPosition, BuildingType), // 建造 - 在目标格子上建造家具或建筑结构
+    Sleep(GridPosition),                  // 睡觉 - 躺到床上或就地睡在地上
     Gathering(GridPosition),
     Wandering(GridPosition), // 闲逛 - 随机走动但不工作
+    Rally(GridPosition),     // 集合 - 沿流场前往集合点
+    Hauling(GridPosition),   // 搬运 - 把随身携带的资源运回仓库
+    Stand(GridPosition),     // 待命 - 前往指定位置并守在那里（驻守、巡逻、训练与警报避难）
+    Attack(Entity),          // 攻击 - 追击并攻击敌对生物
+    Crafting(GridPosition, WorkshopKind), // 作坊 - 在厨房烹饪或在酒坊酿酒
+    Fishing(GridPosition),   // 钓鱼 - 站在岸边朝目标水面垂钓
+    PickUp(GridPosition),    // 捡拾 - 把散落在地上的食物和材料捡起来运回仓库
     Idle,
 }
 
 /// 建筑类型
-#[derive(Clone, Debug, PartialEq)]
-#[allow(dead_code)] // 保留用于未来建筑系统扩展
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BuildingType {
-    Workshop,
+    Workshop(WorkshopKind),
+    #[allow(dead_code)] // 保留用于未来建筑系统扩展
     Stockpile,
+    #[allow(dead_code)] // 保留用于未来建筑系统扩展
     Farm,
-    LivingQuarters,
+    Furniture(FurnitureKind),
+    Construction(ConstructionKind),
+}
+
+impl BuildingType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BuildingType::Workshop(kind) => kind.label(),
+            BuildingType::Stockpile => "仓库",
+            BuildingType::Farm => "农田",
+            BuildingType::Furniture(kind) => kind.label(),
+            BuildingType::Construction(kind) => kind.label(),
+        }
+    }
+
+    /// 建造消耗的材料数量
+    pub fn material_cost(&self) -> u32 {
+        match self {
+            BuildingType::Construction(_) => CONSTRUCTION_MATERIAL_COST,
+            BuildingType::Workshop(_) => WORKSHOP_MATERIAL_COST,
+            _ => FURNITURE_MATERIAL_COST,
+        }
+    }
+}
+
+/// 每件家具消耗的材料数量
+pub const FURNITURE_MATERIAL_COST: u32 = 3;
+
+/// 家具种类：摆放在地块上，床和桌子决定房间的用途
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FurnitureKind {
+    Bed,
+    Table,
+    Chair,
+    Chest,
+}
+
+impl FurnitureKind {
+    pub const ALL: [FurnitureKind; 4] = [
+        FurnitureKind::Bed,
+        FurnitureKind::Table,
+        FurnitureKind::Chair,
+        FurnitureKind::Chest,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FurnitureKind::Bed => "床",
+            FurnitureKind::Table => "桌子",
+            FurnitureKind::Chair => "椅子",
+            FurnitureKind::Chest => "箱子",
+        }
+    }
+
+    /// 地图上显示的字符
+    pub fn glyph(&self) -> char {
+        match self {
+            FurnitureKind::Bed => 'Θ',
+            FurnitureKind::Table => '╥',
+            FurnitureKind::Chair => 'h',
+            FurnitureKind::Chest => '▄',
+        }
+    }
+
+    /// 对房间品质的基础贡献，再乘以材质品质
+    pub fn value(&self) -> f32 {
+        match self {
+            FurnitureKind::Bed => 10.0,
+            FurnitureKind::Table => 8.0,
+            FurnitureKind::Chair => 5.0,
+            FurnitureKind::Chest => 6.0,
+        }
+    }
+}
+
+/// 每座作坊消耗的材料数量
+pub const WORKSHOP_MATERIAL_COST: u32 = 5;
+
+/// 作坊种类：厨房把食物原料做成饭菜，酒坊把谷物和野果酿成酒
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkshopKind {
+    Kitchen,
+    Still,
+}
+
+impl WorkshopKind {
+    pub const ALL: [WorkshopKind; 2] = [WorkshopKind::Kitchen, WorkshopKind::Still];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WorkshopKind::Kitchen => "厨房",
+            WorkshopKind::Still => "酒坊",
+        }
+    }
+
+    /// 地图上显示的字符
+    pub fn glyph(&self) -> char {
+        match self {
+            WorkshopKind::Kitchen => '¤',
+            WorkshopKind::Still => '§',
+        }
+    }
+
+    /// 在作坊里干活时显示的动作
+    pub fn action(&self) -> &'static str {
+        match self {
+            WorkshopKind::Kitchen => "烹饪",
+            WorkshopKind::Still => "酿酒",
+        }
+    }
+}
+
+/// 每处建筑结构消耗的材料数量
+pub const CONSTRUCTION_MATERIAL_COST: u32 = 2;
+
+/// 建筑结构：直接改变地块本身，决定能否通行以及移动代价；门同时围出房间
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstructionKind {
+    Wall,
+    Floor,
+    Road,
+    Bridge,
+    Door,
+}
+
+impl ConstructionKind {
+    pub const ALL: [ConstructionKind; 5] = [
+        ConstructionKind::Wall,
+        ConstructionKind::Floor,
+        ConstructionKind::Road,
+        ConstructionKind::Bridge,
+        ConstructionKind::Door,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConstructionKind::Wall => "墙",
+            ConstructionKind::Floor => "地板",
+            ConstructionKind::Road => "道路",
+            ConstructionKind::Bridge => "桥",
+            ConstructionKind::Door => "门",
+        }
+    }
+
+    /// 建好后的移动速度倍率，取代原地形的速度
+    pub fn movement_speed(&self) -> f32 {
+        match self {
+            ConstructionKind::Wall => 0.0,   // 墙挡住去路
+            ConstructionKind::Floor => 1.0,  // 地板平整
+            ConstructionKind::Road => 1.5,   // 铺好的路走得更快
+            ConstructionKind::Bridge => 1.0, // 桥让水面可以通行
+            ConstructionKind::Door => 0.9,   // 开门略慢
+        }
+    }
+
+    pub fn walkable(&self) -> bool {
+        *self != ConstructionKind::Wall
+    }
+
+    /// 能否建在该地形上：桥只能架在水上，其余只能建在平地上（树苗会被清掉）
+    pub fn can_build_on(&self, terrain: TerrainType) -> bool {
+        match self {
+            ConstructionKind::Bridge => terrain == TerrainType::Water,
+            _ => matches!(
+                terrain,
+                TerrainType::Grass | TerrainType::Stone | TerrainType::Dirt | TerrainType::Sapling
+            ),
+        }
+    }
+}
+
+/// 已建成的建筑结构
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Construction {
+    pub kind: ConstructionKind,
+    pub material: ItemMaterial,
+}
+
+impl Construction {
+    /// 对房间品质的贡献：只有门计入，再乘以材质品质
+    pub fn room_value(&self) -> f32 {
+        match self.kind {
+            ConstructionKind::Door => 4.0 * self.material.quality(),
+            _ => 0.0,
+        }
+    }
 }
 
 /// 建筑组件
@@ -176,6 +1688,8 @@ pub enum TerrainType {
     Tree,
     Water,
     Mountain,
+    Dirt,    // 泥土 - 砍树留下或被踩秃的土地，没人踩时会重新长草
+    Sapling, // 树苗 - 从成年树木旁长出，长大后成为树木
 }
 
 impl TerrainType {
@@ -187,6 +1701,7 @@ impl TerrainType {
             TerrainType::Mountain => 1.8, // 山脉挖矿效率最高
             TerrainType::Water => 0.8,    // 水边采集效率略低
             TerrainType::Grass => 1.0,    // 草地标准效率
+            TerrainType::Dirt | TerrainType::Sapling => 0.0, // 没有可采集的资源
         }
     }
 
@@ -198,6 +1713,19 @@ impl TerrainType {
             TerrainType::Tree => 0.8,     // 森林较慢
             TerrainType::Water => 0.0,    // 水域无法通行
             TerrainType::Mountain => 0.0, // 山脉无法通行
+            TerrainType::Dirt => 1.1,     // 踩出来的土路略快
+            TerrainType::Sapling => 0.9,  // 树苗丛略慢
+        }
+    }
+
+    /// 在该地形上工作对应的劳动/技能
+    pub fn labour(&self) -> SkillKind {
+        match self {
+            TerrainType::Tree => SkillKind::Woodcutting,
+            TerrainType::Stone | TerrainType::Mountain => SkillKind::Mining,
+            TerrainType::Grass | TerrainType::Dirt => SkillKind::Farming,
+            TerrainType::Sapling => SkillKind::Woodcutting,
+            TerrainType::Water => SkillKind::Fishing,
         }
     }
 
@@ -206,11 +1734,18 @@ impl TerrainType {
         match self {
             TerrainType::Grass => "草地 - 适合采集食物",
             TerrainType::Stone => "石地 - 适合采集石头",
-            TerrainType::Tree => "森林 - 富含木材和食物",
+            TerrainType::Tree => "森林 - 砍伐得到木材，会向周围播种",
             TerrainType::Water => "水域 - 可以钓鱼",
             TerrainType::Mountain => "山脉 - 富含矿石和金属",
+            TerrainType::Dirt => "泥土 - 没人踩时会重新长草",
+            TerrainType::Sapling => "树苗 - 长大后可以砍伐",
         }
     }
+
+    /// 是否可以作为采集或挖矿目标
+    pub fn is_harvestable(&self) -> bool {
+        !matches!(self, TerrainType::Dirt | TerrainType::Sapling)
+    }
 }
 
 /// 地形tile
@@ -219,4 +1754,15 @@ pub struct Terrain {
     pub terrain_type: TerrainType,
     pub walkable: bool,
     pub resource_richness: f32, // 资源丰富度 0.5-1.5
+    pub construction: Option<Construction>,
+}
+
+impl Terrain {
+    /// 移动速度倍率：建筑结构取代原地形的速度
+    pub fn movement_speed(&self) -> f32 {
+        match self.construction {
+            Some(construction) => construction.kind.movement_speed(),
+            None => self.terrain_type.movement_speed(),
+        }
+    }
 }
@@ -0,0 +1,345 @@
+// 局部地图连通性分析：用洪水填充划分可行走区域，
+// 为每个格子标注区域ID，并修复主区域过小的地图
+
+use crate::components::TerrainType;
+use crate::world::{WORLD_HEIGHT, WORLD_WIDTH};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// 主区域至少应占可行走格子的比例，低于该值时尝试修复
+pub const MIN_MAIN_REGION_FRACTION: f32 = 0.6;
+/// 主区域至少应占整张地图的比例，修复后仍低于该值的地块不适合出发
+pub const MIN_PLAYABLE_FRACTION: f32 = 0.2;
+/// 小于该格数的孤立区域不值得开辟通道
+const MIN_REPAIR_REGION_SIZE: usize = 12;
+
+/// 局部地图的连通区域划分（四连通，与寻路一致）
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RegionMap {
+    /// 每个格子所属的区域ID，索引为 y * WORLD_WIDTH + x；不可行走为 None
+    regions: Vec<Option<u32>>,
+    /// 每个区域的格数
+    sizes: Vec<usize>,
+}
+
+impl RegionMap {
+    /// 由可行走网格（索引为 y * WORLD_WIDTH + x）计算连通区域
+    pub fn from_walkable(walkable: &[bool]) -> Self {
+        let mut regions = vec![None; walkable.len()];
+        let mut sizes = Vec::new();
+
+        for start in 0..walkable.len() {
+            if !walkable[start] || regions[start].is_some() {
+                continue;
+            }
+            let id = sizes.len() as u32;
+            regions[start] = Some(id);
+            let mut size = 0;
+            let mut stack = vec![start];
+            while let Some(index) = stack.pop() {
+                size += 1;
+                for neighbour in neighbours(index) {
+                    if walkable[neighbour] && regions[neighbour].is_none() {
+                        regions[neighbour] = Some(id);
+                        stack.push(neighbour);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+
+        Self { regions, sizes }
+    }
+
+    /// 由地形网格计算连通区域
+    pub fn from_terrain(terrain: &[TerrainType]) -> Self {
+        let walkable: Vec<bool> = terrain.iter().map(|t| is_walkable_terrain(*t)).collect();
+        Self::from_walkable(&walkable)
+    }
+
+    /// 增量更新一个格子的可行走状态：
+    /// 变为可行走时合并相邻区域，变为不可行走时只重新划分原区域。返回是否发生了变化
+    pub fn set_walkable(&mut self, x: i32, y: i32, walkable: bool) -> bool {
+        let Some(index) = tile_index(x, y).filter(|index| *index < self.regions.len()) else {
+            return false;
+        };
+        if self.regions[index].is_some() == walkable {
+            return false;
+        }
+
+        if walkable {
+            let mut adjacent: Vec<u32> = neighbours(index).filter_map(|n| self.regions[n]).collect();
+            adjacent.sort_unstable();
+            adjacent.dedup();
+            let Some(target) = adjacent.iter().copied().max_by_key(|id| self.region_size(*id)) else {
+                self.regions[index] = Some(self.sizes.len() as u32);
+                self.sizes.push(1);
+                return true;
+            };
+            for region in self.regions.iter_mut() {
+                if let Some(id) = *region {
+                    if id != target && adjacent.contains(&id) {
+                        *region = Some(target);
+                    }
+                }
+            }
+            for id in adjacent.iter().filter(|id| **id != target) {
+                self.sizes[target as usize] += self.sizes[*id as usize];
+                self.sizes[*id as usize] = 0;
+            }
+            self.regions[index] = Some(target);
+            self.sizes[target as usize] += 1;
+        } else {
+            let Some(old) = self.regions[index].take() else {
+                return false;
+            };
+            // 原区域可能被切断，重新填充；第一块沿用原ID，其余分配新ID
+            let mut member = vec![false; self.regions.len()];
+            let mut seeds = Vec::new();
+            for (i, region) in self.regions.iter_mut().enumerate() {
+                if *region == Some(old) {
+                    *region = None;
+                    member[i] = true;
+                    seeds.push(i);
+                }
+            }
+            self.sizes[old as usize] = 0;
+            let mut next_id = Some(old);
+            for seed in seeds {
+                if self.regions[seed].is_some() {
+                    continue;
+                }
+                let id = next_id.take().unwrap_or_else(|| {
+                    self.sizes.push(0);
+                    (self.sizes.len() - 1) as u32
+                });
+                self.regions[seed] = Some(id);
+                let mut stack = vec![seed];
+                while let Some(current) = stack.pop() {
+                    self.sizes[id as usize] += 1;
+                    for neighbour in neighbours(current) {
+                        if member[neighbour] && self.regions[neighbour].is_none() {
+                            self.regions[neighbour] = Some(id);
+                            stack.push(neighbour);
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// 指定格子所属的区域ID（越界或不可行走返回 None）
+    pub fn region_at(&self, x: i32, y: i32) -> Option<u32> {
+        tile_index(x, y).and_then(|index| self.regions.get(index).copied().flatten())
+    }
+
+    /// 两个格子是否连通（不连通时无需运行A*）
+    pub fn connected(&self, from: (i32, i32), to: (i32, i32)) -> bool {
+        match (self.region_at(from.0, from.1), self.region_at(to.0, to.1)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// 最大区域的ID
+    pub fn largest_region(&self) -> Option<u32> {
+        self.sizes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, size)| **size)
+            .map(|(id, _)| id as u32)
+    }
+
+    pub fn region_size(&self, id: u32) -> usize {
+        self.sizes.get(id as usize).copied().unwrap_or(0)
+    }
+
+    pub fn region_count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// 区域内的全部格子
+    pub fn tiles_in(&self, id: u32) -> Vec<(i32, i32)> {
+        self.regions
+            .iter()
+            .enumerate()
+            .filter(|(_, region)| **region == Some(id))
+            .map(|(index, _)| tile_coord(index))
+            .collect()
+    }
+
+    /// 可行走格子总数
+    pub fn walkable_count(&self) -> usize {
+        self.sizes.iter().sum()
+    }
+
+    /// 最大区域占整张地图的比例
+    pub fn main_region_fraction(&self) -> f32 {
+        let largest = self.largest_region().map(|id| self.region_size(id)).unwrap_or(0);
+        largest as f32 / (WORLD_WIDTH * WORLD_HEIGHT) as f32
+    }
+
+    /// 主区域是否足够大，适合作为出发地点
+    pub fn is_playable(&self) -> bool {
+        self.main_region_fraction() >= MIN_PLAYABLE_FRACTION
+    }
+}
+
+/// 地形是否可行走
+pub fn is_walkable_terrain(terrain: TerrainType) -> bool {
+    !matches!(terrain, TerrainType::Water | TerrainType::Mountain)
+}
+
+/// 修复主区域过小的地图：从较大的孤立区域向主区域开辟最短通道
+/// （水域填成浅滩草地，山脉凿成石地）。返回被改动的格子数
+pub fn repair_connectivity(terrain: &mut [TerrainType]) -> usize {
+    let mut changed = 0;
+
+    loop {
+        let regions = RegionMap::from_terrain(terrain);
+        let Some(main) = regions.largest_region() else {
+            return changed;
+        };
+        let main_size = regions.region_size(main);
+        if main_size as f32 >= regions.walkable_count() as f32 * MIN_MAIN_REGION_FRACTION {
+            return changed;
+        }
+
+        // 连接最大的孤立区域
+        let Some(island) = (0..regions.region_count() as u32)
+            .filter(|id| *id != main && regions.region_size(*id) >= MIN_REPAIR_REGION_SIZE)
+            .max_by_key(|id| regions.region_size(*id))
+        else {
+            return changed;
+        };
+
+        let Some(corridor) = shortest_corridor(&regions, island, main) else {
+            return changed;
+        };
+        for index in corridor {
+            terrain[index] = match terrain[index] {
+                TerrainType::Mountain => TerrainType::Stone,
+                TerrainType::Water => TerrainType::Grass,
+                other => other,
+            };
+            changed += 1;
+        }
+    }
+}
+
+/// 从孤立区域出发做广度优先搜索，返回连到主区域所需穿越的不可行走格子
+fn shortest_corridor(regions: &RegionMap, from: u32, to: u32) -> Option<Vec<usize>> {
+    let total = (WORLD_WIDTH * WORLD_HEIGHT) as usize;
+    let mut came_from: Vec<Option<usize>> = vec![None; total];
+    let mut visited = vec![false; total];
+    let mut queue = VecDeque::new();
+
+    for (index, region) in regions.regions.iter().enumerate() {
+        if *region == Some(from) {
+            visited[index] = true;
+            queue.push_back(index);
+        }
+    }
+
+    while let Some(index) = queue.pop_front() {
+        if regions.regions[index] == Some(to) {
+            let mut corridor = Vec::new();
+            let mut current = came_from[index];
+            while let Some(step) = current {
+                if regions.regions[step].is_none() {
+                    corridor.push(step);
+                }
+                current = came_from[step];
+            }
+            return Some(corridor);
+        }
+        for neighbour in neighbours(index) {
+            if !visited[neighbour] {
+                visited[neighbour] = true;
+                came_from[neighbour] = Some(index);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    None
+}
+
+fn tile_index(x: i32, y: i32) -> Option<usize> {
+    if (0..WORLD_WIDTH).contains(&x) && (0..WORLD_HEIGHT).contains(&y) {
+        Some((y * WORLD_WIDTH + x) as usize)
+    } else {
+        None
+    }
+}
+
+fn tile_coord(index: usize) -> (i32, i32) {
+    (index as i32 % WORLD_WIDTH, index as i32 / WORLD_WIDTH)
+}
+
+/// 四个正交方向上的相邻格子索引
+fn neighbours(index: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = tile_coord(index);
+    [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+        .into_iter()
+        .filter_map(|(nx, ny)| tile_index(nx, ny))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALL_X: i32 = WORLD_WIDTH / 2;
+    const GAP_Y: i32 = WORLD_HEIGHT / 2;
+
+    /// 增量更新的结果应与整张重新划分的结果一致
+    fn assert_matches_full_rebuild(regions: &RegionMap) {
+        let walkable: Vec<bool> = regions.regions.iter().map(Option::is_some).collect();
+        let rebuilt = RegionMap::from_walkable(&walkable);
+        for index in 0..walkable.len() {
+            for other in [0, walkable.len() - 1, index.saturating_sub(1)] {
+                let (a, b) = (tile_coord(index), tile_coord(other));
+                assert_eq!(regions.connected(a, b), rebuilt.connected(a, b), "{:?} 与 {:?}", a, b);
+            }
+        }
+        let mut sizes: Vec<usize> = regions.sizes.iter().copied().filter(|size| *size > 0).collect();
+        let mut expected: Vec<usize> = rebuilt.sizes.clone();
+        sizes.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(sizes, expected);
+    }
+
+    #[test]
+    fn set_walkable_splits_and_merges_regions() {
+        let total = (WORLD_WIDTH * WORLD_HEIGHT) as usize;
+        let mut regions = RegionMap::from_walkable(&vec![true; total]);
+        let (left, right) = ((0, 0), (WORLD_WIDTH - 1, WORLD_HEIGHT - 1));
+
+        // 竖墙留一个缺口：仍然连通
+        for y in (0..WORLD_HEIGHT).filter(|y| *y != GAP_Y) {
+            assert!(regions.set_walkable(WALL_X, y, false));
+        }
+        assert!(regions.connected(left, right));
+        assert_matches_full_rebuild(&regions);
+
+        // 堵上缺口：分成左右两块
+        assert!(regions.set_walkable(WALL_X, GAP_Y, false));
+        assert!(!regions.connected(left, right));
+        let left_id = regions.region_at(left.0, left.1).unwrap();
+        let right_id = regions.region_at(right.0, right.1).unwrap();
+        assert_eq!(regions.region_size(left_id), (WALL_X * WORLD_HEIGHT) as usize);
+        assert_eq!(regions.region_size(right_id), ((WORLD_WIDTH - WALL_X - 1) * WORLD_HEIGHT) as usize);
+        assert_matches_full_rebuild(&regions);
+
+        // 重复设置不算变化
+        assert!(!regions.set_walkable(WALL_X, GAP_Y, false));
+
+        // 重新打通：合并回一块
+        assert!(regions.set_walkable(WALL_X, GAP_Y, true));
+        assert!(regions.connected(left, right));
+        let merged = regions.region_at(left.0, left.1).unwrap();
+        assert_eq!(regions.region_size(merged), total - WORLD_HEIGHT as usize + 1);
+        assert_matches_full_rebuild(&regions);
+    }
+}
@@ -0,0 +1,121 @@
+// 流场寻路：为多名矮人共同前往的目的地（集合点、仓库、警报时的活动区域）
+// 从目的地反向做一次加权 Dijkstra，得到整张地图到目的地的代价，
+// 之后每名矮人只需查看相邻格子即可前进，不再各自运行A*
+
+use crate::pathfinding::NavGrid;
+use crate::world::{WORLD_HEIGHT, WORLD_WIDTH};
+use bevy::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// 最多缓存的流场数量，超出后淘汰最久未使用的
+const MAX_CACHED_FLOW_FIELDS: usize = 8;
+
+/// 单个目的地的流场
+#[derive(Clone, Debug)]
+pub struct FlowField {
+    /// 每个格子到目的地的累计代价，索引为 y * WORLD_WIDTH + x；无法到达为 None
+    costs: Vec<Option<u32>>,
+}
+
+impl FlowField {
+    /// 以导航网格的地形代价（进入格子的代价）从目的地反向计算
+    pub fn build(goal: (i32, i32), grid: &NavGrid) -> Self {
+        let mut costs = vec![None; (WORLD_WIDTH * WORLD_HEIGHT) as usize];
+        let mut open = BinaryHeap::new();
+
+        if let Some(index) = tile_index(goal.0, goal.1).filter(|_| grid.is_walkable(goal.0, goal.1)) {
+            costs[index] = Some(0);
+            open.push(Reverse((0u32, goal)));
+        }
+
+        while let Some(Reverse((cost, (x, y)))) = open.pop() {
+            if costs[(y * WORLD_WIDTH + x) as usize].is_some_and(|best| best < cost) {
+                continue;
+            }
+            // 从邻格走进当前格的代价
+            let enter_cost = grid.step_cost_at(x, y);
+            for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                let Some(neighbour) = tile_index(nx, ny) else {
+                    continue;
+                };
+                if !grid.is_walkable(nx, ny) {
+                    continue;
+                }
+                let next_cost = cost + enter_cost;
+                if costs[neighbour].is_none_or(|best| next_cost < best) {
+                    costs[neighbour] = Some(next_cost);
+                    open.push(Reverse((next_cost, (nx, ny))));
+                }
+            }
+        }
+
+        Self { costs }
+    }
+
+    /// 指定格子到目的地的累计代价
+    pub fn cost_at(&self, x: i32, y: i32) -> Option<u32> {
+        tile_index(x, y).and_then(|index| self.costs[index])
+    }
+
+    /// 从指定格子出发的下一步（已在目的地或无法到达时返回 None）
+    pub fn next_step(&self, from: (i32, i32)) -> Option<(i32, i32)> {
+        let current = self.cost_at(from.0, from.1)?;
+        [
+            (from.0 + 1, from.1),
+            (from.0 - 1, from.1),
+            (from.0, from.1 + 1),
+            (from.0, from.1 - 1),
+        ]
+        .into_iter()
+        .filter_map(|(x, y)| self.cost_at(x, y).map(|cost| (cost, (x, y))))
+        .filter(|(cost, _)| *cost < current)
+        .min_by_key(|(cost, _)| *cost)
+        .map(|(_, step)| step)
+    }
+}
+
+/// 热门目的地的流场缓存，地形变化后整体失效
+#[derive(Resource, Default)]
+pub struct FlowFieldCache {
+    /// 目的地 -> (流场, 最近一次使用的时刻)
+    fields: HashMap<(i32, i32), (FlowField, u64)>,
+    clock: u64,
+}
+
+impl FlowFieldCache {
+    /// 获取目的地的流场，缓存中没有时立即计算
+    pub fn field(&mut self, goal: (i32, i32), grid: &NavGrid) -> &FlowField {
+        self.clock += 1;
+        if !self.fields.contains_key(&goal) && self.fields.len() >= MAX_CACHED_FLOW_FIELDS {
+            if let Some(oldest) = self
+                .fields
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(goal, _)| *goal)
+            {
+                self.fields.remove(&oldest);
+            }
+        }
+        let clock = self.clock;
+        let entry = self
+            .fields
+            .entry(goal)
+            .or_insert_with(|| (FlowField::build(goal, grid), clock));
+        entry.1 = clock;
+        &entry.0
+    }
+
+    /// 地形变化后丢弃所有流场
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+}
+
+fn tile_index(x: i32, y: i32) -> Option<usize> {
+    if (0..WORLD_WIDTH).contains(&x) && (0..WORLD_HEIGHT).contains(&y) {
+        Some((y * WORLD_WIDTH + x) as usize)
+    } else {
+        None
+    }
+}
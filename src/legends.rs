@@ -0,0 +1,379 @@
+// 传说数据 - 将世界历史与要塞编年史整理为可浏览、可搜索、可导出的条目
+
+use crate::resources::{ChronicleEntry, FortressChronicle};
+use crate::world_history::{HistoricalEvent, WorldHistory};
+use serde_json::{json, Value};
+
+/// 传说分类
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegendCategory {
+    Civilizations,
+    Figures,
+    Sites,
+    Events,
+    Chronicle,
+}
+
+impl LegendCategory {
+    pub const ALL: [LegendCategory; 5] = [
+        LegendCategory::Civilizations,
+        LegendCategory::Figures,
+        LegendCategory::Sites,
+        LegendCategory::Events,
+        LegendCategory::Chronicle,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LegendCategory::Civilizations => "文明",
+            LegendCategory::Figures => "历史人物",
+            LegendCategory::Sites => "地点",
+            LegendCategory::Events => "历史事件",
+            LegendCategory::Chronicle => "要塞编年史",
+        }
+    }
+}
+
+/// 指向某条传说条目的引用（用于列表与交叉链接）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegendRef {
+    Civilization(usize),
+    Figure(usize),
+    Site(usize),
+    Event(usize),
+    Chronicle(usize),
+}
+
+impl LegendRef {
+    pub fn category(&self) -> LegendCategory {
+        match self {
+            LegendRef::Civilization(_) => LegendCategory::Civilizations,
+            LegendRef::Figure(_) => LegendCategory::Figures,
+            LegendRef::Site(_) => LegendCategory::Sites,
+            LegendRef::Event(_) => LegendCategory::Events,
+            LegendRef::Chronicle(_) => LegendCategory::Chronicle,
+        }
+    }
+}
+
+/// 条目详情：正文与可跳转的链接
+pub struct LegendDetail {
+    pub text: String,
+    pub links: Vec<(LegendRef, String)>,
+}
+
+/// 列出某个分类下匹配搜索词的条目（搜索不区分大小写）
+pub fn list_entries(
+    history: &WorldHistory,
+    chronicle: &FortressChronicle,
+    category: LegendCategory,
+    query: &str,
+) -> Vec<(LegendRef, String)> {
+    let entries: Vec<(LegendRef, String)> = match category {
+        LegendCategory::Civilizations => history
+            .civilizations
+            .iter()
+            .map(|civ| {
+                let status = if civ.is_alive() { "" } else { " (已灭亡)" };
+                (
+                    LegendRef::Civilization(civ.id),
+                    format!("{} [{}]{}", civ.name, civ.race.label(), status),
+                )
+            })
+            .collect(),
+        LegendCategory::Figures => history
+            .figures
+            .iter()
+            .map(|figure| {
+                (
+                    LegendRef::Figure(figure.id),
+                    format!("{} ({}·{})", figure.name, figure.race.label(), figure.role.label()),
+                )
+            })
+            .collect(),
+        LegendCategory::Sites => history
+            .settlements
+            .iter()
+            .map(|site| {
+                let owner = history
+                    .civilization(site.owner)
+                    .map(|civ| civ.name.as_str())
+                    .unwrap_or("无主");
+                (LegendRef::Site(site.id), format!("{} - {}", site.name, owner))
+            })
+            .collect(),
+        LegendCategory::Events => history
+            .events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| (LegendRef::Event(index), history.describe_event(event)))
+            .collect(),
+        LegendCategory::Chronicle => chronicle
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (LegendRef::Chronicle(index), format_chronicle_entry(entry)))
+            .collect(),
+    };
+
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return entries;
+    }
+    entries
+        .into_iter()
+        .filter(|(_, label)| label.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// 构建条目详情及交叉链接
+pub fn describe(history: &WorldHistory, chronicle: &FortressChronicle, entry: LegendRef) -> LegendDetail {
+    let mut links = Vec::new();
+    let text = match entry {
+        LegendRef::Civilization(id) => {
+            let Some(civ) = history.civilization(id) else {
+                return missing_detail();
+            };
+            let mut text = format!(
+                "{}\n种族: {}\n建立: 第{}年",
+                civ.name,
+                civ.race.label(),
+                civ.founded_year
+            );
+            if let Some(year) = civ.fallen_year {
+                text.push_str(&format!("\n灭亡: 第{}年", year));
+            }
+            if let Some(ruler) = civ.ruler.and_then(|id| history.figure(id)) {
+                text.push_str(&format!("\n统治者: {}", ruler.name));
+                links.push((LegendRef::Figure(ruler.id), format!("统治者 {}", ruler.name)));
+            }
+            if let Some(capital) = civ.capital.and_then(|id| history.settlement(id)) {
+                text.push_str(&format!("\n首都: {}", capital.name));
+            }
+            for site in history.settlements.iter().filter(|site| site.owner == id) {
+                links.push((LegendRef::Site(site.id), format!("聚落 {}", site.name)));
+            }
+            for enemy in civ.at_war_with.iter().filter_map(|id| history.civilization(*id)) {
+                links.push((LegendRef::Civilization(enemy.id), format!("交战 {}", enemy.name)));
+            }
+            push_event_links(history, &mut links, |event| {
+                event.kind.civilizations().contains(&id)
+            });
+            text
+        }
+        LegendRef::Figure(id) => {
+            let Some(figure) = history.figure(id) else {
+                return missing_detail();
+            };
+            let mut text = format!(
+                "{}\n种族: {}\n身份: {}\n出生: 第{}年",
+                figure.name,
+                figure.race.label(),
+                figure.role.label(),
+                figure.birth_year
+            );
+            match figure.death_year {
+                Some(year) => text.push_str(&format!("\n逝世: 第{}年", year)),
+                None => text.push_str("\n状态: 在世"),
+            }
+            if let Some(civ) = history.civilization(figure.civ) {
+                links.push((LegendRef::Civilization(civ.id), format!("所属 {}", civ.name)));
+            }
+            if let Some(killer) = figure.slain_by.and_then(|id| history.figure(id)) {
+                text.push_str(&format!("\n被{}所杀", killer.name));
+                links.push((LegendRef::Figure(killer.id), format!("凶手 {}", killer.name)));
+            }
+            push_event_links(history, &mut links, |event| event.kind.figures().contains(&id));
+            text
+        }
+        LegendRef::Site(id) => {
+            let Some(site) = history.settlement(id) else {
+                return missing_detail();
+            };
+            let text = format!(
+                "{}\n坐标: ({}, {})\n人口: {}\n建立: 第{}年{}",
+                site.name,
+                site.coord.x,
+                site.coord.y,
+                site.population,
+                site.founded_year,
+                if site.is_capital { "\n(首都)" } else { "" }
+            );
+            if let Some(owner) = history.civilization(site.owner) {
+                links.push((LegendRef::Civilization(owner.id), format!("归属 {}", owner.name)));
+            }
+            if site.founder != site.owner {
+                if let Some(founder) = history.civilization(site.founder) {
+                    links.push((LegendRef::Civilization(founder.id), format!("建立者 {}", founder.name)));
+                }
+            }
+            push_event_links(history, &mut links, |event| {
+                event.kind.settlements().contains(&id)
+            });
+            text
+        }
+        LegendRef::Event(index) => {
+            let Some(event) = history.events.get(index) else {
+                return missing_detail();
+            };
+            for civ in event.kind.civilizations().into_iter().filter_map(|id| history.civilization(id)) {
+                links.push((LegendRef::Civilization(civ.id), civ.name.clone()));
+            }
+            for site in event.kind.settlements().into_iter().filter_map(|id| history.settlement(id)) {
+                links.push((LegendRef::Site(site.id), site.name.clone()));
+            }
+            for figure in event.kind.figures().into_iter().filter_map(|id| history.figure(id)) {
+                links.push((LegendRef::Figure(figure.id), figure.name.clone()));
+            }
+            history.describe_event(event)
+        }
+        LegendRef::Chronicle(index) => {
+            let Some(entry) = chronicle.entries.get(index) else {
+                return missing_detail();
+            };
+            format!(
+                "要塞编年史\n时间: 第{}天 {}时\n类型: {}\n\n{}",
+                entry.day,
+                entry.hour,
+                entry.kind.label(),
+                entry.text
+            )
+        }
+    };
+
+    LegendDetail { text, links }
+}
+
+/// 导出完整传说为纯文本
+pub fn export_text(history: &WorldHistory, chronicle: &FortressChronicle) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("=== 世界传说 ({}年历史) ===\n", history.years));
+
+    for category in LegendCategory::ALL {
+        out.push_str(&format!("\n━━━ {} ━━━\n", category.label()));
+        for (entry, _) in list_entries(history, chronicle, category, "") {
+            out.push_str(&describe(history, chronicle, entry).text.replace('\n', " | "));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// 导出完整传说为JSON
+pub fn export_json(history: &WorldHistory, chronicle: &FortressChronicle) -> String {
+    let civilizations: Vec<Value> = history
+        .civilizations
+        .iter()
+        .map(|civ| {
+            json!({
+                "id": civ.id,
+                "name": civ.name,
+                "race": civ.race.label(),
+                "founded_year": civ.founded_year,
+                "fallen_year": civ.fallen_year,
+                "capital": civ.capital,
+                "ruler": civ.ruler,
+                "at_war_with": civ.at_war_with,
+            })
+        })
+        .collect();
+    let figures: Vec<Value> = history
+        .figures
+        .iter()
+        .map(|figure| {
+            json!({
+                "id": figure.id,
+                "name": figure.name,
+                "race": figure.race.label(),
+                "civilization": figure.civ,
+                "role": figure.role.label(),
+                "birth_year": figure.birth_year,
+                "death_year": figure.death_year,
+                "slain_by": figure.slain_by,
+            })
+        })
+        .collect();
+    let sites: Vec<Value> = history
+        .settlements
+        .iter()
+        .map(|site| {
+            json!({
+                "id": site.id,
+                "name": site.name,
+                "x": site.coord.x,
+                "y": site.coord.y,
+                "owner": site.owner,
+                "founder": site.founder,
+                "founded_year": site.founded_year,
+                "population": site.population,
+                "is_capital": site.is_capital,
+            })
+        })
+        .collect();
+    let events: Vec<Value> = history
+        .events
+        .iter()
+        .map(|event| event_json(history, event))
+        .collect();
+    let fortress: Vec<Value> = chronicle
+        .entries
+        .iter()
+        .map(|entry| {
+            json!({
+                "day": entry.day,
+                "hour": entry.hour,
+                "kind": entry.kind.label(),
+                "text": entry.text,
+            })
+        })
+        .collect();
+
+    let root = json!({
+        "history_years": history.years,
+        "civilizations": civilizations,
+        "historical_figures": figures,
+        "sites": sites,
+        "events": events,
+        "fortress_chronicle": fortress,
+    });
+    serde_json::to_string_pretty(&root).unwrap_or_default()
+}
+
+fn event_json(history: &WorldHistory, event: &HistoricalEvent) -> Value {
+    json!({
+        "year": event.year,
+        "description": history.describe_event(event),
+        "civilizations": event.kind.civilizations(),
+        "sites": event.kind.settlements(),
+        "figures": event.kind.figures(),
+    })
+}
+
+fn push_event_links(
+    history: &WorldHistory,
+    links: &mut Vec<(LegendRef, String)>,
+    involves: impl Fn(&HistoricalEvent) -> bool,
+) {
+    for (index, event) in history.events.iter().enumerate() {
+        if involves(event) {
+            links.push((LegendRef::Event(index), history.describe_event(event)));
+        }
+    }
+}
+
+fn format_chronicle_entry(entry: &ChronicleEntry) -> String {
+    format!(
+        "第{}天 {}时 [{}] {}",
+        entry.day,
+        entry.hour,
+        entry.kind.label(),
+        entry.text
+    )
+}
+
+fn missing_detail() -> LegendDetail {
+    LegendDetail {
+        text: "条目不存在".to_string(),
+        links: Vec::new(),
+    }
+}
@@ -3,13 +3,18 @@ use bevy::prelude::*;
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 
 mod components;
+mod connectivity;
 mod debug_config;
+mod flow_field;
+mod legends;
 mod logger;
+mod name_generator;
 mod pathfinding;
 mod resources;
 mod systems;
 mod ui_framework;
 mod world;
+mod world_history;
 mod world_map_data;
 
 use resources::*;
@@ -44,13 +49,32 @@ fn main() {
         .init_resource::<GameWorld>()
         .init_resource::<GameTime>()
         .init_resource::<SelectedDwarf>()
+        .init_resource::<TileReservations>()  // 工作目标格子预约
+        .init_resource::<CurrentWeather>()  // 局部地图天气
         .init_resource::<GlobalInventory>()
         .init_resource::<GameInitialized>()
     .init_resource::<WorldSeed>()  // 世界生成种子
     .init_resource::<AtlasSelection>()
         .init_resource::<ActiveLocalMap>()
         .init_resource::<GeneratedMapsRegistry>()  // 已生成地图注册表
+        .init_resource::<connectivity::RegionMap>()  // 局部地图连通区域
+        .init_resource::<pathfinding::NavGrid>()  // 分层寻路导航网格
+        .init_resource::<NavGridSnapshot>()  // 异步寻路共享的导航网格快照
+        .init_resource::<flow_field::FlowFieldCache>()  // 热门目的地的流场缓存
         .init_resource::<logger::GameLogger>()  // 游戏日志系统
+        .init_resource::<FortressChronicle>()  // 要塞编年史
+        .init_resource::<LegendsBrowser>()  // 传说浏览器状态
+        .init_resource::<WorldGenSettings>()  // 世界生成设置
+        .init_resource::<WorldCreationState>()  // 创建世界界面状态
+        .init_resource::<EmbarkPlan>()  // 出发准备方案
+        .init_resource::<name_generator::DwarfNameGenerator>()  // 矮人名字与身份生成器
+        .init_resource::<EmbarkState>()  // 出发准备界面状态
+        .init_resource::<Military>()  // 小队编制与警戒等级
+        .init_resource::<SquadPlacement>()  // 军事面板的地图点选状态
+        .init_resource::<ZoneEditor>()  // 区域面板的编辑状态
+        .init_resource::<Rooms>()  // 当前地块上检测到的房间
+        .init_resource::<Trampling>()  // 当前地图上的踩踏次数，用于踩出土路
+        .init_resource::<BuildMenu>()  // 建造面板的选择
         // 启动系统（总是执行）
         .add_systems(Startup, (setup_camera, init_world_atlas))
         // 进入主菜单时的系统
@@ -64,6 +88,8 @@ fn main() {
             save_dwarves_state,
             simulate_all_offscreen_dwarves, // 模拟所有地块的后台工作
             cleanup_local_map,
+            cleanup_zone_overlays,
+            cleanup_furniture_glyphs,
             reset_game_initialized,
         ))
         .add_systems(OnEnter(GameState::WorldView), (
@@ -83,17 +109,53 @@ fn main() {
             setup_minimap,
             setup_debug_panel,
             setup_notification_panel,
+            setup_labour_panel,
+            setup_military_panel,
+            setup_zone_panel,
+            setup_build_panel,
             mark_game_initialized,  // 放在链的最后,确保在地图生成后才标记
         ).chain().run_if(game_not_initialized))
         // 进入局部地图时的模拟系统（只在重新进入已有地图时运行）
         .add_systems(OnEnter(GameState::LocalView), 
             simulate_offscreen_dwarves.run_if(game_initialized)
         )
-        // 进入主菜单时的系统（从游戏返回主菜单时清理）
-        .add_systems(OnEnter(GameState::MainMenu), (
+        // 从游戏返回主菜单时清理（从传说界面返回时保留世界）
+        .add_systems(OnTransition { exited: GameState::Paused, entered: GameState::MainMenu }, (
             cleanup_game_on_menu_return,
+            cleanup_zone_overlays,
+            cleanup_furniture_glyphs,
             cleanup_world_data,
         ))
+        .add_systems(OnTransition { exited: GameState::WorldView, entered: GameState::MainMenu }, (
+            cleanup_game_on_menu_return,
+            cleanup_world_data,
+        ))
+        // 创建世界界面
+        .add_systems(OnEnter(GameState::CreateWorld), setup_world_creation)
+        .add_systems(OnExit(GameState::CreateWorld), (cleanup_world_creation, sync_world_creation))
+        .add_systems(OnTransition { exited: GameState::CreateWorld, entered: GameState::WorldView }, confirm_world_creation)
+        .add_systems(Update, (
+            world_creation_keyboard_system,
+            world_creation_button_system,
+            world_creation_preview_system,
+        ).chain().run_if(in_state(GameState::CreateWorld)))
+        // 出发准备界面
+        .add_systems(OnEnter(GameState::Embark), setup_embark)
+        .add_systems(OnExit(GameState::Embark), cleanup_embark)
+        .add_systems(OnTransition { exited: GameState::Embark, entered: GameState::LocalView }, apply_embark_plan)
+        .add_systems(Update, (
+            embark_keyboard_system,
+            embark_button_system,
+            embark_refresh_system,
+        ).chain().run_if(in_state(GameState::Embark)))
+        // 传说界面
+        .add_systems(OnEnter(GameState::Legends), setup_legends_view)
+        .add_systems(OnExit(GameState::Legends), cleanup_legends_view)
+        .add_systems(Update, (
+            legends_keyboard_system,
+            legends_button_system,
+            legends_refresh_system,
+        ).chain().run_if(in_state(GameState::Legends)))
         // 进入暂停菜单时的系统
         .add_systems(OnEnter(GameState::Paused), setup_pause_menu)
         // 退出暂停菜单时的系统
@@ -108,6 +170,7 @@ fn main() {
             pause_game_system,  // ESC暂停检测
             local_view_return_to_world_system,
             ui_hotkey_system,  // UI快捷键系统
+            navigation_update_system,  // 地形变化后更新寻路数据
             dwarf_work_system,    // 先决策
             dwarf_movement_system, // 后执行移动
             resource_gathering_system,
@@ -115,6 +178,59 @@ fn main() {
             time_system,
             time_control_system,
         ).run_if(in_state(GameState::LocalView)))
+        // 需求、社交与心情：在工作系统之前运行，发脾气的矮人不再接受工作
+        .add_systems(Update, (
+            hunger_system,
+            thirst_system,
+            weather_system,
+            weather_thought_system,
+            companionship_system,
+            conversation_system,
+            mood_system,
+            tantrum_system,
+        ).chain().before(dwarf_work_system).run_if(in_state(GameState::LocalView)))
+        // 人口变化：成长、死亡、出生与移民潮
+        .add_systems(Update, (
+            aging_system,
+            mortality_system,
+            birth_system,
+            migrant_wave_system,
+        ).chain().after(tantrum_system).before(dwarf_work_system).run_if(in_state(GameState::LocalView)))
+        // 军事：小队命令与训练在工作系统之前运行，覆盖队员和避难平民的工作决策
+        .add_systems(Update, (
+            squad_order_system,
+            military_training_system,
+            combat_system,
+        ).chain().after(migrant_wave_system).before(dwarf_work_system).run_if(in_state(GameState::LocalView)))
+        // 装备：接到工作后取用工具，采集后把装满的货物和捡到的食物运回仓库
+        .add_systems(Update, (
+            tool_equip_system.after(dwarf_work_system).after(input_system).before(resource_gathering_system),
+            hauling_system.after(resource_gathering_system),
+        ).run_if(in_state(GameState::LocalView)))
+        // 食物：在作坊里烹饪和酿酒，仓库里的食物按时腐烂
+        .add_systems(Update, (
+            workshop_system.after(dwarf_work_system),
+            food_spoilage_system.after(time_system),
+        ).run_if(in_state(GameState::LocalView)))
+        // 植被：记录踩踏，每小时生长树苗和青草、踩出土路，并同步到地图
+        .add_systems(Update, (
+            trampling_system.after(dwarf_movement_system),
+            vegetation_system.after(time_system).after(trampling_system),
+            vegetation_sync_system
+                .after(vegetation_system)
+                .after(resource_gathering_system)
+                .before(navigation_update_system),
+        ).run_if(in_state(GameState::LocalView)))
+        // 钓鱼：岸边垂钓，鱼群按时繁殖
+        .add_systems(Update, (
+            fishing_system.after(dwarf_work_system),
+            fish_regrowth_system.after(time_system),
+        ).run_if(in_state(GameState::LocalView)))
+        // 异步寻路：工作系统之前收集结果，之后派发新请求
+        .add_systems(Update, (
+            collect_path_results_system.before(dwarf_work_system),
+            dispatch_path_requests_system.after(dwarf_work_system),
+        ).run_if(in_state(GameState::LocalView)))
         .add_systems(Update, (
             ui_update_system,
             input_system,
@@ -127,12 +243,40 @@ fn main() {
             update_notification_panel,  // 通知面板更新
             toggle_notification_panel,  // F4切换通知面板
             debug_control_system,  // F2切换调试, F5清除日志
+            toggle_labour_panel,  // L切换劳动管理面板
+            labour_button_system,
+            labour_panel_refresh_system,
+            toggle_military_panel,  // K切换军事面板
+            squad_placement_system.before(military_button_system).before(mouse_selection_system),
+            military_button_system,
+            military_panel_refresh_system,
+        ).run_if(in_state(GameState::LocalView)))
+        // 区域：面板、涂抹与地图覆盖层
+        .add_systems(Update, (
+            toggle_zone_panel,  // Z切换区域面板
+            zone_paint_system.before(zone_button_system).before(mouse_selection_system),
+            zone_button_system,
+            zone_panel_refresh_system,
+            zone_overlay_system.after(zone_paint_system).after(zone_button_system),
+        ).run_if(in_state(GameState::LocalView)))
+        // 建造与房间：下订单、同步建筑结构、检测房间；疲劳与作息
+        .add_systems(Update, (
+            toggle_build_panel,  // B切换建造面板
+            build_placement_system.before(build_button_system).before(mouse_selection_system),
+            build_button_system,
+            build_panel_refresh_system.after(room_detection_system),
+            furniture_glyph_system.after(build_placement_system).after(building_system),
+            construction_sync_system.after(building_system).before(navigation_update_system),
+            room_detection_system.after(navigation_update_system).after(building_system),
+            fatigue_system.after(dwarf_movement_system),
+            sleep_schedule_system.after(room_detection_system).before(dwarf_work_system),
         ).run_if(in_state(GameState::LocalView)))
         .add_systems(Update, (
             update_work_indicators,
             mouse_selection_system,
             update_selection_indicator,
             mouse_control_system,
+            rally_command_system,  // G键集合
             update_dwarf_panel,
             dwarf_name_hover_system,
             terrain_info_hover_system,  // 地形信息悬停
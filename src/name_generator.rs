@@ -0,0 +1,228 @@
+// 矮人名字与身份生成器：每种文化有自己的语言（名字音节表、带意译的家族词根和绰号），
+// 以世界种子和生成序号确定性地推导出每个人，同一个世界总会出现同样的人
+
+use crate::components::{Identity, NameCulture};
+use crate::resources::{WorldSeed, DAYS_PER_YEAR};
+use bevy::prelude::*;
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// 同名时最多重新生成的次数
+const MAX_NAME_ATTEMPTS: usize = 64;
+
+/// 一种文化的语言
+struct Language {
+    /// 名的首音节
+    given_onsets: &'static [&'static str],
+    /// 名的尾音节
+    given_codas: &'static [&'static str],
+    /// 家族名词根：(本族语, 意译)
+    family_roots: &'static [(&'static str, &'static str)],
+    /// 绰号：(修饰, 称呼)
+    epithet_adjectives: &'static [&'static str],
+    epithet_nouns: &'static [&'static str],
+}
+
+impl Language {
+    fn of(culture: NameCulture) -> &'static Language {
+        match culture {
+            NameCulture::Mountain => &MOUNTAIN_LANGUAGE,
+            NameCulture::DeepHalls => &DEEP_HALLS_LANGUAGE,
+            NameCulture::Hills => &HILLS_LANGUAGE,
+        }
+    }
+}
+
+static MOUNTAIN_LANGUAGE: Language = Language {
+    given_onsets: &["乌", "索", "巴", "朵", "格", "诺", "德", "奥", "比", "邦", "卡", "托"],
+    given_codas: &["里克", "林", "因", "莉", "洛因", "力", "瓦林", "弗", "伯", "丝"],
+    family_roots: &[
+        ("卡兹", "铁"),
+        ("杜尔", "石"),
+        ("巴拉", "锤"),
+        ("吉姆", "须"),
+        ("扎克", "盾"),
+        ("莫拉", "峰"),
+        ("萨德", "炉"),
+        ("昆德", "岩"),
+    ],
+    epithet_adjectives: &["坚毅的", "沉默的", "宽肩的", "长须的", "不倒的", "铁拳的"],
+    epithet_nouns: &["守山人", "开凿者", "锻者", "老兵", "攀岩人"],
+};
+
+static DEEP_HALLS_LANGUAGE: Language = Language {
+    given_onsets: &["莫", "杜", "瑟", "戈", "阿", "乌", "德", "克", "纳", "维"],
+    given_codas: &["根", "拉克", "德琳", "姆", "努尔", "兹", "芙", "卡", "玛", "鲁姆"],
+    family_roots: &[
+        ("纳格", "深"),
+        ("乌姆", "影"),
+        ("戈拉", "金"),
+        ("德鲁", "脉"),
+        ("卡丁", "晶"),
+        ("霍尔", "厅"),
+        ("兹恩", "暗"),
+        ("阿格", "银"),
+    ],
+    epithet_adjectives: &["寡言的", "目光锐利的", "多疑的", "精明的", "不眠的", "执拗的"],
+    epithet_nouns: &["掘宝人", "守门人", "寻脉者", "雕匠", "矿工"],
+};
+
+static HILLS_LANGUAGE: Language = Language {
+    given_onsets: &["芬", "波", "比", "洛", "哈", "米", "泰", "蒂", "贝", "法"],
+    given_codas: &["恩", "弗", "伯", "莉", "多", "拉", "尔", "娜", "温", "宁"],
+    family_roots: &[
+        ("波恩", "麦"),
+        ("哈拉", "丘"),
+        ("米德", "蜜"),
+        ("泰恩", "橡"),
+        ("洛克", "溪"),
+        ("法恩", "蕨"),
+        ("贝尔", "酒"),
+        ("韦恩", "风"),
+    ],
+    epithet_adjectives: &["爱笑的", "好客的", "贪杯的", "手巧的", "慢性子的", "红脸的"],
+    epithet_nouns: &["酿酒人", "放羊人", "织工", "厨子", "歌者"],
+};
+
+/// 外貌描述用的词表
+const HAIR_COLOURS: [&str; 6] = ["火红", "乌黑", "栗棕", "金黄", "铁灰", "雪白"];
+const BEARD_STYLES: [&str; 5] = ["编成三股辫的长须", "浓密的短须", "分叉的长须", "梳理整齐的胡须", "乱蓬蓬的络腮胡"];
+const EYE_COLOURS: [&str; 5] = ["灰", "蓝", "绿", "琥珀", "深褐"];
+const BUILDS: [&str; 5] = ["矮壮", "瘦削", "魁梧", "圆滚滚", "结实"];
+const SKIN_TONES: [&str; 4] = ["黝黑", "苍白", "红润", "饱经风霜"];
+
+/// 名字生成器：按世界种子与序号逐个生成矮人身份
+#[derive(Resource)]
+pub struct DwarfNameGenerator {
+    seed: u64,
+    /// 已使用的生成序号
+    issued: u64,
+    /// 要塞首批移民所属的文化
+    home_culture: NameCulture,
+}
+
+impl Default for DwarfNameGenerator {
+    fn default() -> Self {
+        Self::new(WorldSeed::default().seed)
+    }
+}
+
+impl DwarfNameGenerator {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(mix(seed, u64::MAX));
+        Self {
+            seed,
+            issued: 0,
+            home_culture: *NameCulture::ALL.choose(&mut rng).unwrap_or(&NameCulture::Mountain),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 生成一名新矮人的身份。
+    /// `today` 为当前要塞日，`ages` 为年龄范围（岁）；有父母时继承父母的文化和家族名
+    pub fn generate(
+        &mut self,
+        today: u32,
+        ages: RangeInclusive<u32>,
+        parent: Option<&Identity>,
+        taken: &HashSet<String>,
+    ) -> Identity {
+        let mut identity = self.next(today, ages.clone(), parent);
+        for _ in 1..MAX_NAME_ATTEMPTS {
+            if !taken.contains(&identity.full_name()) {
+                return identity;
+            }
+            identity = self.next(today, ages.clone(), parent);
+        }
+        // 重名实在太多时在名字后加上序号
+        let base = identity.given_name.clone();
+        for n in 2.. {
+            identity.given_name = format!("{}{}世", base, n);
+            if !taken.contains(&identity.full_name()) {
+                break;
+            }
+        }
+        identity
+    }
+
+    fn next(&mut self, today: u32, ages: RangeInclusive<u32>, parent: Option<&Identity>) -> Identity {
+        let index = self.issued;
+        self.issued += 1;
+        let mut rng = SmallRng::seed_from_u64(mix(self.seed, index));
+
+        // 移民大多来自同一文化，偶尔有外来者
+        let culture = match parent {
+            Some(parent) => parent.culture,
+            None if rng.gen_bool(0.75) => self.home_culture,
+            None => *NameCulture::ALL.choose(&mut rng).unwrap_or(&self.home_culture),
+        };
+        let language = Language::of(culture);
+
+        let given_name = format!(
+            "{}{}",
+            pick(language.given_onsets, &mut rng),
+            pick(language.given_codas, &mut rng)
+        );
+        let (family_name, family_meaning) = match parent {
+            Some(parent) => (parent.family_name.clone(), parent.family_meaning.clone()),
+            None => {
+                let (first_native, first_meaning) = *language.family_roots.choose(&mut rng).unwrap_or(&("无", "无"));
+                let (second_native, second_meaning) = *language.family_roots.choose(&mut rng).unwrap_or(&("名", "名"));
+                (
+                    format!("{}{}", first_native, second_native),
+                    format!("{}{}", first_meaning, second_meaning),
+                )
+            }
+        };
+        let epithet = format!(
+            "{}{}",
+            pick(language.epithet_adjectives, &mut rng),
+            pick(language.epithet_nouns, &mut rng)
+        );
+
+        let age_years = rng.gen_range(ages);
+        // 新生儿就生在今天，其他人的生日散布在一年里
+        let day_offset = if age_years == 0 { 0 } else { rng.gen_range(0..DAYS_PER_YEAR) };
+        let born_day = today as i64 - (age_years * DAYS_PER_YEAR + day_offset) as i64;
+
+        // 上了年纪的矮人头发多半已经花白
+        let hair = if age_years >= 100 && rng.gen_bool(0.7) {
+            pick(&HAIR_COLOURS[4..], &mut rng)
+        } else {
+            pick(&HAIR_COLOURS, &mut rng)
+        };
+        let appearance = vec![
+            format!("{}色{}", hair, pick(&BEARD_STYLES, &mut rng)),
+            format!("{}色的眼睛，皮肤{}", pick(&EYE_COLOURS, &mut rng), pick(&SKIN_TONES, &mut rng)),
+            format!("身材{}", pick(&BUILDS, &mut rng)),
+        ];
+
+        Identity {
+            culture,
+            given_name,
+            family_name,
+            family_meaning,
+            epithet,
+            appearance,
+            born_day,
+        }
+    }
+}
+
+fn pick(options: &[&'static str], rng: &mut SmallRng) -> &'static str {
+    options.choose(rng).copied().unwrap_or("")
+}
+
+/// 由世界种子和序号得到每个人独立的随机种子
+fn mix(seed: u64, index: u64) -> u64 {
+    let mut x = seed ^ index.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
@@ -1,8 +1,8 @@
-use crate::components::GridPosition;
-use crate::components::Terrain;
+use crate::components::{Terrain, TerrainType};
+use crate::connectivity::is_walkable_terrain;
 use crate::world::*;
 use bevy::prelude::*;
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
 /// A*寻路节点
@@ -52,47 +52,39 @@ fn get_neighbors(pos: (i32, i32)) -> Vec<(i32, i32)> {
     ]
 }
 
-/// 检查位置是否可行走
-fn is_walkable(pos: (i32, i32), terrain_query: &Query<(&GridPosition, &Terrain)>) -> bool {
-    // 边界检查
-    if pos.0 < 0 || pos.0 >= WORLD_WIDTH || pos.1 < 0 || pos.1 >= WORLD_HEIGHT {
-        return false;
-    }
-
-    // 地形检查
-    for (terrain_pos, terrain) in terrain_query.iter() {
-        if terrain_pos.x == pos.0 && terrain_pos.y == pos.1 {
-            return terrain.walkable;
-        }
-    }
-
-    false
-}
-
-/// A*寻路算法实现
+/// 在限定范围内运行A*（范围为闭区间 min..=max，使用数组代替哈希表）
 /// 返回从start到goal的路径（不包含起点）
-pub fn find_path(
+fn find_path_in_bounds(
     start: (i32, i32),
     goal: (i32, i32),
-    terrain_query: &Query<(&GridPosition, &Terrain)>,
+    grid: &NavGrid,
+    min: (i32, i32),
+    max: (i32, i32),
 ) -> Option<Vec<(i32, i32)>> {
-    // 检查目标是否可达
-    if !is_walkable(goal, terrain_query) {
+    if !grid.is_walkable(goal.0, goal.1) {
         return None;
     }
-
-    // 如果已经在目标位置
     if start == goal {
         return Some(Vec::new());
     }
 
+    let width = max.0 - min.0 + 1;
+    let height = max.1 - min.1 + 1;
+    let inside = |pos: (i32, i32)| {
+        (min.0..=max.0).contains(&pos.0) && (min.1..=max.1).contains(&pos.1)
+    };
+    let local = |pos: (i32, i32)| ((pos.1 - min.1) * width + (pos.0 - min.0)) as usize;
+    if !inside(start) || !inside(goal) {
+        return None;
+    }
+
+    let size = (width * height) as usize;
+    let mut closed = vec![false; size];
+    let mut came_from: Vec<Option<(i32, i32)>> = vec![None; size];
+    let mut g_scores = vec![i32::MAX; size];
     let mut open_set = BinaryHeap::new();
-    let mut closed_set = HashSet::new();
-    let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
-    let mut g_scores: HashMap<(i32, i32), i32> = HashMap::new();
 
-    // 初始化起点
-    g_scores.insert(start, 0);
+    g_scores[local(start)] = 0;
     open_set.push(PathNode {
         position: start,
         g_cost: 0,
@@ -100,61 +92,36 @@ pub fn find_path(
         parent: None,
     });
 
-    // 最大迭代次数，防止死循环
-    let mut iterations = 0;
-    let max_iterations = 1000;
-
     while let Some(current) = open_set.pop() {
-        iterations += 1;
-        if iterations > max_iterations {
-            return None; // 超时，认为不可达
-        }
-
         let current_pos = current.position;
 
-        // 到达目标
+        // 到达目标，重建路径
         if current_pos == goal {
-            // 重建路径
             let mut path = Vec::new();
             let mut current = goal;
-
-            while let Some(&parent) = came_from.get(&current) {
+            while let Some(parent) = came_from[local(current)] {
                 path.push(current);
                 current = parent;
             }
-
             path.reverse();
             return Some(path);
         }
 
-        // 已经访问过
-        if closed_set.contains(&current_pos) {
+        if closed[local(current_pos)] {
             continue;
         }
+        closed[local(current_pos)] = true;
 
-        closed_set.insert(current_pos);
-
-        // 检查所有邻居
         for neighbor in get_neighbors(current_pos) {
-            if closed_set.contains(&neighbor) {
+            if !inside(neighbor) || closed[local(neighbor)] || !grid.is_walkable(neighbor.0, neighbor.1) {
                 continue;
             }
 
-            if !is_walkable(neighbor, terrain_query) {
-                continue;
-            }
-
-            // 计算移动代价（只有正交移动，统一代价10）
-            let move_cost = 10;
-
-            let tentative_g = g_scores.get(&current_pos).unwrap_or(&i32::MAX) + move_cost;
-            let neighbor_g = *g_scores.get(&neighbor).unwrap_or(&i32::MAX);
-
-            if tentative_g < neighbor_g {
-                // 找到更好的路径
-                came_from.insert(neighbor, current_pos);
-                g_scores.insert(neighbor, tentative_g);
-
+            // 只有正交移动，代价取决于进入格子的地形（道路更便宜）
+            let tentative_g = g_scores[local(current_pos)] + grid.step_cost_at(neighbor.0, neighbor.1) as i32;
+            if tentative_g < g_scores[local(neighbor)] {
+                came_from[local(neighbor)] = Some(current_pos);
+                g_scores[local(neighbor)] = tentative_g;
                 open_set.push(PathNode {
                     position: neighbor,
                     g_cost: tentative_g,
@@ -165,10 +132,408 @@ pub fn find_path(
         }
     }
 
-    // 没有找到路径
     None
 }
 
+/// 寻路入口：近距离直接在局部范围内运行A*，远距离使用分层寻路
+/// 返回从start到goal的路径（不包含起点）
+pub fn find_path(start: (i32, i32), goal: (i32, i32), grid: &NavGrid) -> Option<Vec<(i32, i32)>> {
+    if !grid.is_walkable(goal.0, goal.1) {
+        return None;
+    }
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    // 起点和终点所在区块及其周围一圈区块内尝试直接寻路
+    if heuristic(start, goal) <= CHUNK_SIZE * 2 {
+        let min = (
+            (start.0.min(goal.0) - CHUNK_SIZE).max(0),
+            (start.1.min(goal.1) - CHUNK_SIZE).max(0),
+        );
+        let max = (
+            (start.0.max(goal.0) + CHUNK_SIZE).min(WORLD_WIDTH - 1),
+            (start.1.max(goal.1) + CHUNK_SIZE).min(WORLD_HEIGHT - 1),
+        );
+        if let Some(path) = find_path_in_bounds(start, goal, grid, min, max) {
+            return Some(path);
+        }
+    }
+
+    grid.find_hierarchical_path(start, goal)
+}
+
+/// 分层寻路的区块边长（格）
+pub const CHUNK_SIZE: i32 = 10;
+/// 入口段长度达到该值时在两端各放一个过渡点，否则只放在中点
+const LONG_ENTRANCE_LENGTH: i32 = 6;
+
+/// 抽象图中的节点（位于区块边界上的格子）
+type NavNode = (i32, i32);
+
+/// 区块的缓存数据：边界入口节点、节点之间的区块内距离和跨区块连接
+#[derive(Clone, Debug, Default)]
+struct ChunkGraph {
+    /// 节点 -> 同区块内可达的其他节点及代价
+    intra: HashMap<NavNode, Vec<(NavNode, i32)>>,
+    /// 节点 -> 相邻区块中与之相连的节点
+    inter: HashMap<NavNode, Vec<NavNode>>,
+}
+
+/// 按移动速度倍率计算进入格子的单步代价：平地为10，移动越慢代价越高，不可通行为0
+pub fn step_cost(speed: f32) -> u32 {
+    if speed <= 0.0 {
+        0
+    } else {
+        (10.0 / speed).round() as u32
+    }
+}
+
+/// 导航网格：可行走数据和分层寻路（HPA*）的抽象图
+/// 地形改变时只重建受影响的区块
+#[derive(Resource, Clone, Debug, Default)]
+pub struct NavGrid {
+    /// 索引为 y * WORLD_WIDTH + x
+    walkable: Vec<bool>,
+    /// 进入每个格子的移动代价（见 `step_cost`），供流场等加权搜索使用
+    step_costs: Vec<u32>,
+    chunks: Vec<ChunkGraph>,
+    /// 相邻区块之间的入口（键为两个区块ID，较小者在前；值为两侧格子）
+    borders: HashMap<(usize, usize), Vec<(NavNode, NavNode)>>,
+    /// 地形已改变、尚未重建的区块
+    dirty_chunks: HashSet<usize>,
+}
+
+impl NavGrid {
+    /// 由地形网格（索引为 y * WORLD_WIDTH + x）构建导航网格
+    pub fn from_terrain(terrain: &[TerrainType]) -> Self {
+        let mut grid = Self {
+            walkable: terrain.iter().map(|t| is_walkable_terrain(*t)).collect(),
+            step_costs: terrain.iter().map(|t| step_cost(t.movement_speed())).collect(),
+            chunks: vec![ChunkGraph::default(); (chunk_cols() * chunk_rows()) as usize],
+            borders: HashMap::new(),
+            dirty_chunks: HashSet::new(),
+        };
+        for chunk in 0..grid.chunks.len() {
+            for neighbour in chunk_neighbours(chunk) {
+                grid.rebuild_border(chunk, neighbour);
+            }
+        }
+        for chunk in 0..grid.chunks.len() {
+            grid.rebuild_chunk_graph(chunk);
+        }
+        grid
+    }
+
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        (0..WORLD_WIDTH).contains(&x)
+            && (0..WORLD_HEIGHT).contains(&y)
+            && self.walkable.get((y * WORLD_WIDTH + x) as usize).copied().unwrap_or(false)
+    }
+
+    /// 进入指定格子的移动代价（越界或不可行走为0）
+    pub fn step_cost_at(&self, x: i32, y: i32) -> u32 {
+        if !self.is_walkable(x, y) {
+            return 0;
+        }
+        self.step_costs.get((y * WORLD_WIDTH + x) as usize).copied().unwrap_or(0)
+    }
+
+    /// 更新一个格子的地形（含建筑结构）：可行走状态或移动代价变化时标记所在区块待重建。
+    /// 返回 (可行走状态是否变化, 移动代价是否变化)
+    pub fn set_terrain(&mut self, x: i32, y: i32, terrain: &Terrain) -> (bool, bool) {
+        let walkable = terrain.walkable;
+        if !(0..WORLD_WIDTH).contains(&x) || !(0..WORLD_HEIGHT).contains(&y) {
+            return (false, false);
+        }
+        let index = (y * WORLD_WIDTH + x) as usize;
+        if index >= self.walkable.len() {
+            return (false, false);
+        }
+        let cost = step_cost(terrain.movement_speed());
+        let cost_changed = self.step_costs[index] != cost;
+        let walkable_changed = self.walkable[index] != walkable;
+        self.step_costs[index] = cost;
+        self.walkable[index] = walkable;
+        if walkable_changed || cost_changed {
+            self.dirty_chunks.insert(chunk_of((x, y)));
+        }
+        (walkable_changed, cost_changed)
+    }
+
+    /// 重建所有待重建区块（及其相邻区块的入口和区块内距离）
+    pub fn rebuild_dirty(&mut self) -> usize {
+        if self.dirty_chunks.is_empty() {
+            return 0;
+        }
+        let dirty: Vec<usize> = self.dirty_chunks.drain().collect();
+        let mut affected = HashSet::new();
+        for chunk in dirty.iter().copied() {
+            affected.insert(chunk);
+            for neighbour in chunk_neighbours(chunk) {
+                self.rebuild_border(chunk, neighbour);
+                affected.insert(neighbour);
+            }
+        }
+        for chunk in affected {
+            self.rebuild_chunk_graph(chunk);
+        }
+        dirty.len()
+    }
+
+    /// 重新计算两个相邻区块之间的入口
+    fn rebuild_border(&mut self, a: usize, b: usize) {
+        let key = (a.min(b), a.max(b));
+        let (first, second) = (chunk_origin(key.0), chunk_origin(key.1));
+        let mut entrances = Vec::new();
+
+        // 第二个区块在右侧时沿竖直边界扫描，在上方时沿水平边界扫描
+        let vertical = second.1 == first.1;
+        let length = if vertical {
+            CHUNK_SIZE.min(WORLD_HEIGHT - first.1)
+        } else {
+            CHUNK_SIZE.min(WORLD_WIDTH - first.0)
+        };
+        let pair = |offset: i32| -> (NavNode, NavNode) {
+            if vertical {
+                ((second.0 - 1, first.1 + offset), (second.0, first.1 + offset))
+            } else {
+                ((first.0 + offset, second.1 - 1), (first.0 + offset, second.1))
+            }
+        };
+        let open = |offset: i32| {
+            let (inner, outer) = pair(offset);
+            self.is_walkable(inner.0, inner.1) && self.is_walkable(outer.0, outer.1)
+        };
+
+        let mut offset = 0;
+        while offset < length {
+            if !open(offset) {
+                offset += 1;
+                continue;
+            }
+            let run_start = offset;
+            while offset < length && open(offset) {
+                offset += 1;
+            }
+            let run_end = offset - 1;
+            if run_end - run_start + 1 >= LONG_ENTRANCE_LENGTH {
+                entrances.push(pair(run_start));
+                entrances.push(pair(run_end));
+            } else {
+                entrances.push(pair((run_start + run_end) / 2));
+            }
+        }
+
+        self.borders.insert(key, entrances);
+    }
+
+    /// 重新计算区块的节点、区块内距离和跨区块连接
+    fn rebuild_chunk_graph(&mut self, chunk: usize) {
+        let mut inter: HashMap<NavNode, Vec<NavNode>> = HashMap::new();
+        for neighbour in chunk_neighbours(chunk) {
+            let key = (chunk.min(neighbour), chunk.max(neighbour));
+            for (first, second) in self.borders.get(&key).into_iter().flatten() {
+                let (own, other) = if chunk_of(*first) == chunk {
+                    (*first, *second)
+                } else {
+                    (*second, *first)
+                };
+                inter.entry(own).or_default().push(other);
+            }
+        }
+
+        let mut intra = HashMap::new();
+        for node in inter.keys().copied() {
+            let distances = self.chunk_distances(node);
+            let edges = inter
+                .keys()
+                .copied()
+                .filter(|other| *other != node)
+                .filter_map(|other| distances.get(&other).map(|cost| (other, *cost)))
+                .collect();
+            intra.insert(node, edges);
+        }
+
+        self.chunks[chunk] = ChunkGraph { intra, inter };
+    }
+
+    /// 在格子所在区块内按地形代价做 Dijkstra 搜索，返回从该格子出发到区块内各格子的代价
+    fn chunk_distances(&self, from: NavNode) -> HashMap<NavNode, i32> {
+        let (min, max) = chunk_bounds(chunk_of(from));
+        let mut distances = HashMap::new();
+        if !self.is_walkable(from.0, from.1) {
+            return distances;
+        }
+        let mut open_set = BinaryHeap::new();
+        distances.insert(from, 0);
+        open_set.push(Reverse((0, from)));
+        while let Some(Reverse((cost, current))) = open_set.pop() {
+            if cost > distances[&current] {
+                continue;
+            }
+            for neighbor in get_neighbors(current) {
+                let inside = (min.0..=max.0).contains(&neighbor.0) && (min.1..=max.1).contains(&neighbor.1);
+                if !inside || !self.is_walkable(neighbor.0, neighbor.1) {
+                    continue;
+                }
+                let tentative = cost + self.step_cost_at(neighbor.0, neighbor.1) as i32;
+                if tentative < *distances.get(&neighbor).unwrap_or(&i32::MAX) {
+                    distances.insert(neighbor, tentative);
+                    open_set.push(Reverse((tentative, neighbor)));
+                }
+            }
+        }
+        distances
+    }
+
+    /// 分层寻路：在抽象图上规划经过的入口，再逐段细化为格子路径
+    fn find_hierarchical_path(&self, start: (i32, i32), goal: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        let start_chunk = chunk_of(start);
+        let goal_chunk = chunk_of(goal);
+
+        // 起点、终点临时接入所在区块的入口节点
+        let start_distances = self.chunk_distances(start);
+        let goal_distances = self.chunk_distances(goal);
+        let start_edges: Vec<(NavNode, i32)> = self.chunks[start_chunk]
+            .inter
+            .keys()
+            .filter_map(|node| start_distances.get(node).map(|cost| (*node, *cost)))
+            .collect();
+        // 代价按进入的格子计算，从终点反向搜得的代价要换成从入口走到终点的代价
+        let goal_cost = self.step_cost_at(goal.0, goal.1) as i32;
+        let goal_edges: HashMap<NavNode, i32> = self.chunks[goal_chunk]
+            .inter
+            .keys()
+            .filter_map(|node| {
+                let reverse = goal_distances.get(node)?;
+                Some((*node, reverse - self.step_cost_at(node.0, node.1) as i32 + goal_cost))
+            })
+            .collect();
+
+        let mut open_set = BinaryHeap::new();
+        let mut g_scores: HashMap<NavNode, i32> = HashMap::new();
+        let mut came_from: HashMap<NavNode, NavNode> = HashMap::new();
+        let mut closed = HashSet::new();
+
+        g_scores.insert(start, 0);
+        open_set.push(PathNode {
+            position: start,
+            g_cost: 0,
+            h_cost: heuristic(start, goal),
+            parent: None,
+        });
+
+        let mut reached = false;
+        while let Some(current) = open_set.pop() {
+            let node = current.position;
+            if node == goal {
+                reached = true;
+                break;
+            }
+            if !closed.insert(node) {
+                continue;
+            }
+
+            let mut edges: Vec<(NavNode, i32)> = Vec::new();
+            if node == start {
+                edges.extend(start_edges.iter().copied());
+            }
+            let graph = &self.chunks[chunk_of(node)];
+            edges.extend(graph.intra.get(&node).into_iter().flatten().copied());
+            edges.extend(
+                graph
+                    .inter
+                    .get(&node)
+                    .into_iter()
+                    .flatten()
+                    .map(|other| (*other, self.step_cost_at(other.0, other.1) as i32)),
+            );
+            if let Some(cost) = goal_edges.get(&node) {
+                edges.push((goal, *cost));
+            }
+
+            let node_g = g_scores[&node];
+            for (next, cost) in edges {
+                let tentative_g = node_g + cost;
+                if tentative_g < *g_scores.get(&next).unwrap_or(&i32::MAX) {
+                    g_scores.insert(next, tentative_g);
+                    came_from.insert(next, node);
+                    open_set.push(PathNode {
+                        position: next,
+                        g_cost: tentative_g,
+                        h_cost: heuristic(next, goal),
+                        parent: Some(node),
+                    });
+                }
+            }
+        }
+        if !reached {
+            return None;
+        }
+
+        let mut waypoints = vec![goal];
+        let mut current = goal;
+        while let Some(parent) = came_from.get(&current) {
+            waypoints.push(*parent);
+            current = *parent;
+        }
+        waypoints.reverse();
+
+        // 逐段细化：同一区块内的两点在区块范围内运行A*，跨区块的两点本就相邻
+        let mut path = Vec::new();
+        for segment in waypoints.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            if heuristic(from, to) == 1 {
+                path.push(to);
+                continue;
+            }
+            let (min, max) = chunk_bounds(chunk_of(from));
+            path.extend(find_path_in_bounds(from, to, self, min, max)?);
+        }
+        Some(path)
+    }
+}
+
+fn chunk_cols() -> i32 {
+    (WORLD_WIDTH + CHUNK_SIZE - 1) / CHUNK_SIZE
+}
+
+fn chunk_rows() -> i32 {
+    (WORLD_HEIGHT + CHUNK_SIZE - 1) / CHUNK_SIZE
+}
+
+fn chunk_of(pos: (i32, i32)) -> usize {
+    ((pos.1 / CHUNK_SIZE) * chunk_cols() + pos.0 / CHUNK_SIZE) as usize
+}
+
+/// 区块左下角格子
+fn chunk_origin(chunk: usize) -> (i32, i32) {
+    let chunk = chunk as i32;
+    ((chunk % chunk_cols()) * CHUNK_SIZE, (chunk / chunk_cols()) * CHUNK_SIZE)
+}
+
+/// 区块范围（闭区间）
+fn chunk_bounds(chunk: usize) -> ((i32, i32), (i32, i32)) {
+    let origin = chunk_origin(chunk);
+    (
+        origin,
+        (
+            (origin.0 + CHUNK_SIZE - 1).min(WORLD_WIDTH - 1),
+            (origin.1 + CHUNK_SIZE - 1).min(WORLD_HEIGHT - 1),
+        ),
+    )
+}
+
+/// 四个方向上相邻的区块
+fn chunk_neighbours(chunk: usize) -> impl Iterator<Item = usize> {
+    let (cx, cy) = (chunk as i32 % chunk_cols(), chunk as i32 / chunk_cols());
+    [(cx + 1, cy), (cx - 1, cy), (cx, cy + 1), (cx, cy - 1)]
+        .into_iter()
+        .filter(|(x, y)| (0..chunk_cols()).contains(x) && (0..chunk_rows()).contains(y))
+        .map(|(x, y)| (y * chunk_cols() + x) as usize)
+}
+
 /// 简化路径：只移除完全冗余的中间点（必须是相邻格子且方向一致）
 /// 这个版本更保守，确保简化后的路径点仍然是逐步相邻的
 /// 例如：(0,0) -> (1,0) -> (2,0) -> (3,0) 简化为 (0,0) -> (3,0)
@@ -217,3 +582,79 @@ pub fn simplify_path(path: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
 
     simplified
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 森林中横穿一条草地小路的地形：小路走得比森林快
+    fn forest_with_path(path_row: i32) -> Vec<TerrainType> {
+        (0..WORLD_WIDTH * WORLD_HEIGHT)
+            .map(|index| {
+                if index / WORLD_WIDTH == path_row {
+                    TerrainType::Grass
+                } else {
+                    TerrainType::Tree
+                }
+            })
+            .collect()
+    }
+
+    /// 检查路径逐格相邻、可行走并到达终点，返回按进入格子累计的代价
+    fn path_cost(grid: &NavGrid, start: (i32, i32), goal: (i32, i32), path: &[(i32, i32)]) -> u32 {
+        let mut previous = start;
+        let mut cost = 0;
+        for step in path {
+            assert_eq!(heuristic(previous, *step), 1, "路径在 {:?} 处不连续", step);
+            assert!(grid.is_walkable(step.0, step.1), "路径经过不可行走的 {:?}", step);
+            cost += grid.step_cost_at(step.0, step.1);
+            previous = *step;
+        }
+        assert_eq!(previous, goal);
+        cost
+    }
+
+    fn flat_cost(grid: &NavGrid, start: (i32, i32), goal: (i32, i32)) -> u32 {
+        let max = (WORLD_WIDTH - 1, WORLD_HEIGHT - 1);
+        let path = find_path_in_bounds(start, goal, grid, (0, 0), max).expect("平面A*应找到路径");
+        path_cost(grid, start, goal, &path)
+    }
+
+    fn hierarchical_cost(grid: &NavGrid, start: (i32, i32), goal: (i32, i32)) -> u32 {
+        let path = grid.find_hierarchical_path(start, goal).expect("分层寻路应找到路径");
+        path_cost(grid, start, goal, &path)
+    }
+
+    #[test]
+    fn hierarchical_path_matches_flat_cost_on_weighted_grid() {
+        // 起点终点离小路5格、离区块上边界的入口4格：按步数算走上边更近，按代价算绕到小路更省
+        let grid = NavGrid::from_terrain(&forest_with_path(CHUNK_SIZE));
+        let (start, goal) = ((2, 15), (WORLD_WIDTH - 5, 15));
+
+        let flat = flat_cost(&grid, start, goal);
+        assert_eq!(hierarchical_cost(&grid, start, goal), flat);
+        assert_eq!(find_path(start, goal, &grid).map(|path| path_cost(&grid, start, goal, &path)), Some(flat));
+    }
+
+    #[test]
+    fn hierarchical_path_uses_new_path_after_cost_change() {
+        let mut grid = NavGrid::from_terrain(&forest_with_path(-1));
+        let (start, goal) = ((2, 15), (WORLD_WIDTH - 5, 15));
+        let before = hierarchical_cost(&grid, start, goal);
+
+        for x in 0..WORLD_WIDTH {
+            let terrain = Terrain {
+                terrain_type: TerrainType::Grass,
+                walkable: true,
+                resource_richness: 1.0,
+                construction: None,
+            };
+            assert_eq!(grid.set_terrain(x, CHUNK_SIZE, &terrain), (false, true));
+        }
+        assert!(grid.rebuild_dirty() > 0);
+
+        let after = hierarchical_cost(&grid, start, goal);
+        assert!(after < before);
+        assert_eq!(after, flat_cost(&grid, start, goal));
+    }
+}
@@ -8,28 +8,363 @@ pub enum GameState {
     WorldView,
     LocalView,
     Paused,
+    Legends,
+    CreateWorld,
+    Embark,
+}
+
+/// 一批入库的食物：同一小时内入库的同种食物合为一批，按入库时长腐烂
+#[derive(Clone, Debug)]
+pub struct FoodBatch {
+    pub item: crate::components::FoodItem,
+    pub amount: u32,
+    /// 已入库的游戏小时数
+    pub age_hours: u32,
+}
+
+/// 仓库里的食物：按入库先后排列，取用时先取最早入库的
+#[derive(Clone, Debug, Default)]
+pub struct FoodStock {
+    batches: Vec<FoodBatch>,
+}
+
+impl FoodStock {
+    pub fn add(&mut self, item: crate::components::FoodItem, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        match self
+            .batches
+            .iter_mut()
+            .find(|batch| batch.item == item && batch.age_hours == 0)
+        {
+            Some(batch) => batch.amount += amount,
+            None => self.batches.push(FoodBatch { item, amount, age_hours: 0 }),
+        }
+    }
+
+    /// 所有食物的总份数
+    pub fn total(&self) -> u32 {
+        self.batches.iter().map(|batch| batch.amount).sum()
+    }
+
+    /// 满足条件的食物份数
+    pub fn count(&self, filter: impl Fn(crate::components::FoodItem) -> bool) -> u32 {
+        self.batches
+            .iter()
+            .filter(|batch| filter(batch.item))
+            .map(|batch| batch.amount)
+            .sum()
+    }
+
+    pub fn amount(&self, item: crate::components::FoodItem) -> u32 {
+        self.count(|existing| existing == item)
+    }
+
+    /// 取出一份满足条件、最早入库的食物
+    pub fn take_where(
+        &mut self,
+        filter: impl Fn(crate::components::FoodItem) -> bool,
+    ) -> Option<crate::components::FoodItem> {
+        let index = self.batches.iter().position(|batch| filter(batch.item))?;
+        let batch = &mut self.batches[index];
+        let item = batch.item;
+        batch.amount -= 1;
+        if batch.amount == 0 {
+            self.batches.remove(index);
+        }
+        Some(item)
+    }
+
+    /// 某一分类中各种食物的份数，按显示名称合并（饭菜不分主料）
+    pub fn summary(&self, category: crate::components::FoodCategory) -> Vec<(&'static str, u32)> {
+        let mut entries: Vec<(&'static str, u32)> = Vec::new();
+        for batch in self.batches.iter().filter(|batch| batch.item.category() == category) {
+            match entries.iter_mut().find(|(label, _)| *label == batch.item.label()) {
+                Some((_, amount)) => *amount += batch.amount,
+                None => entries.push((batch.item.label(), batch.amount)),
+            }
+        }
+        entries
+    }
+
+    /// 所有食物存放一小时，返回腐烂扔掉的食物及份数
+    pub fn age_one_hour(&mut self) -> Vec<(crate::components::FoodItem, u32)> {
+        let mut spoiled = Vec::new();
+        self.batches.retain_mut(|batch| {
+            batch.age_hours += 1;
+            match batch.item.shelf_life_hours() {
+                Some(limit) if batch.age_hours >= limit => {
+                    spoiled.push((batch.item, batch.amount));
+                    false
+                }
+                _ => true,
+            }
+        });
+        spoiled
+    }
 }
 
 /// 全局资源库存
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct GlobalInventory {
     pub stone: u32,
     pub wood: u32,
-    pub food: u32,
+    pub food: FoodStock,
     pub metal: u32,
+    pub tools: u32,
+    pub seeds: u32,
 }
 
 impl Default for GlobalInventory {
     fn default() -> Self {
+        use crate::components::{DrinkKind, FoodItem, FoodKind};
+
+        let mut food = FoodStock::default();
+        food.add(FoodItem::Raw(FoodKind::Crop), 100);
+        food.add(FoodItem::Drink(DrinkKind::Ale), 40);
         Self {
             stone: 50,
             wood: 30,
-            food: 100,
+            food,
             metal: 10,
+            tools: 7,
+            seeds: 20,
         }
     }
 }
 
+impl GlobalInventory {
+    /// 某种材质对应的库存（铜和铁都计入金属）
+    pub fn material_stock(&self, material: crate::components::ItemMaterial) -> u32 {
+        use crate::components::ItemMaterial;
+
+        match material {
+            ItemMaterial::Wood => self.wood,
+            ItemMaterial::Stone => self.stone,
+            ItemMaterial::Copper | ItemMaterial::Iron => self.metal,
+        }
+    }
+
+    /// 从库存中取出材料，不够时什么也不做并返回 false
+    pub fn take_material(&mut self, material: crate::components::ItemMaterial, amount: u32) -> bool {
+        use crate::components::ItemMaterial;
+
+        let stock = match material {
+            ItemMaterial::Wood => &mut self.wood,
+            ItemMaterial::Stone => &mut self.stone,
+            ItemMaterial::Copper | ItemMaterial::Iron => &mut self.metal,
+        };
+        if *stock < amount {
+            return false;
+        }
+        *stock -= amount;
+        true
+    }
+}
+
+/// 出发物资种类
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmbarkSupply {
+    Food,
+    Drink,
+    Tools,
+    Seeds,
+    Wood,
+    Stone,
+    Metal,
+}
+
+impl EmbarkSupply {
+    pub const ALL: [EmbarkSupply; 7] = [
+        EmbarkSupply::Food,
+        EmbarkSupply::Drink,
+        EmbarkSupply::Tools,
+        EmbarkSupply::Seeds,
+        EmbarkSupply::Wood,
+        EmbarkSupply::Stone,
+        EmbarkSupply::Metal,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EmbarkSupply::Food => "谷物",
+            EmbarkSupply::Drink => "麦酒",
+            EmbarkSupply::Tools => "工具",
+            EmbarkSupply::Seeds => "种子",
+            EmbarkSupply::Wood => "木材",
+            EmbarkSupply::Stone => "石头",
+            EmbarkSupply::Metal => "金属",
+        }
+    }
+
+    /// 每次调整的数量
+    pub fn step(&self) -> u32 {
+        match self {
+            EmbarkSupply::Food | EmbarkSupply::Drink => 10,
+            EmbarkSupply::Tools => 1,
+            EmbarkSupply::Seeds => 5,
+            EmbarkSupply::Wood | EmbarkSupply::Stone => 10,
+            EmbarkSupply::Metal => 5,
+        }
+    }
+
+    /// 每个调整步长消耗的点数
+    pub fn cost_per_step(&self) -> i32 {
+        match self {
+            EmbarkSupply::Food | EmbarkSupply::Drink => 2,
+            EmbarkSupply::Tools => 4,
+            EmbarkSupply::Seeds => 1,
+            EmbarkSupply::Wood | EmbarkSupply::Stone => 1,
+            EmbarkSupply::Metal => 3,
+        }
+    }
+
+    /// 食物类物资在仓库里对应的食物
+    fn food_item(&self) -> Option<crate::components::FoodItem> {
+        use crate::components::{DrinkKind, FoodItem, FoodKind};
+
+        match self {
+            EmbarkSupply::Food => Some(FoodItem::Raw(FoodKind::Crop)),
+            EmbarkSupply::Drink => Some(FoodItem::Drink(DrinkKind::Ale)),
+            _ => None,
+        }
+    }
+
+    pub fn amount(&self, inventory: &GlobalInventory) -> u32 {
+        if let Some(item) = self.food_item() {
+            return inventory.food.amount(item);
+        }
+        match self {
+            EmbarkSupply::Tools => inventory.tools,
+            EmbarkSupply::Seeds => inventory.seeds,
+            EmbarkSupply::Wood => inventory.wood,
+            EmbarkSupply::Stone => inventory.stone,
+            EmbarkSupply::Metal => inventory.metal,
+            EmbarkSupply::Food | EmbarkSupply::Drink => 0,
+        }
+    }
+
+    /// 增加或减少一个步长的物资
+    pub fn adjust(&self, inventory: &mut GlobalInventory, direction: i32) {
+        let step = self.step();
+        if let Some(item) = self.food_item() {
+            if direction > 0 {
+                inventory.food.add(item, step);
+            } else {
+                for _ in 0..step {
+                    inventory.food.take_where(|existing| existing == item);
+                }
+            }
+            return;
+        }
+        let amount = match self {
+            EmbarkSupply::Tools => &mut inventory.tools,
+            EmbarkSupply::Seeds => &mut inventory.seeds,
+            EmbarkSupply::Wood => &mut inventory.wood,
+            EmbarkSupply::Stone => &mut inventory.stone,
+            EmbarkSupply::Metal => &mut inventory.metal,
+            EmbarkSupply::Food | EmbarkSupply::Drink => return,
+        };
+        *amount = if direction > 0 {
+            *amount + step
+        } else {
+            amount.saturating_sub(step)
+        };
+    }
+}
+
+/// 出发时的一名矮人：身份、专长技能及等级
+#[derive(Clone, Debug)]
+pub struct EmbarkDwarf {
+    pub identity: crate::components::Identity,
+    pub skill: crate::components::SkillKind,
+    pub level: u32,
+}
+
+/// 出发准备方案（点数预算内选择矮人、技能与物资）
+#[derive(Resource, Clone)]
+pub struct EmbarkPlan {
+    pub dwarves: Vec<EmbarkDwarf>,
+    pub supplies: GlobalInventory,
+}
+
+/// 出发点数预算
+pub const EMBARK_POINTS: i32 = 300;
+/// 每名矮人消耗的点数
+pub const EMBARK_DWARF_COST: i32 = 20;
+/// 每级技能消耗的点数
+pub const EMBARK_SKILL_LEVEL_COST: i32 = 5;
+/// 出发矮人数量范围
+pub const EMBARK_MIN_DWARVES: usize = 3;
+pub const EMBARK_MAX_DWARVES: usize = 12;
+/// 出发时技能最高等级
+pub const EMBARK_MAX_SKILL_LEVEL: u32 = 5;
+
+/// 首批移民的年龄范围（岁）
+pub const EMBARK_DWARF_AGES: std::ops::RangeInclusive<u32> = 20..=90;
+
+impl Default for EmbarkPlan {
+    fn default() -> Self {
+        Self::new(&mut crate::name_generator::DwarfNameGenerator::default())
+    }
+}
+
+impl EmbarkPlan {
+    /// 默认方案：七名矮人，身份由名字生成器生成
+    pub fn new(names: &mut crate::name_generator::DwarfNameGenerator) -> Self {
+        use crate::components::SkillKind;
+
+        // 默认七名矮人，各有一项2级专长
+        let specialties = [
+            SkillKind::Mining,
+            SkillKind::Woodcutting,
+            SkillKind::Farming,
+            SkillKind::Crafting,
+            SkillKind::Building,
+            SkillKind::Fighting,
+            SkillKind::Mining,
+        ];
+        let mut taken = std::collections::HashSet::new();
+        Self {
+            dwarves: specialties
+                .iter()
+                .map(|skill| {
+                    let identity = names.generate(0, EMBARK_DWARF_AGES, None, &taken);
+                    taken.insert(identity.full_name());
+                    EmbarkDwarf {
+                        identity,
+                        skill: *skill,
+                        level: 2,
+                    }
+                })
+                .collect(),
+            supplies: GlobalInventory::default(),
+        }
+    }
+
+    /// 已消耗的点数
+    pub fn points_spent(&self) -> i32 {
+        let dwarves: i32 = self
+            .dwarves
+            .iter()
+            .map(|dwarf| EMBARK_DWARF_COST + dwarf.level as i32 * EMBARK_SKILL_LEVEL_COST)
+            .sum();
+        let supplies: i32 = EmbarkSupply::ALL
+            .iter()
+            .map(|supply| {
+                let steps = supply.amount(&self.supplies).div_ceil(supply.step());
+                steps as i32 * supply.cost_per_step()
+            })
+            .sum();
+        dwarves + supplies
+    }
+
+    pub fn points_left(&self) -> i32 {
+        EMBARK_POINTS - self.points_spent()
+    }
+}
+
 /// 游戏时间
 #[derive(Resource)]
 pub struct GameTime {
@@ -39,6 +374,41 @@ pub struct GameTime {
     pub time_scale: f32, // 时间流逝速度倍率 (0.0 = 暂停, 1.0 = 正常, 2.0 = 2倍速)
 }
 
+/// 每个季节的天数
+pub const DAYS_PER_SEASON: u32 = 3;
+/// 每年的天数（四季）
+pub const DAYS_PER_YEAR: u32 = DAYS_PER_SEASON * 4;
+
+/// 季节
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// 某一天所在的季节
+    pub fn of_day(day: u32) -> Self {
+        match (day % DAYS_PER_YEAR) / DAYS_PER_SEASON {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Season::Spring => "春",
+            Season::Summer => "夏",
+            Season::Autumn => "秋",
+            Season::Winter => "冬",
+        }
+    }
+}
+
 impl Default for GameTime {
     fn default() -> Self {
         Self {
@@ -51,6 +421,40 @@ impl Default for GameTime {
 }
 
 impl GameTime {
+    /// 当前年份（从0开始）
+    pub fn year(&self) -> u32 {
+        self.day / DAYS_PER_YEAR
+    }
+
+    /// 当前季节
+    pub fn season(&self) -> Season {
+        Season::of_day(self.day)
+    }
+
+    /// 从开局起算的游戏小时数
+    pub fn total_hours(&self) -> u32 {
+        self.day * 24 + self.hour
+    }
+
+    /// 室外气温（摄氏度）：由季节决定基准，夜里和雨雪天更冷
+    pub fn temperature(&self, weather: crate::components::Weather) -> f32 {
+        use crate::components::Weather;
+        let base = match self.season() {
+            Season::Spring => 12.0,
+            Season::Summer => 24.0,
+            Season::Autumn => 10.0,
+            Season::Winter => -4.0,
+        };
+        let night = if (6..20).contains(&self.hour) { 0.0 } else { -6.0 };
+        let weather = match weather {
+            Weather::Sunny => 2.0,
+            Weather::Cloudy => 0.0,
+            Weather::Rain => -3.0,
+            Weather::Snow => -8.0,
+        };
+        base + night + weather
+    }
+
     /// 获取当前时间的光照强度 (0.0 = 黑夜, 1.0 = 白天)
     #[allow(dead_code)] // 保留用于未来更复杂的昼夜系统
     pub fn get_daylight(&self) -> f32 {
@@ -90,12 +494,619 @@ impl GameTime {
     }
 }
 
+/// 要塞编年史条目类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChronicleKind {
+    Founded,
+    Migrant,
+    Death,
+    Birth,
+    Building,
+    Artifact,
+    Tantrum,
+    Marriage,
+    Battle,
+}
+
+impl ChronicleKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChronicleKind::Founded => "建立",
+            ChronicleKind::Migrant => "移民",
+            ChronicleKind::Death => "死亡",
+            ChronicleKind::Birth => "出生",
+            ChronicleKind::Building => "建筑",
+            ChronicleKind::Artifact => "传世之作",
+            ChronicleKind::Tantrum => "发脾气",
+            ChronicleKind::Marriage => "婚姻",
+            ChronicleKind::Battle => "战斗",
+        }
+    }
+}
+
+/// 要塞编年史条目
+#[derive(Clone, Debug)]
+pub struct ChronicleEntry {
+    pub day: u32,
+    pub hour: u32,
+    pub kind: ChronicleKind,
+    pub text: String,
+}
+
+/// 玩家要塞的编年史（记录游戏内发生的重要事件）
+#[derive(Resource, Default)]
+pub struct FortressChronicle {
+    pub entries: Vec<ChronicleEntry>,
+}
+
+impl FortressChronicle {
+    /// 以当前游戏时间记录一条编年史
+    pub fn record(&mut self, game_time: &GameTime, kind: ChronicleKind, text: String) {
+        self.entries.push(ChronicleEntry {
+            day: game_time.day,
+            hour: game_time.hour,
+            kind,
+            text,
+        });
+    }
+}
+
+/// 当前天气（局部地图）
+#[derive(Resource)]
+pub struct CurrentWeather {
+    pub weather: crate::components::Weather,
+    /// 距离下次变天的游戏小时数
+    pub hours_left: u32,
+}
+
+impl Default for CurrentWeather {
+    fn default() -> Self {
+        Self {
+            weather: crate::components::Weather::Sunny,
+            hours_left: 6,
+        }
+    }
+}
+
 /// 选中的矮人
 #[derive(Resource, Default)]
 pub struct SelectedDwarf {
     pub entity: Option<Entity>,
 }
 
+/// 工作目标格子的预约：同一格子同时只能被一名矮人作为工作目标
+#[derive(Resource, Default)]
+pub struct TileReservations {
+    tiles: std::collections::HashMap<(i32, i32), Entity>,
+    holders: std::collections::HashMap<Entity, (i32, i32)>,
+}
+
+impl TileReservations {
+    /// 预约格子（同时释放该矮人之前的预约）；格子已被他人预约时覆盖，用于玩家指派
+    pub fn reserve(&mut self, tile: (i32, i32), entity: Entity) {
+        self.release(entity);
+        if let Some(previous) = self.tiles.insert(tile, entity) {
+            self.holders.remove(&previous);
+        }
+        self.holders.insert(entity, tile);
+    }
+
+    /// 释放矮人持有的预约
+    pub fn release(&mut self, entity: Entity) {
+        if let Some(tile) = self.holders.remove(&entity) {
+            self.tiles.remove(&tile);
+        }
+    }
+
+    /// 格子是否已被其他矮人预约
+    pub fn is_reserved_by_other(&self, tile: (i32, i32), entity: Entity) -> bool {
+        self.tiles.get(&tile).is_some_and(|holder| *holder != entity)
+    }
+
+    /// 矮人是否持有该格子的预约
+    pub fn holds(&self, entity: Entity, tile: (i32, i32)) -> bool {
+        self.holders.get(&entity) == Some(&tile)
+    }
+
+    /// 丢弃已不存在的矮人（离开地图、死亡）持有的预约
+    pub fn retain_holders(&mut self, mut keep: impl FnMut(Entity) -> bool) {
+        self.holders.retain(|entity, _| keep(*entity));
+        let holders = &self.holders;
+        self.tiles.retain(|_, entity| holders.contains_key(entity));
+    }
+}
+
+/// 要塞最多的小队数量
+pub const MAX_SQUADS: usize = 4;
+const SQUAD_NUMERALS: [&str; MAX_SQUADS] = ["一", "二", "三", "四"];
+
+/// 要塞警戒等级：警戒时平民撤到安全地点
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlertLevel {
+    #[default]
+    Normal,
+    Alert,
+}
+
+impl AlertLevel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertLevel::Normal => "平时",
+            AlertLevel::Alert => "警戒",
+        }
+    }
+}
+
+/// 小队制服：队员会从金属库存中领取缺少的装备
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Uniform {
+    #[default]
+    Civilian,
+    Light,
+    Heavy,
+}
+
+impl Uniform {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Uniform::Civilian => "便服",
+            Uniform::Light => "轻装",
+            Uniform::Heavy => "重装",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Uniform::Civilian => Uniform::Light,
+            Uniform::Light => Uniform::Heavy,
+            Uniform::Heavy => Uniform::Civilian,
+        }
+    }
+
+    pub fn needs_weapon(&self) -> bool {
+        !matches!(self, Uniform::Civilian)
+    }
+
+    pub fn needs_armour(&self) -> bool {
+        matches!(self, Uniform::Heavy)
+    }
+}
+
+/// 训练安排：值勤时间内没有命令的队员前往训练地点操练
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrainingSchedule {
+    #[default]
+    Off,
+    Mornings,
+    FullTime,
+}
+
+impl TrainingSchedule {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrainingSchedule::Off => "不训练",
+            TrainingSchedule::Mornings => "上午训练",
+            TrainingSchedule::FullTime => "全职训练",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            TrainingSchedule::Off => TrainingSchedule::Mornings,
+            TrainingSchedule::Mornings => TrainingSchedule::FullTime,
+            TrainingSchedule::FullTime => TrainingSchedule::Off,
+        }
+    }
+
+    /// 该小时是否在训练时间内
+    pub fn on_duty(&self, hour: u32) -> bool {
+        match self {
+            TrainingSchedule::Off => false,
+            TrainingSchedule::Mornings => (6..12).contains(&hour),
+            TrainingSchedule::FullTime => true,
+        }
+    }
+}
+
+/// 小队命令
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SquadOrder {
+    #[default]
+    None,
+    /// 驻守在指定位置
+    Station((i32, i32)),
+    /// 沿巡逻路线往返
+    Patrol,
+    /// 击杀指定的敌对生物
+    Kill(Entity),
+    /// 撤退到安全地点
+    Retreat,
+}
+
+impl SquadOrder {
+    pub fn describe(&self) -> String {
+        match self {
+            SquadOrder::None => "无命令".to_string(),
+            SquadOrder::Station((x, y)) => format!("驻守 ({}, {})", x, y),
+            SquadOrder::Patrol => "巡逻".to_string(),
+            SquadOrder::Kill(_) => "击杀目标".to_string(),
+            SquadOrder::Retreat => "撤退".to_string(),
+        }
+    }
+}
+
+/// 一支小队，队员以姓名记录
+#[derive(Clone, Debug)]
+pub struct Squad {
+    pub name: String,
+    pub members: Vec<String>,
+    pub uniform: Uniform,
+    pub training: TrainingSchedule,
+    pub order: SquadOrder,
+    pub patrol_route: Vec<(i32, i32)>,
+    pub patrol_index: usize,
+}
+
+impl Squad {
+    /// 当前巡逻目标点
+    pub fn patrol_point(&self) -> Option<(i32, i32)> {
+        self.patrol_route.get(self.patrol_index % self.patrol_route.len().max(1)).copied()
+    }
+
+    pub fn advance_patrol(&mut self) {
+        if !self.patrol_route.is_empty() {
+            self.patrol_index = (self.patrol_index + 1) % self.patrol_route.len();
+        }
+    }
+}
+
+/// 要塞军事：小队编制与警戒等级
+#[derive(Resource, Default)]
+pub struct Military {
+    pub squads: Vec<Squad>,
+    pub alert: AlertLevel,
+}
+
+impl Military {
+    /// 新建一支小队，已达上限时返回 None
+    pub fn add_squad(&mut self) -> Option<usize> {
+        let index = self.squads.len();
+        let numeral = SQUAD_NUMERALS.get(index)?;
+        self.squads.push(Squad {
+            name: format!("第{}小队", numeral),
+            members: Vec::new(),
+            uniform: Uniform::default(),
+            training: TrainingSchedule::default(),
+            order: SquadOrder::default(),
+            patrol_route: Vec::new(),
+            patrol_index: 0,
+        });
+        Some(index)
+    }
+
+    /// 矮人所属小队的序号
+    pub fn squad_of(&self, name: &str) -> Option<usize> {
+        self.squads
+            .iter()
+            .position(|squad| squad.members.iter().any(|member| member == name))
+    }
+
+    /// 编入小队（先离开原来的小队）
+    pub fn enlist(&mut self, squad: usize, name: &str) {
+        self.discharge(name);
+        if let Some(squad) = self.squads.get_mut(squad) {
+            squad.members.push(name.to_string());
+        }
+    }
+
+    /// 退出小队，返回是否原本在队中
+    pub fn discharge(&mut self, name: &str) -> bool {
+        let mut found = false;
+        for squad in self.squads.iter_mut() {
+            let before = squad.members.len();
+            squad.members.retain(|member| member != name);
+            found |= squad.members.len() != before;
+        }
+        found
+    }
+}
+
+/// 军事面板中等待在地图上点选位置的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SquadPlacementKind {
+    Station,
+    PatrolPoint,
+    KillTarget,
+}
+
+impl SquadPlacementKind {
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            SquadPlacementKind::Station => "点击地图选择驻守位置",
+            SquadPlacementKind::PatrolPoint => "点击地图添加巡逻点",
+            SquadPlacementKind::KillTarget => "点击敌人指定击杀目标",
+        }
+    }
+}
+
+/// 军事面板的点选状态：(小队序号, 操作)
+#[derive(Resource, Default)]
+pub struct SquadPlacement {
+    pub pending: Option<(usize, SquadPlacementKind)>,
+}
+
+/// 每个地块最多划定的区域数
+pub const MAX_ZONES: usize = 8;
+
+/// 区域类型：洞区限制成员的活动范围，其余区域吸引矮人前往
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoneKind {
+    /// 洞区：成员只在洞区内工作和闲逛
+    Burrow,
+    /// 会客区：闲逛的矮人喜欢到这里聚会
+    MeetingArea,
+    /// 牧场：区域内的农耕目标更受青睐
+    Pasture,
+    /// 医院：受伤的矮人到这里休养，恢复更快
+    Hospital,
+}
+
+impl ZoneKind {
+    pub const ALL: [ZoneKind; 4] = [
+        ZoneKind::Burrow,
+        ZoneKind::MeetingArea,
+        ZoneKind::Pasture,
+        ZoneKind::Hospital,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ZoneKind::Burrow => "洞区",
+            ZoneKind::MeetingArea => "会客区",
+            ZoneKind::Pasture => "牧场",
+            ZoneKind::Hospital => "医院",
+        }
+    }
+
+    /// 地图上覆盖层的颜色
+    pub fn overlay_color(&self) -> Color {
+        match self {
+            ZoneKind::Burrow => Color::srgba(0.9, 0.6, 0.2, 0.25),
+            ZoneKind::MeetingArea => Color::srgba(0.3, 0.5, 1.0, 0.25),
+            ZoneKind::Pasture => Color::srgba(0.4, 0.9, 0.3, 0.25),
+            ZoneKind::Hospital => Color::srgba(1.0, 0.3, 0.4, 0.25),
+        }
+    }
+}
+
+/// 玩家划定的区域
+#[derive(Clone, Debug)]
+pub struct Zone {
+    pub name: String,
+    pub kind: ZoneKind,
+    pub tiles: std::collections::HashSet<(i32, i32)>,
+    /// 洞区成员（按名字记录），其他区域不使用
+    pub members: Vec<String>,
+    /// 警戒时平民撤进这个洞区避难（每个地块最多指定一个），其他区域不使用
+    pub refuge: bool,
+}
+
+/// 从一组地块中随机挑一块，优先挑选 `near` 附近 `radius` 格以内的
+pub fn random_tile_near<'a>(
+    tiles: impl IntoIterator<Item = &'a (i32, i32)>,
+    near: (i32, i32),
+    radius: i32,
+    rng: &mut impl rand::Rng,
+) -> Option<(i32, i32)> {
+    let all: Vec<(i32, i32)> = tiles.into_iter().copied().collect();
+    let nearby: Vec<(i32, i32)> = all
+        .iter()
+        .filter(|(x, y)| (x - near.0).abs() <= radius && (y - near.1).abs() <= radius)
+        .copied()
+        .collect();
+    let pool = if nearby.is_empty() { all } else { nearby };
+    (!pool.is_empty()).then(|| pool[rng.gen_range(0..pool.len())])
+}
+
+/// 区域面板的画笔：选中区域后在地图上按住左键涂抹或擦除
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoneBrush {
+    Paint,
+    Erase,
+}
+
+/// 区域面板的编辑状态
+#[derive(Resource, Default)]
+pub struct ZoneEditor {
+    /// 当前选中的区域序号
+    pub selected: Option<usize>,
+    pub brush: Option<ZoneBrush>,
+}
+
+/// 摆放在地块上的家具
+#[derive(Clone, Debug)]
+pub struct PlacedFurniture {
+    pub kind: crate::components::FurnitureKind,
+    pub material: crate::components::ItemMaterial,
+    pub pos: (i32, i32),
+    /// 大师级工匠偶尔造出的传世之作
+    pub artifact: bool,
+}
+
+/// 传世之作对房间品质的贡献是普通家具的几倍
+pub const ARTIFACT_VALUE_MULTIPLIER: f32 = 3.0;
+
+impl PlacedFurniture {
+    /// 对房间品质的贡献
+    pub fn value(&self) -> f32 {
+        let value = self.kind.value() * self.material.quality();
+        if self.artifact {
+            value * ARTIFACT_VALUE_MULTIPLIER
+        } else {
+            value
+        }
+    }
+}
+
+/// 建在地块上的作坊
+#[derive(Clone, Debug)]
+pub struct PlacedWorkshop {
+    pub kind: crate::components::WorkshopKind,
+    pub material: crate::components::ItemMaterial,
+    pub pos: (i32, i32),
+}
+
+/// 当前地图上各地块最近被矮人踩过的次数（每小时衰减），踩得多的草地变成土路
+#[derive(Resource, Default)]
+pub struct Trampling {
+    pub steps: std::collections::HashMap<(i32, i32), f32>,
+}
+
+/// 散落在地上的食物（如钓上来的鱼），等矮人捡回仓库；放久了也会腐烂
+#[derive(Clone, Debug)]
+pub struct LooseFood {
+    pub kind: crate::components::FoodKind,
+    pub amount: u32,
+    pub pos: (i32, i32),
+    pub age_hours: u32,
+}
+
+/// 散落在地上的木材、石头和金属（采集时随身装不下的部分），等矮人捡回仓库
+#[derive(Clone, Debug)]
+pub struct LooseMaterial {
+    pub kind: crate::components::MaterialKind,
+    pub amount: u32,
+    pub pos: (i32, i32),
+}
+
+/// 地块水域里的鱼群：被钓走后按剩余数量慢慢繁殖回来，钓得太狠会越来越难钓
+#[derive(Clone, Debug)]
+pub struct FishPopulation {
+    pub fish: u32,
+    pub capacity: u32,
+}
+
+impl FishPopulation {
+    /// 按世界地图格子的生物群系决定鱼群规模：海里最多，湖泊、河流次之，其余只有零星的池塘
+    pub fn for_cell(cell: Option<&crate::world_map_data::WorldCell>) -> Self {
+        use crate::world_map_data::WorldBiome;
+        let capacity = match cell {
+            Some(cell) if cell.biome == WorldBiome::Ocean => 200,
+            Some(cell) if cell.biome == WorldBiome::Lake => 120,
+            Some(cell) if cell.biome == WorldBiome::River || cell.has_river() => 80,
+            Some(cell) if cell.biome == WorldBiome::Swamp => 40,
+            _ => 15,
+        };
+        Self { fish: capacity, capacity }
+    }
+
+    /// 剩余鱼群占满额的比例
+    pub fn abundance(&self) -> f32 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            self.fish as f32 / self.capacity as f32
+        }
+    }
+}
+
+/// 玩家下达、等待矮人建造的订单
+#[derive(Clone, Debug)]
+pub struct BuildOrder {
+    pub pos: (i32, i32),
+    pub building: crate::components::BuildingType,
+    pub material: crate::components::ItemMaterial,
+}
+
+/// 房间品质达到该值算作"好房间"，睡在里面或在里面吃饭会有好心情
+pub const GOOD_ROOM_QUALITY: f32 = 20.0;
+/// 房间最多包含的地块数，超过则视为没有围起来
+pub const MAX_ROOM_TILES: usize = 48;
+
+/// 房间用途
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RoomUse {
+    /// 分配给某名矮人的卧室
+    Bedroom(String),
+    /// 大家共用的餐厅
+    DiningHall,
+}
+
+impl RoomUse {
+    pub fn describe(&self) -> String {
+        match self {
+            RoomUse::Bedroom(owner) => format!("{} 的卧室", owner),
+            RoomUse::DiningHall => "餐厅".to_string(),
+        }
+    }
+}
+
+/// 由墙（不可行走的地块）和门围出的房间，房间检测系统根据地形和家具重新计算
+#[derive(Clone, Debug)]
+pub struct Room {
+    /// 房间内坐标最小的地块，用作房间的标识（房间用途按它保存）
+    pub anchor: (i32, i32),
+    pub tiles: std::collections::HashSet<(i32, i32)>,
+    /// 房间内家具和门的价值之和
+    pub quality: f32,
+    pub beds: Vec<(i32, i32)>,
+    pub tables: usize,
+    pub usage: Option<RoomUse>,
+}
+
+impl Room {
+    pub fn quality_label(&self) -> &'static str {
+        match self.quality {
+            q if q < 10.0 => "简陋",
+            q if q < GOOD_ROOM_QUALITY => "普通",
+            q if q < 40.0 => "舒适",
+            _ => "豪华",
+        }
+    }
+}
+
+/// 当前地块上检测到的房间
+#[derive(Resource, Default)]
+pub struct Rooms {
+    pub rooms: Vec<Room>,
+}
+
+impl Rooms {
+    pub fn room_at(&self, pos: (i32, i32)) -> Option<&Room> {
+        self.rooms.iter().find(|room| room.tiles.contains(&pos))
+    }
+
+    /// 分配给某名矮人、且至少有一张床的卧室
+    pub fn bedroom_of(&self, name: &str) -> Option<&Room> {
+        self.rooms.iter().find(|room| {
+            !room.beds.is_empty() && matches!(&room.usage, Some(RoomUse::Bedroom(owner)) if owner == name)
+        })
+    }
+
+    /// 有桌子的餐厅
+    pub fn dining_halls(&self) -> impl Iterator<Item = &Room> {
+        self.rooms
+            .iter()
+            .filter(|room| room.tables > 0 && room.usage == Some(RoomUse::DiningHall))
+    }
+}
+
+/// 建造面板的状态：选中要摆放的家具和材质后点击地图下订单
+#[derive(Resource)]
+pub struct BuildMenu {
+    pub selected: Option<crate::components::BuildingType>,
+    pub material: crate::components::ItemMaterial,
+}
+
+impl Default for BuildMenu {
+    fn default() -> Self {
+        Self {
+            selected: None,
+            material: crate::components::ItemMaterial::Wood,
+        }
+    }
+}
+
 /// 游戏是否已初始化（用于区分首次进入和从暂停恢复）
 #[derive(Resource, Default)]
 pub struct GameInitialized {
@@ -105,7 +1116,7 @@ pub struct GameInitialized {
 /// 世界生成种子（确保地形和矮人生成使用相同的地形数据）
 #[derive(Resource)]
 pub struct WorldSeed {
-    pub seed: u32,
+    pub seed: u64,
 }
 
 impl Default for WorldSeed {
@@ -137,6 +1148,46 @@ pub struct StoredMapTile {
     pub has_tree_sway: bool,
     pub water_phase: f32,
     pub tree_offset: f32,
+    /// 地块上建成的建筑结构
+    pub construction: Option<crate::components::Construction>,
+    /// 树苗已经生长的小时数（冬天不长）
+    pub growth_hours: u32,
+}
+
+impl StoredMapTile {
+    /// 在地块上建成建筑结构：改写可行走状态和外观，水面不再起伏、树不再摇晃
+    pub fn construct(&mut self, construction: crate::components::Construction) {
+        // 建在树苗上时先把树苗清掉
+        if self.terrain_type == crate::components::TerrainType::Sapling {
+            self.terrain_type = crate::components::TerrainType::Dirt;
+            self.growth_hours = 0;
+        }
+        let (color, ascii_char, char_color) = crate::world::construction_visual(construction);
+        self.walkable = construction.kind.walkable();
+        self.color = color;
+        self.ascii_char = ascii_char;
+        self.char_color = char_color;
+        self.has_water_animation = false;
+        self.has_tree_sway = false;
+        self.construction = Some(construction);
+    }
+
+    /// 植被变化（砍树、长草、长出树苗、踩出土路）：改写地形并重新挑选外观，树木随风摇晃
+    pub fn set_vegetation(
+        &mut self,
+        terrain: crate::components::TerrainType,
+        biome: Option<crate::world_map_data::WorldBiome>,
+        rng: &mut impl rand::Rng,
+    ) {
+        let (color, ascii_char, char_color) = crate::world::tile_visual(rng, terrain, biome);
+        self.terrain_type = terrain;
+        self.walkable = crate::connectivity::is_walkable_terrain(terrain);
+        self.color = color;
+        self.ascii_char = ascii_char;
+        self.char_color = char_color;
+        self.has_tree_sway = terrain == crate::components::TerrainType::Tree;
+        self.growth_hours = 0;
+    }
 }
 
 /// 存储的矮人数据
@@ -150,6 +1201,17 @@ pub struct StoredDwarf {
     pub happiness: f32,
     pub current_task: Option<crate::components::Task>,
     pub work_progress: f32,
+    pub skills: crate::components::Skills,
+    pub labours: crate::components::Labours,
+    pub personality: crate::components::Personality,
+    pub thoughts: crate::components::Thoughts,
+    pub relationships: crate::components::Relationships,
+    pub age: crate::components::Age,
+    pub identity: crate::components::Identity,
+    pub equipment: crate::components::Equipment,
+    pub fatigue: f32,
+    pub thirst: f32,
+    pub shift: crate::components::Shift,
     /// 上次更新时的游戏时间（用于全局模拟）
     pub last_update_day: u32,
     pub last_update_hour: u32,
@@ -166,4 +1228,169 @@ pub struct GeneratedMapsRegistry {
     pub spawn_location: Option<IVec2>,
     /// 矮人是否已经生成（防止重复生成）
     pub dwarves_spawned: bool,
+    /// 要塞仓库在出生地块上的局部坐标，矮人把采集到的资源运到这里
+    pub stockpile: Option<(i32, i32)>,
+    /// 每个地块上玩家划定的区域 - key: 世界坐标(x,y)
+    pub zones: std::collections::HashMap<IVec2, Vec<Zone>>,
+    /// 每个地块上摆放的家具
+    pub furniture: std::collections::HashMap<IVec2, Vec<PlacedFurniture>>,
+    /// 每个地块上建好的作坊
+    pub workshops: std::collections::HashMap<IVec2, Vec<PlacedWorkshop>>,
+    /// 每个地块上散落在地上的食物
+    pub loose_food: std::collections::HashMap<IVec2, Vec<LooseFood>>,
+    /// 每个地块上散落在地上的材料
+    pub loose_materials: std::collections::HashMap<IVec2, Vec<LooseMaterial>>,
+    /// 每个地块水域里的鱼群（第一次有人钓鱼时按生物群系创建）
+    pub fish_stocks: std::collections::HashMap<IVec2, FishPopulation>,
+    /// 每个地块的植被模拟到了哪个游戏小时（`GameTime::total_hours`）
+    pub vegetation_clock: std::collections::HashMap<IVec2, u32>,
+    /// 每个地块上等待建造的订单
+    pub build_orders: std::collections::HashMap<IVec2, Vec<BuildOrder>>,
+    /// 每个地块上玩家指定的房间用途 - key: 房间标识地块
+    pub room_uses: std::collections::HashMap<IVec2, std::collections::HashMap<(i32, i32), RoomUse>>,
+}
+
+impl GeneratedMapsRegistry {
+    /// 当前地块上的要塞仓库位置（只有要塞所在的地块才有）
+    pub fn fortress_stockpile(&self, active: Option<IVec2>) -> Option<(i32, i32)> {
+        self.stockpile.filter(|_| active.is_some() && active == self.spawn_location)
+    }
+
+    /// 当前地块上的区域
+    pub fn local_zones(&self, active: Option<IVec2>) -> &[Zone] {
+        active
+            .and_then(|coord| self.zones.get(&coord))
+            .map(|zones| zones.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// 矮人所属洞区的地块并集；不属于任何洞区（或洞区还没有地块）的矮人不受限制（返回 None）
+    pub fn burrow_tiles(&self, active: Option<IVec2>, name: &str) -> Option<std::collections::HashSet<(i32, i32)>> {
+        let tiles: std::collections::HashSet<(i32, i32)> = self
+            .local_zones(active)
+            .iter()
+            .filter(|zone| zone.kind == ZoneKind::Burrow && zone.members.iter().any(|member| member == name))
+            .flat_map(|zone| zone.tiles.iter().copied())
+            .collect();
+        (!tiles.is_empty()).then_some(tiles)
+    }
+
+    /// 当前地块上指定为避难所的洞区地块；没有指定或洞区还没有地块时返回 None
+    pub fn refuge_tiles(&self, active: Option<IVec2>) -> Option<&std::collections::HashSet<(i32, i32)>> {
+        self.local_zones(active)
+            .iter()
+            .find(|zone| zone.kind == ZoneKind::Burrow && zone.refuge)
+            .map(|zone| &zone.tiles)
+            .filter(|tiles| !tiles.is_empty())
+    }
+
+    /// 地块是否位于当前地块上某类区域之内
+    pub fn in_zone(&self, active: Option<IVec2>, kind: ZoneKind, pos: (i32, i32)) -> bool {
+        self.local_zones(active)
+            .iter()
+            .any(|zone| zone.kind == kind && zone.tiles.contains(&pos))
+    }
+
+    /// 从所有洞区中除名，并收回分给他的卧室（矮人死亡时调用）
+    pub fn forget_dwarf(&mut self, name: &str) {
+        for zone in self.zones.values_mut().flatten() {
+            zone.members.retain(|member| member != name);
+        }
+        for uses in self.room_uses.values_mut() {
+            uses.retain(|_, usage| *usage != RoomUse::Bedroom(name.to_string()));
+        }
+    }
+
+    /// 当前地块上的家具
+    pub fn local_furniture(&self, active: Option<IVec2>) -> &[PlacedFurniture] {
+        active
+            .and_then(|coord| self.furniture.get(&coord))
+            .map(|furniture| furniture.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// 当前地块上的作坊
+    pub fn local_workshops(&self, active: Option<IVec2>) -> &[PlacedWorkshop] {
+        active
+            .and_then(|coord| self.workshops.get(&coord))
+            .map(|workshops| workshops.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// 当前地块上散落的食物
+    pub fn local_loose_food(&self, active: Option<IVec2>) -> &[LooseFood] {
+        active
+            .and_then(|coord| self.loose_food.get(&coord))
+            .map(|food| food.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// 当前地块上散落的材料
+    pub fn local_loose_materials(&self, active: Option<IVec2>) -> &[LooseMaterial] {
+        active
+            .and_then(|coord| self.loose_materials.get(&coord))
+            .map(|materials| materials.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// 把食物放在地上，同一格上同种的食物堆在一起
+    pub fn drop_food(&mut self, coord: IVec2, kind: crate::components::FoodKind, amount: u32, pos: (i32, i32)) {
+        if amount == 0 {
+            return;
+        }
+        let loose = self.loose_food.entry(coord).or_default();
+        match loose.iter_mut().find(|food| food.pos == pos && food.kind == kind) {
+            Some(food) => food.amount += amount,
+            None => loose.push(LooseFood {
+                kind,
+                amount,
+                pos,
+                age_hours: 0,
+            }),
+        }
+    }
+
+    /// 把材料放在地上，同一格上同种的材料堆在一起
+    pub fn drop_material(&mut self, coord: IVec2, kind: crate::components::MaterialKind, amount: u32, pos: (i32, i32)) {
+        if amount == 0 {
+            return;
+        }
+        let loose = self.loose_materials.entry(coord).or_default();
+        match loose.iter_mut().find(|material| material.pos == pos && material.kind == kind) {
+            Some(material) => material.amount += amount,
+            None => loose.push(LooseMaterial { kind, amount, pos }),
+        }
+    }
+
+    /// 当前地块上还能不能钓到鱼（还没人钓过的水域算作能钓）
+    pub fn local_has_fish(&self, active: Option<IVec2>) -> bool {
+        active.is_some_and(|coord| self.fish_stocks.get(&coord).is_none_or(|stock| stock.fish > 0))
+    }
+
+    /// 当前地块上某个位置的地图块
+    pub fn local_tile(&self, active: Option<IVec2>, pos: (i32, i32)) -> Option<&StoredMapTile> {
+        active
+            .and_then(|coord| self.maps.get(&coord))
+            .and_then(|tiles| tiles.iter().find(|tile| (tile.x, tile.y) == pos))
+    }
+
+    /// 当前地块上已建成的建筑结构
+    pub fn local_constructions(
+        &self,
+        active: Option<IVec2>,
+    ) -> impl Iterator<Item = ((i32, i32), crate::components::Construction)> + '_ {
+        active
+            .and_then(|coord| self.maps.get(&coord))
+            .into_iter()
+            .flatten()
+            .filter_map(|tile| tile.construction.map(|construction| ((tile.x, tile.y), construction)))
+    }
+
+    /// 当前地块上等待建造的订单
+    pub fn local_build_orders(&self, active: Option<IVec2>) -> &[BuildOrder] {
+        active
+            .and_then(|coord| self.build_orders.get(&coord))
+            .map(|orders| orders.as_slice())
+            .unwrap_or(&[])
+    }
 }
@@ -0,0 +1,431 @@
+use crate::components::*;
+use crate::pathfinding::NavGrid;
+use crate::resources::*;
+use crate::ui_framework::*;
+use crate::world::*;
+use bevy::prelude::*;
+
+/// 普通按钮颜色
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.3, 0.4);
+/// 选中的家具按钮颜色
+const SELECTED_COLOR: Color = Color::srgb(0.6, 0.5, 0.15);
+/// 取消按钮颜色
+const CANCEL_COLOR: Color = Color::srgb(0.5, 0.15, 0.15);
+/// 家具可选的材质
+const FURNITURE_MATERIALS: [ItemMaterial; 3] = [ItemMaterial::Wood, ItemMaterial::Stone, ItemMaterial::Iron];
+/// 建筑结构可用的材质
+const CONSTRUCTION_MATERIALS: [ItemMaterial; 2] = [ItemMaterial::Wood, ItemMaterial::Stone];
+
+/// 建造面板中订单和房间列表的容器
+#[derive(Component)]
+pub struct BuildPanelRows;
+
+/// 建造面板按钮
+#[derive(Component, Clone, Copy)]
+pub enum BuildButton {
+    /// 选择要摆放的家具、作坊或建筑结构
+    Select(BuildingType),
+    /// 切换材质
+    CycleMaterial,
+    /// 取消所有待建订单
+    CancelOrders,
+    /// 把房间设为选中矮人的卧室
+    Bedroom((i32, i32)),
+    /// 把房间设为餐厅
+    DiningHall((i32, i32)),
+    /// 取消房间用途
+    ClearUse((i32, i32)),
+}
+
+/// 设置建造面板（初始隐藏，B键切换）
+pub fn setup_build_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<UITheme>,
+) {
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let mut builder = PanelBuilder::new(commands.reborrow(), font.clone(), theme.clone());
+
+    // 创建建造面板（左侧中间）
+    let build_config = PanelConfig {
+        anchor: PanelAnchor::MiddleLeft,
+        offset: Vec2::new(15.0, 0.0),
+        min_width: 480.0,
+        min_height: 140.0,
+        background_color: Color::srgba(0.09, 0.08, 0.05, 0.94),
+        border_color: Some(Color::srgba(0.8, 0.65, 0.3, 0.7)),
+        padding: 12.0,
+    };
+
+    let build_panel = builder.create_hidden_panel("build_panel", build_config, BuildingMenuPanel);
+    builder.add_title(build_panel, "◆ 建造与房间 ◆");
+    builder.add_small_text(
+        build_panel,
+        "选择家具、作坊或建筑结构后点击地图下订单，点击已有订单可取消；用墙和门围起床或桌子即成房间",
+    );
+
+    let rows = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::top(Val::Px(8.0)),
+                ..default()
+            },
+            BuildPanelRows,
+        ))
+        .id();
+    commands.entity(build_panel).add_child(rows);
+}
+
+/// 切换建造面板显示/隐藏（B键），隐藏时收起选择
+pub fn toggle_build_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<(&mut UIPanel, &mut Node)>,
+    mut build_menu: ResMut<BuildMenu>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyB) {
+        toggle_panel_visibility(&mut panel_query, "build_panel");
+        if build_menu.selected.is_some() {
+            build_menu.selected = None;
+        }
+    }
+}
+
+/// 处理建造面板按钮点击
+pub fn build_button_system(
+    interaction_query: Query<(&Interaction, &BuildButton), Changed<Interaction>>,
+    selected: Res<SelectedDwarf>,
+    dwarves: Query<&Dwarf>,
+    active_local: Res<ActiveLocalMap>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut build_menu: ResMut<BuildMenu>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let Some(coord) = active_local.coord else {
+        return;
+    };
+
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            BuildButton::Select(building) => {
+                // 再次点击同一项收起选择
+                build_menu.selected = (build_menu.selected != Some(building)).then_some(building);
+            }
+            BuildButton::CycleMaterial => {
+                let index = FURNITURE_MATERIALS
+                    .iter()
+                    .position(|material| *material == build_menu.material)
+                    .unwrap_or(0);
+                build_menu.material = FURNITURE_MATERIALS[(index + 1) % FURNITURE_MATERIALS.len()];
+            }
+            BuildButton::CancelOrders => {
+                if let Some(orders) = map_registry.build_orders.get_mut(&coord) {
+                    logger.info(format!("取消了 {} 个建造订单", orders.len()));
+                    orders.clear();
+                }
+            }
+            BuildButton::Bedroom(anchor) => {
+                let Some(dwarf) = selected.entity.and_then(|entity| dwarves.get(entity).ok()) else {
+                    logger.warning("请先选中一名矮人".to_string());
+                    continue;
+                };
+                super::assign_room(&mut map_registry, coord, anchor, Some(RoomUse::Bedroom(dwarf.name.clone())));
+                logger.info(format!("{} 分到了一间卧室", dwarf.name));
+            }
+            BuildButton::DiningHall(anchor) => {
+                super::assign_room(&mut map_registry, coord, anchor, Some(RoomUse::DiningHall));
+                logger.info("设立了一间餐厅".to_string());
+            }
+            BuildButton::ClearUse(anchor) => {
+                super::assign_room(&mut map_registry, coord, anchor, None);
+            }
+        }
+    }
+}
+
+/// 在地图上点击下建造订单，点击已有订单则取消。
+/// 需在矮人选择系统之前运行，下订单消耗掉这次左键点击
+#[allow(clippy::too_many_arguments)]
+pub fn build_placement_system(
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    nav_grid: Res<NavGrid>,
+    build_menu: Res<BuildMenu>,
+    active_local: Res<ActiveLocalMap>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let (Some(building), Some(coord)) = (build_menu.selected, active_local.coord) else {
+        return;
+    };
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // 点在界面按钮上的不算
+    if interactions.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+    let Ok(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
+        return;
+    };
+
+    // 转换为网格坐标
+    let grid_x = ((world_position.x + (WORLD_WIDTH as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+    let grid_y = ((world_position.y + (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+    if !(0..WORLD_WIDTH).contains(&grid_x) || !(0..WORLD_HEIGHT).contains(&grid_y) {
+        return;
+    }
+    mouse_button.clear_just_pressed(MouseButton::Left);
+    let pos = (grid_x, grid_y);
+
+    if map_registry.local_build_orders(Some(coord)).iter().any(|order| order.pos == pos) {
+        if let Some(orders) = map_registry.build_orders.get_mut(&coord) {
+            orders.retain(|order| order.pos != pos);
+        }
+        return;
+    }
+    if map_registry.local_furniture(Some(coord)).iter().any(|item| item.pos == pos) {
+        logger.warning("那里已经有家具了".to_string());
+        return;
+    }
+    if map_registry.local_workshops(Some(coord)).iter().any(|workshop| workshop.pos == pos) {
+        logger.warning("那里已经有作坊了".to_string());
+        return;
+    }
+    let Some(tile) = map_registry.local_tile(Some(coord), pos) else {
+        return;
+    };
+    match building {
+        BuildingType::Construction(kind) => {
+            if !CONSTRUCTION_MATERIALS.contains(&build_menu.material) {
+                logger.warning("建筑结构只能用木料或石料建造".to_string());
+                return;
+            }
+            if tile.construction.is_some() {
+                logger.warning("那里已经建有建筑结构了".to_string());
+                return;
+            }
+            if !kind.can_build_on(tile.terrain_type) {
+                let hint = match kind {
+                    ConstructionKind::Bridge => "桥只能架在水面上",
+                    _ => "建筑结构只能建在草地、泥土、树苗或石地上",
+                };
+                logger.warning(hint.to_string());
+                return;
+            }
+        }
+        _ => {
+            let on_door = tile.construction.is_some_and(|construction| construction.kind == ConstructionKind::Door);
+            if !nav_grid.is_walkable(grid_x, grid_y) || on_door {
+                logger.warning("家具和作坊只能摆在可以行走的地块上，且不能挡住门".to_string());
+                return;
+            }
+        }
+    }
+    map_registry.build_orders.entry(coord).or_default().push(BuildOrder {
+        pos,
+        building,
+        material: build_menu.material,
+    });
+}
+
+/// 面板可见时，在选择、订单、家具、房间或选中矮人变化后重建面板内容
+#[allow(clippy::too_many_arguments)]
+pub fn build_panel_refresh_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    panel_query: Query<Ref<UIPanel>, With<BuildingMenuPanel>>,
+    rows_query: Query<Entity, With<BuildPanelRows>>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    build_menu: Res<BuildMenu>,
+    rooms: Res<Rooms>,
+) {
+    let Ok(panel) = panel_query.single() else {
+        return;
+    };
+    if panel.state != PanelState::Visible {
+        return;
+    }
+    if !panel.is_changed() && !map_registry.is_changed() && !build_menu.is_changed() && !rooms.is_changed() {
+        return;
+    }
+
+    let Ok(rows) = rows_query.single() else {
+        return;
+    };
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let orders = map_registry.local_build_orders(active_local.coord);
+
+    commands.entity(rows).despawn_related::<Children>();
+    commands.entity(rows).with_children(|parent| {
+        let select_color = |building: BuildingType| {
+            if build_menu.selected == Some(building) {
+                SELECTED_COLOR
+            } else {
+                BUTTON_COLOR
+            }
+        };
+        spawn_row(parent, |row| {
+            for kind in ConstructionKind::ALL {
+                let building = BuildingType::Construction(kind);
+                let glyph = construction_visual(Construction {
+                    kind,
+                    material: build_menu.material,
+                })
+                .1;
+                spawn_button(row, &font, &format!("{} {}", glyph, kind.label()), select_color(building), BuildButton::Select(building));
+            }
+        });
+        spawn_row(parent, |row| {
+            for kind in FurnitureKind::ALL {
+                let building = BuildingType::Furniture(kind);
+                spawn_button(row, &font, &format!("{} {}", kind.glyph(), kind.label()), select_color(building), BuildButton::Select(building));
+            }
+            spawn_button(
+                row,
+                &font,
+                &format!("材质: {}", build_menu.material.label()),
+                BUTTON_COLOR,
+                BuildButton::CycleMaterial,
+            );
+        });
+        spawn_row(parent, |row| {
+            for kind in WorkshopKind::ALL {
+                let building = BuildingType::Workshop(kind);
+                spawn_button(row, &font, &format!("{} {}", kind.glyph(), kind.label()), select_color(building), BuildButton::Select(building));
+            }
+        });
+        if let Some(building) = build_menu.selected {
+            spawn_row(parent, |row| {
+                spawn_label(
+                    row,
+                    &font,
+                    &format!(
+                        "点击地图建造{}{}（消耗 {} 份材料）",
+                        build_menu.material.label(),
+                        building.label(),
+                        building.material_cost()
+                    ),
+                );
+            });
+        }
+        spawn_row(parent, |row| {
+            spawn_label(row, &font, &format!("待建订单: {}", orders.len()));
+            if !orders.is_empty() {
+                spawn_button(row, &font, "取消全部订单", CANCEL_COLOR, BuildButton::CancelOrders);
+            }
+        });
+
+        if rooms.rooms.is_empty() {
+            spawn_row(parent, |row| spawn_label(row, &font, "还没有围好的房间"));
+        }
+        for room in rooms.rooms.iter() {
+            let usage = room.usage.as_ref().map(|usage| usage.describe()).unwrap_or_else(|| "未分配".to_string());
+            spawn_row(parent, |row| {
+                spawn_label(
+                    row,
+                    &font,
+                    &format!(
+                        "━━ 房间 ({}, {})：{} 格，品质 {:.0}（{}），床 {}，桌 {} —— {}",
+                        room.anchor.0,
+                        room.anchor.1,
+                        room.tiles.len(),
+                        room.quality,
+                        room.quality_label(),
+                        room.beds.len(),
+                        room.tables,
+                        usage
+                    ),
+                );
+            });
+            spawn_row(parent, |row| {
+                if !room.beds.is_empty() {
+                    spawn_button(row, &font, "设为选中矮人的卧室", BUTTON_COLOR, BuildButton::Bedroom(room.anchor));
+                }
+                if room.tables > 0 {
+                    spawn_button(row, &font, "设为餐厅", BUTTON_COLOR, BuildButton::DiningHall(room.anchor));
+                }
+                if room.usage.is_some() {
+                    spawn_button(row, &font, "取消用途", CANCEL_COLOR, BuildButton::ClearUse(room.anchor));
+                }
+            });
+        }
+    });
+}
+
+fn spawn_row(parent: &mut ChildSpawnerCommands, build: impl FnOnce(&mut ChildSpawnerCommands)) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            margin: UiRect::top(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(build);
+}
+
+fn spawn_label(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, text: &str) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font: font.clone(),
+            font_size: 15.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.95, 0.9, 0.75)),
+        Node {
+            margin: UiRect::horizontal(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    label: &str,
+    color: Color,
+    button: BuildButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                margin: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(color),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+        });
+}
@@ -1,5 +1,6 @@
 use crate::components::*;
 use crate::resources::*;
+use crate::systems::log_history_summary;
 use crate::ui_framework::*;
 use crate::world_map_data::*;
 use bevy::prelude::*;
@@ -46,8 +47,9 @@ fn cleanup_local_entities(
 }
 
 /// 保存矮人状态
+#[allow(clippy::type_complexity)]
 pub fn save_dwarves_state(
-    dwarf_data_query: Query<(&Dwarf, &GridPosition, &WorkState)>,
+    dwarf_data_query: Query<(&Dwarf, &Skills, &Labours, &Personality, &Thoughts, &Relationships, &Age, &Identity, &Equipment, &Shift, &GridPosition, &WorkState)>,
     mut map_registry: ResMut<GeneratedMapsRegistry>,
     active_local: Res<ActiveLocalMap>,
     game_time: Res<GameTime>,
@@ -55,7 +57,7 @@ pub fn save_dwarves_state(
 ) {
     if let Some(coord) = active_local.coord {
         let mut stored_dwarves = Vec::new();
-        for (dwarf, pos, work) in dwarf_data_query.iter() {
+        for (dwarf, skills, labours, personality, thoughts, relationships, age, identity, equipment, shift, pos, work) in dwarf_data_query.iter() {
             stored_dwarves.push(StoredDwarf {
                 name: dwarf.name.clone(),
                 grid_x: pos.x,
@@ -65,6 +67,17 @@ pub fn save_dwarves_state(
                 happiness: dwarf.happiness,
                 current_task: work.current_task.clone(),
                 work_progress: work.work_progress,
+                skills: skills.clone(),
+                labours: labours.clone(),
+                personality: personality.clone(),
+                thoughts: thoughts.clone(),
+                relationships: relationships.clone(),
+                age: age.clone(),
+                identity: identity.clone(),
+                equipment: equipment.clone(),
+                fatigue: dwarf.fatigue,
+                thirst: dwarf.thirst,
+                shift: *shift,
                 last_update_day: game_time.day,
                 last_update_hour: game_time.hour,
             });
@@ -157,12 +170,16 @@ pub fn cleanup_game_on_menu_return(
 }
 
 /// 清理世界线数据（在返回主菜单时）
+#[allow(clippy::too_many_arguments)]
 pub fn cleanup_world_data(
     mut commands: Commands,
     mut map_registry: ResMut<GeneratedMapsRegistry>,
     mut world_seed: ResMut<WorldSeed>,
+    mut settings: ResMut<WorldGenSettings>,
     mut game_time: ResMut<GameTime>,
     mut inventory: ResMut<GlobalInventory>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut military: ResMut<Military>,
     world_atlas: Option<ResMut<WorldAtlas>>,
     mut logger: ResMut<crate::logger::GameLogger>,
 ) {
@@ -171,25 +188,30 @@ pub fn cleanup_world_data(
     map_registry.dwarves.clear();
     map_registry.spawn_location = None;
     map_registry.dwarves_spawned = false;
+    map_registry.stockpile = None;
+    map_registry.zones.clear();
+    map_registry.furniture.clear();
+    map_registry.workshops.clear();
+    map_registry.loose_food.clear();
+    map_registry.loose_materials.clear();
+    map_registry.fish_stocks.clear();
+    map_registry.vegetation_clock.clear();
+    map_registry.build_orders.clear();
+    map_registry.room_uses.clear();
     
     // 重新生成世界种子
     world_seed.seed = rand::random();
     
-    // 重新生成世界地图
+    // 重新生成世界地图（保留世界生成设置，仅更换种子）
+    settings.seed = world_seed.seed;
     if let Some(mut atlas) = world_atlas {
-        *atlas = WorldAtlas::generate(
-            world_seed.seed as u64,
-            WORLD_ATLAS_DEFAULT_WIDTH,
-            WORLD_ATLAS_DEFAULT_HEIGHT,
-        );
+        atlas.regenerate(&settings);
+        log_history_summary(&atlas, &mut logger);
         logger.info(format!("重新生成世界地图，种子: {}", world_seed.seed));
     } else {
         // 如果 WorldAtlas 还不存在，创建它
-        let atlas = WorldAtlas::generate(
-            world_seed.seed as u64,
-            WORLD_ATLAS_DEFAULT_WIDTH,
-            WORLD_ATLAS_DEFAULT_HEIGHT,
-        );
+        let atlas = WorldAtlas::generate_with_settings(&settings);
+        log_history_summary(&atlas, &mut logger);
         commands.insert_resource(atlas);
         logger.info(format!("首次创建世界地图，种子: {}", world_seed.seed));
     }
@@ -201,10 +223,13 @@ pub fn cleanup_world_data(
     game_time.time_scale = 1.0;
     
     // 重置资源
-    inventory.stone = 50;
-    inventory.wood = 30;
-    inventory.food = 100;
-    inventory.metal = 10;
+    *inventory = GlobalInventory::default();
+
+    // 清空要塞编年史
+    chronicle.entries.clear();
+
+    // 解散所有小队
+    *military = Military::default();
     
     logger.info("返回主菜单，游戏数据已重置".to_string());
 }
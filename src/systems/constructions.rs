@@ -0,0 +1,76 @@
+use crate::components::*;
+use crate::resources::*;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// 注册表中建成的建筑结构同步到地图上的地形实体：
+/// 更新地形组件（导航系统据此更新寻路网格和连通区域），并换上结构的底色和字符
+#[allow(clippy::type_complexity)]
+pub fn construction_sync_system(
+    mut commands: Commands,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut terrain_query: Query<(&GridPosition, &mut Terrain, &mut Sprite)>,
+    mut glyph_query: Query<
+        (
+            Entity,
+            &GridPosition,
+            &mut AsciiChar,
+            &mut Text2d,
+            &mut TextColor,
+            &mut Transform,
+            Option<&WaterAnimation>,
+            Option<&TreeSway>,
+        ),
+        Without<Hostile>,
+    >,
+) {
+    if !map_registry.is_changed() {
+        return;
+    }
+    let Some(tiles) = active_local.coord.and_then(|coord| map_registry.maps.get(&coord)) else {
+        return;
+    };
+    let built: HashMap<(i32, i32), &StoredMapTile> = tiles
+        .iter()
+        .filter(|tile| tile.construction.is_some())
+        .map(|tile| ((tile.x, tile.y), tile))
+        .collect();
+    if built.is_empty() {
+        return;
+    }
+
+    for (pos, mut terrain, mut sprite) in terrain_query.iter_mut() {
+        let Some(tile) = built.get(&(pos.x, pos.y)) else {
+            continue;
+        };
+        if terrain.construction == tile.construction {
+            continue;
+        }
+        terrain.construction = tile.construction;
+        terrain.terrain_type = tile.terrain_type;
+        terrain.walkable = tile.walkable;
+        sprite.color = tile.color;
+    }
+
+    for (entity, pos, mut ascii, mut text, mut color, mut transform, water, sway) in glyph_query.iter_mut() {
+        let Some(tile) = built.get(&(pos.x, pos.y)) else {
+            continue;
+        };
+        if ascii.character == tile.ascii_char {
+            continue;
+        }
+        ascii.character = tile.ascii_char;
+        *text = Text2d::new(tile.ascii_char.to_string());
+        color.0 = tile.char_color;
+        // 桥下的水不再起伏：把字符放回原位并移除动画
+        if let Some(water) = water {
+            transform.translation.y = water.base_y;
+            commands.entity(entity).remove::<WaterAnimation>();
+        }
+        if let Some(sway) = sway {
+            transform.translation.x = sway.base_x;
+            commands.entity(entity).remove::<TreeSway>();
+        }
+    }
+}
@@ -131,7 +131,7 @@ pub fn update_debug_panel(
         game_time.time_scale,
         inventory.stone,
         inventory.wood,
-        inventory.food,
+        inventory.food.total(),
         inventory.metal,
         total_dwarves,
         idle_count,
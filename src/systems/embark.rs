@@ -0,0 +1,563 @@
+use bevy::prelude::*;
+
+use crate::components::SkillKind;
+use crate::name_generator::DwarfNameGenerator;
+use crate::resources::{
+    ActiveLocalMap, EmbarkDwarf, EmbarkPlan, EmbarkSupply, GameState, GlobalInventory, WorldSeed,
+    EMBARK_DWARF_AGES, EMBARK_MAX_DWARVES, EMBARK_MAX_SKILL_LEVEL, EMBARK_MIN_DWARVES,
+    EMBARK_POINTS,
+};
+use crate::world::{survey_site, SiteSurvey};
+use crate::world_map_data::{WorldAtlas, WorldCell};
+
+/// 出发准备界面状态
+#[derive(Resource, Default)]
+pub struct EmbarkState {
+    pub status: String,
+    /// 所选地块的主区域足够大，允许出发
+    pub site_playable: bool,
+}
+
+/// 出发准备界面根节点
+#[derive(Component)]
+pub struct EmbarkUI;
+
+/// 矮人列表容器（矮人数量变化时重建）
+#[derive(Component)]
+pub struct EmbarkDwarfListRoot;
+
+/// 出发准备界面按钮
+#[derive(Component, Clone, Copy)]
+pub enum EmbarkButton {
+    AddDwarf,
+    RemoveDwarf,
+    /// 切换第 n 名矮人的专长技能
+    CycleSkill(usize),
+    /// 调整第 n 名矮人的技能等级
+    Level(usize, i32),
+    Supply(EmbarkSupply, i32),
+    Embark,
+    Reset,
+    Back,
+}
+
+/// 出发准备界面中需要刷新的文本
+#[derive(Component, Clone, Copy)]
+pub enum EmbarkText {
+    Points,
+    Status,
+    Supply(EmbarkSupply),
+}
+
+/// 地块不适合出发时的提示
+const UNPLAYABLE_SITE_MESSAGE: &str = "该地块可通行区域太小，请返回选择其他地块";
+
+const BUTTON_COLOR: Color = Color::srgb(0.3, 0.4, 0.5);
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.4, 0.5, 0.6);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.2, 0.5, 0.3);
+
+/// 进入出发准备界面
+#[allow(clippy::too_many_arguments)]
+pub fn setup_embark(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    world_atlas: Res<WorldAtlas>,
+    active_local: Res<ActiveLocalMap>,
+    world_seed: Res<WorldSeed>,
+    mut names: ResMut<DwarfNameGenerator>,
+    mut plan: ResMut<EmbarkPlan>,
+    mut state: ResMut<EmbarkState>,
+) {
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+
+    // 同一地块总是出现同一批矮人
+    if names.seed() != world_seed.seed {
+        *names = DwarfNameGenerator::new(world_seed.seed);
+        *plan = EmbarkPlan::new(&mut names);
+    }
+
+    let survey = active_local
+        .coord
+        .and_then(|coord| world_atlas.cell_at(coord))
+        .map(|cell| (cell, survey_site(&world_atlas, cell.coord)));
+    let site_text = match &survey {
+        Some((cell, survey)) => describe_site(cell, &world_atlas, survey),
+        None => "未选择地块".to_string(),
+    };
+    state.site_playable = survey.as_ref().is_some_and(|(_, survey)| survey.playable);
+    state.status = if state.site_playable {
+        "Enter出发 | Esc返回大地图".to_string()
+    } else {
+        UNPLAYABLE_SITE_MESSAGE.to_string()
+    };
+    // 触发一次刷新
+    plan.set_changed();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.15, 0.2)),
+            EmbarkUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("◆ 出发准备 ◆"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.9, 0.5)),
+                Node {
+                    margin: UiRect::bottom(Val::Px(16.0)),
+                    ..default()
+                },
+            ));
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                })
+                .with_children(|body| {
+                    // 左侧：地块勘察
+                    body.spawn((
+                        Text::new(site_text),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.85, 0.9, 1.0)),
+                        Node {
+                            width: Val::Px(380.0),
+                            margin: UiRect::right(Val::Px(20.0)),
+                            ..default()
+                        },
+                    ));
+
+                    // 中间：矮人与技能
+                    body.spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Px(460.0),
+                        margin: UiRect::right(Val::Px(20.0)),
+                        ..default()
+                    })
+                    .with_children(|column| {
+                        column
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                margin: UiRect::bottom(Val::Px(8.0)),
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                spawn_label(row, &font, "矮人", 120.0);
+                                spawn_button(row, &font, "-", EmbarkButton::RemoveDwarf);
+                                spawn_button(row, &font, "+", EmbarkButton::AddDwarf);
+                            });
+                        column.spawn((
+                            Node {
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            EmbarkDwarfListRoot,
+                        ));
+                    });
+
+                    // 右侧：物资与点数
+                    body.spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Px(360.0),
+                        ..default()
+                    })
+                    .with_children(|column| {
+                        for supply in EmbarkSupply::ALL {
+                            column
+                                .spawn(Node {
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::bottom(Val::Px(4.0)),
+                                    ..default()
+                                })
+                                .with_children(|row| {
+                                    spawn_label(row, &font, supply.label(), 80.0);
+                                    spawn_button(row, &font, "-", EmbarkButton::Supply(supply, -1));
+                                    row.spawn((
+                                        Text::new(""),
+                                        TextFont {
+                                            font: font.clone(),
+                                            font_size: 20.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                                        Node {
+                                            width: Val::Px(130.0),
+                                            justify_content: JustifyContent::Center,
+                                            ..default()
+                                        },
+                                        EmbarkText::Supply(supply),
+                                    ));
+                                    spawn_button(row, &font, "+", EmbarkButton::Supply(supply, 1));
+                                });
+                        }
+
+                        column.spawn((
+                            Text::new(""),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 22.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(1.0, 0.9, 0.5)),
+                            Node {
+                                margin: UiRect::top(Val::Px(16.0)),
+                                ..default()
+                            },
+                            EmbarkText::Points,
+                        ));
+
+                        column
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Row,
+                                flex_wrap: FlexWrap::Wrap,
+                                margin: UiRect::top(Val::Px(16.0)),
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                spawn_button(row, &font, "出发", EmbarkButton::Embark);
+                                spawn_button(row, &font, "重置", EmbarkButton::Reset);
+                                spawn_button(row, &font, "返回", EmbarkButton::Back);
+                            });
+
+                        column.spawn((
+                            Text::new(""),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.7, 0.7, 0.8)),
+                            Node {
+                                margin: UiRect::top(Val::Px(12.0)),
+                                ..default()
+                            },
+                            EmbarkText::Status,
+                        ));
+                    });
+                });
+        });
+}
+
+/// 离开出发准备界面
+pub fn cleanup_embark(mut commands: Commands, roots: Query<Entity, With<EmbarkUI>>) {
+    for entity in roots.iter() {
+        if let Ok(mut entity_cmd) = commands.get_entity(entity) {
+            entity_cmd.despawn();
+        }
+    }
+}
+
+/// 出发准备快捷键
+pub fn embark_keyboard_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<EmbarkState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard.just_pressed(KeyCode::Enter) || keyboard.just_pressed(KeyCode::NumpadEnter) {
+        try_embark(&mut state, &mut next_state);
+    }
+    if keyboard.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::WorldView);
+    }
+}
+
+/// 出发准备界面按钮交互
+pub fn embark_button_system(
+    mut interaction_query: Query<(&Interaction, &EmbarkButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut plan: ResMut<EmbarkPlan>,
+    mut names: ResMut<DwarfNameGenerator>,
+    mut state: ResMut<EmbarkState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, button, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(BUTTON_PRESSED_COLOR);
+                match *button {
+                    EmbarkButton::Embark => try_embark(&mut state, &mut next_state),
+                    EmbarkButton::Back => next_state.set(GameState::WorldView),
+                    EmbarkButton::Reset => {
+                        *names = DwarfNameGenerator::new(names.seed());
+                        *plan = EmbarkPlan::new(&mut names);
+                        state.status = "已恢复默认方案".to_string();
+                    }
+                    adjustment => {
+                        // 在副本上调整，超出预算时不生效
+                        let mut candidate = plan.clone();
+                        match apply_adjustment(&mut candidate, adjustment, &mut names) {
+                            Err(reason) => state.status = reason,
+                            Ok(()) if candidate.points_left() < 0 => {
+                                state.status = format!("点数不足（剩余 {}）", plan.points_left());
+                            }
+                            Ok(()) => {
+                                *plan = candidate;
+                                state.status.clear();
+                            }
+                        }
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(BUTTON_HOVER_COLOR);
+            }
+            Interaction::None => {
+                *color = BackgroundColor(BUTTON_COLOR);
+            }
+        }
+    }
+}
+
+/// 方案变化时刷新矮人列表和文本
+pub fn embark_refresh_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    plan: Res<EmbarkPlan>,
+    state: Res<EmbarkState>,
+    list_root: Query<Entity, With<EmbarkDwarfListRoot>>,
+    mut texts: Query<(&mut Text, &EmbarkText)>,
+) {
+    if !plan.is_changed() && !state.is_changed() {
+        return;
+    }
+
+    for (mut text, kind) in texts.iter_mut() {
+        **text = match kind {
+            EmbarkText::Points => format!("剩余点数: {} / {}", plan.points_left(), EMBARK_POINTS),
+            EmbarkText::Status => state.status.clone(),
+            EmbarkText::Supply(supply) => format!(
+                "{} ({}点)",
+                supply.amount(&plan.supplies),
+                supply.amount(&plan.supplies).div_ceil(supply.step()) as i32 * supply.cost_per_step()
+            ),
+        };
+    }
+
+    if !plan.is_changed() {
+        return;
+    }
+    let Ok(root) = list_root.single() else {
+        return;
+    };
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    commands.entity(root).despawn_related::<Children>();
+    commands.entity(root).with_children(|list| {
+        for (index, dwarf) in plan.dwarves.iter().enumerate() {
+            list.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                margin: UiRect::bottom(Val::Px(2.0)),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_label(row, &font, &dwarf.identity.full_name(), 140.0);
+                spawn_button(row, &font, dwarf.skill.label(), EmbarkButton::CycleSkill(index));
+                spawn_button(row, &font, "-", EmbarkButton::Level(index, -1));
+                spawn_label(row, &font, &format!("等级 {}", dwarf.level), 80.0);
+                spawn_button(row, &font, "+", EmbarkButton::Level(index, 1));
+            });
+        }
+    });
+}
+
+/// 确认出发：以方案中的物资作为要塞初始库存
+pub fn apply_embark_plan(
+    plan: Res<EmbarkPlan>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    *inventory = plan.supplies.clone();
+    logger.info(format!(
+        "出发: {} 名矮人, 消耗点数 {} / {}",
+        plan.dwarves.len(),
+        plan.points_spent(),
+        EMBARK_POINTS
+    ));
+}
+
+/// 出发（所选地块不适合时拒绝）
+fn try_embark(state: &mut EmbarkState, next_state: &mut NextState<GameState>) {
+    if state.site_playable {
+        next_state.set(GameState::LocalView);
+    } else {
+        state.status = UNPLAYABLE_SITE_MESSAGE.to_string();
+    }
+}
+
+/// 对方案执行一次调整（不检查点数预算）
+fn apply_adjustment(
+    plan: &mut EmbarkPlan,
+    button: EmbarkButton,
+    names: &mut DwarfNameGenerator,
+) -> Result<(), String> {
+    match button {
+        EmbarkButton::AddDwarf => {
+            if plan.dwarves.len() >= EMBARK_MAX_DWARVES {
+                return Err(format!("最多携带 {} 名矮人", EMBARK_MAX_DWARVES));
+            }
+            let taken = plan.dwarves.iter().map(|dwarf| dwarf.identity.full_name()).collect();
+            plan.dwarves.push(EmbarkDwarf {
+                identity: names.generate(0, EMBARK_DWARF_AGES, None, &taken),
+                skill: SkillKind::Hauling,
+                level: 0,
+            });
+        }
+        EmbarkButton::RemoveDwarf => {
+            if plan.dwarves.len() <= EMBARK_MIN_DWARVES {
+                return Err(format!("至少需要 {} 名矮人", EMBARK_MIN_DWARVES));
+            }
+            plan.dwarves.pop();
+        }
+        EmbarkButton::CycleSkill(index) => {
+            if let Some(dwarf) = plan.dwarves.get_mut(index) {
+                let current = SkillKind::ALL.iter().position(|kind| *kind == dwarf.skill).unwrap_or(0);
+                dwarf.skill = SkillKind::ALL[(current + 1) % SkillKind::ALL.len()];
+            }
+        }
+        EmbarkButton::Level(index, direction) => {
+            if let Some(dwarf) = plan.dwarves.get_mut(index) {
+                dwarf.level = dwarf
+                    .level
+                    .saturating_add_signed(direction)
+                    .min(EMBARK_MAX_SKILL_LEVEL);
+            }
+        }
+        EmbarkButton::Supply(supply, direction) => supply.adjust(&mut plan.supplies, direction),
+        EmbarkButton::Embark | EmbarkButton::Reset | EmbarkButton::Back => {}
+    }
+    Ok(())
+}
+
+/// 地块勘察报告：群落、气候、水系、归属与预期资源
+fn describe_site(cell: &WorldCell, atlas: &WorldAtlas, survey: &SiteSurvey) -> String {
+    let climate = if cell.temperature < -0.2 {
+        "寒冷"
+    } else if cell.temperature > 0.5 {
+        "炎热"
+    } else {
+        "温和"
+    };
+    let humidity = if cell.moisture < -0.2 {
+        "干燥"
+    } else if cell.moisture > 0.3 {
+        "湿润"
+    } else {
+        "适中"
+    };
+    let owner = cell
+        .owner
+        .and_then(|id| atlas.history.civilization(id))
+        .map(|civ| format!("{} [{}]", civ.name, civ.race.label()))
+        .unwrap_or_else(|| "无主之地".to_string());
+    let river = if cell.has_river() {
+        format!("河流 (流量{:.1})", cell.flow)
+    } else {
+        "无".to_string()
+    };
+
+    let rating = |percent: f32| {
+        if percent >= 25.0 {
+            "丰富"
+        } else if percent >= 8.0 {
+            "一般"
+        } else {
+            "稀少"
+        }
+    };
+    let wood = survey.percent(survey.tree);
+    let stone = survey.percent(survey.stone + survey.mountain);
+    let food = survey.percent(survey.grass + survey.tree);
+    let metal = survey.percent(survey.mountain);
+    let water = survey.percent(survey.water);
+
+    format!(
+        "━━━ 地块 ({}, {}) ━━━\n群落: {}\n气候: {} ({:.2}) / {} ({:.2})\n海拔: {:.2}\n水系: {}\n归属: {}\n\n━━━ 地形勘察 ━━━\n草地 {:.0}% | 石地 {:.0}% | 森林 {:.0}%\n水域 {:.0}% | 山脉 {:.0}%\n最大可通行区域: {:.0}%{}\n\n━━━ 预期资源 ━━━\n木材: {}\n石头: {}\n食物: {}\n金属: {}\n水源: {}",
+        cell.coord.x,
+        cell.coord.y,
+        cell.label(),
+        climate,
+        cell.temperature,
+        humidity,
+        cell.moisture,
+        cell.elevation,
+        river,
+        owner,
+        survey.percent(survey.grass),
+        survey.percent(survey.stone),
+        wood,
+        water,
+        survey.percent(survey.mountain),
+        survey.percent(survey.largest_region),
+        if survey.playable { "" } else { " (过小)" },
+        rating(wood),
+        rating(stone),
+        rating(food),
+        rating(metal * 2.0),
+        rating(water * 2.0),
+    )
+}
+
+fn spawn_label(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, label: &str, width: f32) {
+    parent.spawn((
+        Text::new(label),
+        TextFont {
+            font: font.clone(),
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 0.8, 0.9)),
+        Node {
+            width: Val::Px(width),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    label: &str,
+    button: EmbarkButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                margin: UiRect::all(Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+        });
+}
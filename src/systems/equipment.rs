@@ -0,0 +1,200 @@
+use crate::components::*;
+use crate::connectivity::RegionMap;
+use crate::resources::*;
+use bevy::prelude::*;
+
+/// 取用工具：接到采矿/伐木工作但手上没有对应工具的矮人，
+/// 先把手上的工具放回仓库，再从仓库取一把（没有时用石头打制一把石制工具）；
+/// 都办不到就放弃这份工作。需在工作系统之后、采集系统之前运行
+#[allow(clippy::type_complexity)]
+pub fn tool_equip_system(
+    mut dwarves: Query<(&Dwarf, &mut WorkState, &mut Equipment)>,
+    terrain_query: Query<(&GridPosition, &Terrain)>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    for (dwarf, mut work_state, mut equipment) in dwarves.iter_mut() {
+        let target = match &work_state.current_task {
+            Some(Task::Gathering(target)) | Some(Task::Mining(target)) => target.clone(),
+            _ => continue,
+        };
+        let Some(labour) = terrain_query
+            .iter()
+            .find(|(pos, _)| **pos == target)
+            .map(|(_, terrain)| terrain.terrain_type.labour())
+        else {
+            continue;
+        };
+        let Some(tool) = labour.required_tool() else {
+            continue;
+        };
+        if equipment.has_tool_for(labour) {
+            continue;
+        }
+
+        // 手持武器的矮人不会放下武器去干活
+        let new_tool = if equipment.holds_weapon() {
+            None
+        } else if inventory.tools > 0 {
+            inventory.tools -= 1;
+            Some(Item::new(tool, ItemMaterial::Copper))
+        } else if inventory.stone >= STONE_TOOL_COST {
+            inventory.stone -= STONE_TOOL_COST;
+            Some(Item::new(tool, ItemMaterial::Stone))
+        } else {
+            None
+        };
+
+        let Some(new_tool) = new_tool else {
+            logger.info(format!("{} 没有可用的{}，放弃了{}工作", dwarf.name, tool.label(), labour.label()));
+            work_state.current_task = Some(Task::Idle);
+            work_state.work_progress = 0.0;
+            work_state.cached_path.clear();
+            work_state.path_index = 0;
+            work_state.task_cooldown = 2.0;
+            work_state.task_duration = 0.0;
+            continue;
+        };
+
+        if let Some(old_tool) = equipment.hands.replace(new_tool) {
+            return_to_stock(old_tool, &mut inventory);
+        }
+        logger.debug(format!("{} 拿起了{}", dwarf.name, new_tool.name()));
+    }
+}
+
+/// 放回仓库的工具：金属工具计入库存工具，石制和木制工具拆回原料
+pub fn return_to_stock(item: Item, inventory: &mut GlobalInventory) {
+    match item.material {
+        ItemMaterial::Copper | ItemMaterial::Iron => inventory.tools += 1,
+        ItemMaterial::Stone => inventory.stone += STONE_TOOL_COST,
+        ItemMaterial::Wood => inventory.wood += 1,
+    }
+}
+
+/// 搬运：货物过半的空闲矮人、以及找不到活干准备闲逛却还带着货物的矮人把资源运回要塞仓库，
+/// 到达仓库后卸货入库；没有仓库或仓库不可达时就地卸货。
+/// 捡拾散落资源的矮人走到地方后把东西装进货物，随即运回仓库
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn hauling_system(
+    time: Res<Time>,
+    mut dwarves: Query<(
+        &Dwarf,
+        &mut WorkState,
+        &mut Equipment,
+        &mut Skills,
+        &mut Thoughts,
+        &GridPosition,
+        Has<Tantrum>,
+    )>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    region_map: Res<RegionMap>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+
+    let stockpile = map_registry.fortress_stockpile(active_local.coord);
+
+    for (dwarf, mut work_state, mut equipment, mut skills, mut thoughts, pos, tantrum) in dwarves.iter_mut() {
+        let current_pos = (pos.x, pos.y);
+        match &work_state.current_task {
+            Some(Task::Hauling(target)) => {
+                let distance = (current_pos.0 - target.x).abs() + (current_pos.1 - target.y).abs();
+                if distance > STOCKPILE_ARRIVAL_RADIUS {
+                    continue;
+                }
+                let hauled = equipment.cargo.total();
+                unload(&mut equipment.cargo, &mut inventory);
+                logger.debug(format!("{} 把 {} 份资源运进了仓库", dwarf.name, hauled));
+                super::gain_experience(dwarf, &mut skills, &mut thoughts, SkillKind::Hauling, &mut logger);
+                work_state.current_task = Some(Task::Idle);
+                work_state.task_cooldown = 0.5;
+                work_state.task_duration = 0.0;
+            }
+            Some(Task::PickUp(target)) => {
+                if current_pos != (target.x, target.y) {
+                    continue;
+                }
+                let picked = active_local
+                    .coord
+                    .map_or(0, |coord| pick_up_loose(&mut map_registry, coord, current_pos, &mut equipment));
+                if picked > 0 {
+                    logger.debug(format!("{} 捡起了 {} 份散落的资源", dwarf.name, picked));
+                }
+                work_state.current_task = Some(Task::Idle);
+                work_state.task_cooldown = 0.5;
+                work_state.task_duration = 0.0;
+                if equipment.cargo.total() > 0 {
+                    haul_to_stockpile(&mut work_state, &mut equipment.cargo, &mut inventory, stockpile, current_pos, &region_map);
+                }
+            }
+            None | Some(Task::Idle)
+                if equipment.cargo.total() as f32 >= equipment.carry_capacity() as f32 * HAUL_LOAD_FRACTION =>
+            {
+                haul_to_stockpile(&mut work_state, &mut equipment.cargo, &mut inventory, stockpile, current_pos, &region_map);
+            }
+            // 没有活干时不带着货物闲逛；发脾气的矮人不听使唤
+            Some(Task::Wandering(_)) if !tantrum && equipment.cargo.total() > 0 => {
+                haul_to_stockpile(&mut work_state, &mut equipment.cargo, &mut inventory, stockpile, current_pos, &region_map);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 动身把货物运回仓库；没有仓库或仓库不可达时就地卸货
+fn haul_to_stockpile(
+    work_state: &mut WorkState,
+    cargo: &mut Cargo,
+    inventory: &mut GlobalInventory,
+    stockpile: Option<(i32, i32)>,
+    from: (i32, i32),
+    region_map: &RegionMap,
+) {
+    match stockpile.filter(|depot| region_map.connected(from, *depot)) {
+        Some((x, y)) => {
+            work_state.current_task = Some(Task::Hauling(GridPosition { x, y }));
+            work_state.cached_path.clear();
+            work_state.path_index = 0;
+            work_state.task_duration = 0.0;
+        }
+        None => unload(cargo, inventory),
+    }
+}
+
+/// 把脚下散落的食物和材料装进货物，装不下的留在地上，返回捡起的份数
+fn pick_up_loose(map_registry: &mut GeneratedMapsRegistry, coord: IVec2, pos: (i32, i32), equipment: &mut Equipment) -> u32 {
+    let mut picked = 0;
+    if let Some(loose) = map_registry.loose_food.get_mut(&coord) {
+        for food in loose.iter_mut().filter(|food| food.pos == pos) {
+            let amount = food.amount - equipment.stow_food(food.kind, food.amount);
+            food.amount -= amount;
+            picked += amount;
+        }
+        loose.retain(|food| food.amount > 0);
+    }
+    if let Some(loose) = map_registry.loose_materials.get_mut(&coord) {
+        for material in loose.iter_mut().filter(|material| material.pos == pos) {
+            let amount = material.amount - equipment.stow_material(material.kind, material.amount);
+            material.amount -= amount;
+            picked += amount;
+        }
+        loose.retain(|material| material.amount > 0);
+    }
+    picked
+}
+
+/// 把随身货物全部存入全局库存
+fn unload(cargo: &mut Cargo, inventory: &mut GlobalInventory) {
+    inventory.wood += cargo.wood;
+    inventory.stone += cargo.stone;
+    for (kind, amount) in cargo.food.drain(..) {
+        inventory.food.add(FoodItem::Raw(kind), amount);
+    }
+    inventory.metal += cargo.metal;
+    *cargo = Cargo::default();
+}
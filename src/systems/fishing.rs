@@ -0,0 +1,103 @@
+use crate::components::*;
+use crate::resources::*;
+use crate::world_map_data::WorldAtlas;
+use bevy::prelude::*;
+use rand::Rng;
+
+/// 完成一次垂钓的基础速度（每秒进度）
+const FISHING_SPEED: f32 = 0.15;
+/// 不会钓鱼的矮人在鱼群满额时上钩的概率
+const BASE_CATCH_CHANCE: f32 = 0.3;
+/// 每级钓鱼技能增加的上钩概率
+const CATCH_CHANCE_PER_LEVEL: f32 = 0.06;
+/// 上钩概率上限
+const MAX_CATCH_CHANCE: f32 = 0.9;
+/// 鱼群每小时的繁殖率（按剩余数量与空余容量计算）
+const FISH_BREEDING_RATE: f32 = 0.05;
+
+/// 钓鱼：站在岸边的矮人按钓鱼技能累积进度，每完成一次垂钓按技能和剩余鱼群决定是否上钩；
+/// 钓到的鱼放在脚边，等人捡回仓库。鱼群第一次被钓时按地块的生物群系创建
+#[allow(clippy::type_complexity)]
+pub fn fishing_system(
+    time: Res<Time>,
+    mut query: Query<(&Dwarf, &mut WorkState, &mut Skills, &Personality, &mut Thoughts, &GridPosition)>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    world_atlas: Res<WorldAtlas>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+    let Some(coord) = active_local.coord else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+
+    for (dwarf, mut work_state, mut skills, personality, mut thoughts, pos) in query.iter_mut() {
+        let Some(Task::Fishing(target)) = work_state.current_task.clone() else {
+            continue;
+        };
+        if (pos.x - target.x).abs() + (pos.y - target.y).abs() != 1 {
+            continue;
+        }
+
+        let temperament = personality.work_speed() * MoodLevel::from_mood(dwarf.happiness).work_speed();
+        work_state.work_progress +=
+            time.delta_secs() * FISHING_SPEED * skills.work_speed(SkillKind::Fishing) * temperament;
+        if work_state.work_progress < 1.0 {
+            continue;
+        }
+
+        work_state.work_progress = 0.0;
+        work_state.current_task = Some(Task::Idle);
+        work_state.task_cooldown = 0.5;
+        work_state.task_duration = 0.0;
+
+        let stock = map_registry
+            .fish_stocks
+            .entry(coord)
+            .or_insert_with(|| FishPopulation::for_cell(world_atlas.cell_at(coord)));
+        let chance = catch_chance(skills.level(SkillKind::Fishing)) * stock.abundance();
+        if stock.fish > 0 && rng.gen::<f32>() < chance {
+            stock.fish -= 1;
+            map_registry.drop_food(coord, FoodKind::Fish, 1, (pos.x, pos.y));
+            logger.info(format!("{} 钓到了一条鱼", dwarf.name));
+        } else {
+            logger.debug(format!("{} 钓了半天，什么也没钓到", dwarf.name));
+        }
+        super::gain_experience(dwarf, &mut skills, &mut thoughts, SkillKind::Fishing, &mut logger);
+    }
+}
+
+/// 鱼群满额时的上钩概率：技能越高越容易钓到
+fn catch_chance(level: u32) -> f32 {
+    (BASE_CATCH_CHANCE + CATCH_CHANCE_PER_LEVEL * level as f32).min(MAX_CATCH_CHANCE)
+}
+
+/// 鱼群繁殖：每个游戏小时所有地块的鱼群按剩余数量和空余容量繁殖，
+/// 钓得越狠恢复越慢；被钓光的水域每小时仍会游进来一条鱼
+pub fn fish_regrowth_system(
+    game_time: Res<GameTime>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut last_hour: Local<Option<(u32, u32)>>,
+) {
+    let now = (game_time.day, game_time.hour);
+    if *last_hour == Some(now) {
+        return;
+    }
+    let first_run = last_hour.is_none();
+    *last_hour = Some(now);
+    if first_run || map_registry.fish_stocks.values().all(|stock| stock.fish >= stock.capacity) {
+        return;
+    }
+
+    for stock in map_registry.fish_stocks.values_mut() {
+        if stock.fish >= stock.capacity {
+            continue;
+        }
+        let growth = FISH_BREEDING_RATE * stock.fish as f32 * (1.0 - stock.abundance());
+        stock.fish = (stock.fish + (growth.round() as u32).max(1)).min(stock.capacity);
+    }
+}
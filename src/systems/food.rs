@@ -0,0 +1,154 @@
+use crate::components::*;
+use crate::resources::*;
+use bevy::prelude::*;
+
+/// 在作坊里完成一份工作的基础速度（每秒进度）
+const CRAFT_SPEED: f32 = 0.25;
+/// 厨房一次最多用到几种原料
+const MAX_MEAL_INGREDIENTS: usize = 3;
+/// 酒坊每份原料酿出的酒
+const DRINKS_PER_BREW: u32 = 2;
+/// 库存饭菜少于该值时厨房才开工
+const MEAL_STOCK_TARGET: u32 = 30;
+/// 库存的酒少于该值时酒坊才开工
+const DRINK_STOCK_TARGET: u32 = 30;
+
+fn is_raw(item: FoodItem) -> bool {
+    matches!(item, FoodItem::Raw(_))
+}
+
+/// 作坊是否有活可干：有原料，且库存的成品还不够
+pub fn workshop_has_job(kind: WorkshopKind, inventory: &GlobalInventory) -> bool {
+    let food = &inventory.food;
+    match kind {
+        WorkshopKind::Kitchen => {
+            food.count(|item| item.category() == FoodCategory::Meal) < MEAL_STOCK_TARGET && food.count(is_raw) > 0
+        }
+        WorkshopKind::Still => {
+            food.count(|item| item.category() == FoodCategory::Drink) < DRINK_STOCK_TARGET
+                && DrinkKind::ALL
+                    .iter()
+                    .any(|drink| food.amount(FoodItem::Raw(drink.ingredient())) > 0)
+        }
+    }
+}
+
+/// 作坊工作：站在作坊上的矮人按工艺技能累积进度，完成后从仓库取原料——
+/// 厨房每种原料各取一份（最多三种，容易坏的优先），做成同样份数的饭菜，用到的原料种类越多饭菜越好；
+/// 酒坊取一份存量较多的谷物或野果，酿成两份酒
+#[allow(clippy::type_complexity)]
+pub fn workshop_system(
+    time: Res<Time>,
+    mut query: Query<(&Dwarf, &mut WorkState, &mut Skills, &Personality, &mut Thoughts, &GridPosition)>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+
+    for (dwarf, mut work_state, mut skills, personality, mut thoughts, pos) in query.iter_mut() {
+        let Some(Task::Crafting(target, workshop)) = work_state.current_task.clone() else {
+            continue;
+        };
+        if (pos.x, pos.y) != (target.x, target.y) {
+            continue;
+        }
+
+        let temperament = personality.work_speed() * MoodLevel::from_mood(dwarf.happiness).work_speed();
+        work_state.work_progress +=
+            time.delta_secs() * CRAFT_SPEED * skills.work_speed(SkillKind::Crafting) * temperament;
+        if work_state.work_progress < 1.0 {
+            continue;
+        }
+
+        work_state.work_progress = 0.0;
+        work_state.current_task = Some(Task::Idle);
+        work_state.task_cooldown = 0.5;
+        work_state.task_duration = 0.0;
+
+        let made = match workshop {
+            WorkshopKind::Kitchen => cook(&mut inventory.food),
+            WorkshopKind::Still => brew(&mut inventory.food),
+        };
+        let Some(made) = made else {
+            logger.info(format!("{} 在{}里找不到原料", dwarf.name, workshop.label()));
+            work_state.task_cooldown = 2.0;
+            continue;
+        };
+        logger.info(format!("{} {}", dwarf.name, made));
+        super::gain_experience(dwarf, &mut skills, &mut thoughts, SkillKind::Crafting, &mut logger);
+    }
+}
+
+/// 烹饪：每种原料取一份，做成同样份数的饭菜，每份以其中一种原料为主料
+fn cook(food: &mut FoodStock) -> Option<String> {
+    let mut ingredients = Vec::new();
+    for kind in FoodKind::ALL {
+        if ingredients.len() >= MAX_MEAL_INGREDIENTS {
+            break;
+        }
+        if food.take_where(|item| item == FoodItem::Raw(kind)).is_some() {
+            ingredients.push(kind);
+        }
+    }
+    if ingredients.is_empty() {
+        return None;
+    }
+    let meal = MealKind::from_ingredients(ingredients.len());
+    for kind in ingredients.iter() {
+        food.add(FoodItem::Meal(meal, *kind), 1);
+    }
+    let names: Vec<&str> = ingredients.iter().map(|kind| kind.label()).collect();
+    Some(format!("用{}做了 {} 份{}", names.join("、"), ingredients.len(), meal.label()))
+}
+
+/// 酿酒：取一份存量较多的原料酿成酒
+fn brew(food: &mut FoodStock) -> Option<String> {
+    let drink = DrinkKind::ALL
+        .into_iter()
+        .filter(|drink| food.amount(FoodItem::Raw(drink.ingredient())) > 0)
+        .max_by_key(|drink| food.amount(FoodItem::Raw(drink.ingredient())))?;
+    food.take_where(|item| item == FoodItem::Raw(drink.ingredient()))?;
+    food.add(FoodItem::Drink(drink), DRINKS_PER_BREW);
+    Some(format!("用{}酿了 {} 份{}", drink.ingredient().label(), DRINKS_PER_BREW, drink.label()))
+}
+
+/// 食物腐烂：每个游戏小时仓库里和散落在地上的食物存放时间加一，过了保质期的整批扔掉
+pub fn food_spoilage_system(
+    game_time: Res<GameTime>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut last_hour: Local<Option<(u32, u32)>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let now = (game_time.day, game_time.hour);
+    if *last_hour == Some(now) {
+        return;
+    }
+    let first_run = last_hour.is_none();
+    *last_hour = Some(now);
+    if first_run {
+        return;
+    }
+
+    for (item, amount) in inventory.food.age_one_hour() {
+        logger.warning(format!("仓库里 {} 份{}腐烂了", amount, item.label()));
+    }
+
+    if map_registry.loose_food.values().all(|loose| loose.is_empty()) {
+        return;
+    }
+    for loose in map_registry.loose_food.values_mut() {
+        loose.retain_mut(|food| {
+            food.age_hours += 1;
+            match FoodItem::Raw(food.kind).shelf_life_hours() {
+                Some(limit) if food.age_hours >= limit => {
+                    logger.warning(format!("地上 {} 份{}腐烂了", food.amount, food.kind.label()));
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+}
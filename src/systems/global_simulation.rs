@@ -1,6 +1,8 @@
 use crate::components::*;
 use crate::resources::*;
+use crate::world_map_data::WorldAtlas;
 use bevy::prelude::*;
+use std::collections::HashMap;
 
 /// 全局模拟系统 - 模拟不在当前地图的矮人工作
 /// 这个系统在每次进入地图前运行，计算矮人在离开期间完成的工作
@@ -42,6 +44,9 @@ pub fn simulate_offscreen_dwarves(
         
         max_hours_passed = max_hours_passed.max(time_passed_hours);
         
+        // 离开期间照样长大
+        dwarf.age.days += current_day.saturating_sub(dwarf.last_update_day);
+
         // 更新矮人的时间戳
         dwarf.last_update_day = current_day;
         dwarf.last_update_hour = current_hour;
@@ -60,7 +65,8 @@ pub fn simulate_offscreen_dwarves(
         || total_resources_gathered.2 > 0 || total_resources_gathered.3 > 0 {
         inventory.wood += total_resources_gathered.0;
         inventory.stone += total_resources_gathered.1;
-        inventory.food += total_resources_gathered.2;
+        // 离开期间采集到的食物按野果入库
+        inventory.food.add(FoodItem::Raw(FoodKind::Plant), total_resources_gathered.2);
         inventory.metal += total_resources_gathered.3;
         
         logger.info(format!(
@@ -126,19 +132,45 @@ fn simulate_dwarf_work(dwarf: &mut StoredDwarf, hours_passed: u32) -> (u32, u32,
     let hours_f = hours_passed as f32;
     dwarf.hunger = (dwarf.hunger - hours_f * 0.5).max(0.0);     // 饥饿度下降
     dwarf.happiness = (dwarf.happiness - hours_f * 0.2).max(20.0); // 幸福度略微下降，但保持最低值
+    if hours_passed >= 8 {
+        dwarf.fatigue = 0.0; // 离开期间照常作息，已经睡足
+    }
+    dwarf.thirst = 30.0; // 离开期间就地找水喝
     
     resources
 }
 
-/// 模拟所有未加载地图的矮人（在WorldView状态下调用）
+/// 模拟所有未加载地图的矮人，并把各地图的植被补算到当前时间（在WorldView状态下调用）
 pub fn simulate_all_offscreen_dwarves(
     mut map_registry: ResMut<GeneratedMapsRegistry>,
     mut inventory: ResMut<GlobalInventory>,
     game_time: Res<GameTime>,
+    world_atlas: Res<WorldAtlas>,
     mut logger: ResMut<crate::logger::GameLogger>,
 ) {
     let current_day = game_time.day;
     let current_hour = game_time.hour;
+
+    // 植被不论有没有矮人都在生长；离线地图上没有踩踏
+    let mut rng = rand::thread_rng();
+    let coords: Vec<IVec2> = map_registry.maps.keys().copied().collect();
+    let no_trampling = HashMap::new();
+    let regrown: usize = coords
+        .into_iter()
+        .map(|coord| {
+            super::advance_vegetation(
+                &mut map_registry,
+                &world_atlas,
+                coord,
+                game_time.total_hours(),
+                &no_trampling,
+                &mut rng,
+            )
+        })
+        .sum();
+    if regrown > 0 {
+        logger.debug(format!("全局模拟: 植被生长改变了 {} 个地块", regrown));
+    }
     
     let mut total_wood = 0u32;
     let mut total_stone = 0u32;
@@ -193,7 +225,7 @@ pub fn simulate_all_offscreen_dwarves(
     if tiles_processed > 0 {
         inventory.wood += total_wood;
         inventory.stone += total_stone;
-        inventory.food += total_food;
+        inventory.food.add(FoodItem::Raw(FoodKind::Plant), total_food);
         inventory.metal += total_metal;
         
         logger.info(format!(
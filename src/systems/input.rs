@@ -142,6 +142,7 @@ pub fn mouse_control_system(
     selected: Res<SelectedDwarf>,
     mut dwarves: Query<&mut WorkState, With<Dwarf>>,
     terrain_query: Query<(&GridPosition, &Terrain)>,
+    mut reservations: ResMut<TileReservations>,
 ) {
     // 只在右键点击且有选中矮人时处理
     if !mouse_button.just_pressed(MouseButton::Right) {
@@ -190,6 +191,8 @@ pub fn mouse_control_system(
     // 只有当目标位置可行走时才分配任务
     if is_walkable {
         if let Ok(mut work_state) = dwarves.get_mut(selected_entity) {
+            // 玩家指派优先：接管该格子的预约，原先预约的矮人会放弃目标
+            reservations.reserve((grid_x, grid_y), selected_entity);
             work_state.current_task = Some(Task::Gathering(GridPosition {
                 x: grid_x,
                 y: grid_y,
@@ -198,6 +201,60 @@ pub fn mouse_control_system(
     }
 }
 
+/// 集合令系统 - 按G让所有矮人前往鼠标所指的位置（共享同一张流场）
+pub fn rally_command_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut dwarves: Query<&mut WorkState, With<Dwarf>>,
+    terrain_query: Query<(&GridPosition, &Terrain)>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyG) {
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+
+    let Ok(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
+        return;
+    };
+
+    let grid_x = ((world_position.x + (WORLD_WIDTH as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+    let grid_y = ((world_position.y + (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+
+    let is_walkable = terrain_query
+        .iter()
+        .any(|(terrain_pos, terrain)| terrain_pos.x == grid_x && terrain_pos.y == grid_y && terrain.walkable);
+    if !is_walkable {
+        logger.warning(format!("集合点 ({}, {}) 无法通行", grid_x, grid_y));
+        return;
+    }
+
+    let mut count = 0;
+    for mut work_state in dwarves.iter_mut() {
+        work_state.current_task = Some(Task::Rally(GridPosition {
+            x: grid_x,
+            y: grid_y,
+        }));
+        work_state.cached_path.clear();
+        work_state.path_index = 0;
+        work_state.task_duration = 0.0;
+        count += 1;
+    }
+    logger.info(format!("集合令: {} 名矮人前往 ({}, {})", count, grid_x, grid_y));
+}
+
 /// 矮人名字悬停系统
 pub fn dwarf_name_hover_system(
     mut commands: Commands,
@@ -259,7 +316,8 @@ pub fn dwarf_name_hover_system(
     }
 }
 
-/// 地形信息悬停系统 - 显示鼠标下方的地形信息
+/// 地形信息悬停系统 - 显示鼠标下方的地形信息，水面上显示剩余的鱼群
+#[allow(clippy::too_many_arguments)]
 pub fn terrain_info_hover_system(
     mut commands: Commands,
     windows: Query<&Window>,
@@ -267,6 +325,9 @@ pub fn terrain_info_hover_system(
     terrain_query: Query<(&GridPosition, &Terrain)>,
     existing_labels: Query<Entity, With<TerrainInfoLabel>>,
     asset_server: Res<AssetServer>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    world_atlas: Res<crate::world_map_data::WorldAtlas>,
 ) {
     // 清除所有现有地形标签
     for entity in existing_labels.iter() {
@@ -304,13 +365,31 @@ pub fn terrain_info_hover_system(
         if terrain_pos.x == grid_x && terrain_pos.y == grid_y {
             let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
 
-            // 构建地形信息文本
+            // 构建地形信息文本，建有建筑结构时一并显示
+            let construction_info = terrain
+                .construction
+                .map(|construction| format!("{}{}\n", construction.material.label(), construction.kind.label()))
+                .unwrap_or_default();
+            // 还没人钓过的水域按生物群系显示满额的鱼群
+            let fish_info = match (terrain.terrain_type, active_local.coord) {
+                (TerrainType::Water, Some(coord)) => {
+                    let stock = map_registry
+                        .fish_stocks
+                        .get(&coord)
+                        .cloned()
+                        .unwrap_or_else(|| FishPopulation::for_cell(world_atlas.cell_at(coord)));
+                    format!("\n鱼群: {}/{}", stock.fish, stock.capacity)
+                }
+                _ => String::new(),
+            };
             let terrain_info = format!(
-                "{}\n资源产出: {:.0}%\n丰富度: {:.1}x\n移动速度: {:.0}%",
+                "{}{}\n资源产出: {:.0}%\n丰富度: {:.1}x\n移动速度: {:.0}%{}",
+                construction_info,
                 terrain.terrain_type.description(),
                 terrain.terrain_type.resource_multiplier() * 100.0,
                 terrain.resource_richness,
-                terrain.terrain_type.movement_speed() * 100.0
+                terrain.movement_speed() * 100.0,
+                fish_info
             );
 
             // 在鼠标位置附近显示信息
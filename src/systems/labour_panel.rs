@@ -0,0 +1,284 @@
+use crate::components::*;
+use crate::ui_framework::*;
+use bevy::prelude::*;
+
+/// 允许的劳动按钮颜色
+const LABOUR_ON_COLOR: Color = Color::srgb(0.2, 0.5, 0.25);
+/// 禁止的劳动按钮颜色
+const LABOUR_OFF_COLOR: Color = Color::srgb(0.25, 0.2, 0.2);
+/// 表头按钮颜色
+const HEADER_COLOR: Color = Color::srgb(0.3, 0.4, 0.5);
+/// 夜班按钮颜色
+const NIGHT_SHIFT_COLOR: Color = Color::srgb(0.2, 0.2, 0.45);
+
+/// 劳动面板中矮人列表的容器
+#[derive(Component)]
+pub struct LabourPanelRows;
+
+/// 劳动面板按钮
+#[derive(Component, Clone, Copy)]
+pub enum LabourButton {
+    /// 切换单个矮人的一项劳动
+    Toggle(Entity, SkillKind),
+    /// 为全体矮人切换一整列
+    Column(SkillKind),
+    /// 切换单个矮人的作息班次
+    Shift(Entity),
+}
+
+/// 设置劳动管理面板（初始隐藏，L键切换）
+pub fn setup_labour_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<UITheme>,
+) {
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let mut builder = PanelBuilder::new(commands.reborrow(), font.clone(), theme.clone());
+
+    // 创建劳动管理面板（右侧中间）
+    let labour_config = PanelConfig {
+        anchor: PanelAnchor::MiddleRight,
+        offset: Vec2::new(15.0, 0.0),
+        min_width: 560.0,
+        min_height: 200.0,
+        background_color: Color::srgba(0.06, 0.08, 0.12, 0.94),
+        border_color: Some(Color::srgba(0.4, 0.7, 0.4, 0.7)),
+        padding: 12.0,
+    };
+
+    let labour_panel = builder.create_hidden_panel("labour_panel", labour_config, LabourPanel);
+    builder.add_title(labour_panel, "◆ 劳动管理 ◆");
+    builder.add_small_text(
+        labour_panel,
+        "点击格子切换单个矮人，点击表头切换全体；数字为技能等级，禁止的劳动不会被自动分配；最后一列切换白班/夜班",
+    );
+
+    let rows = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::top(Val::Px(8.0)),
+                ..default()
+            },
+            LabourPanelRows,
+        ))
+        .id();
+    commands.entity(labour_panel).add_child(rows);
+}
+
+/// 切换劳动管理面板显示/隐藏（L键）
+pub fn toggle_labour_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<(&mut UIPanel, &mut Node)>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyL) {
+        toggle_panel_visibility(&mut panel_query, "labour_panel");
+    }
+}
+
+/// 处理劳动按钮点击
+pub fn labour_button_system(
+    interaction_query: Query<(&Interaction, &LabourButton), Changed<Interaction>>,
+    mut dwarves: Query<(&Dwarf, &mut Labours, &mut WorkState)>,
+    mut shifts: Query<&mut Shift>,
+    terrain_query: Query<(&GridPosition, &Terrain)>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            LabourButton::Toggle(entity, kind) => {
+                let Ok((dwarf, mut labours, mut work_state)) = dwarves.get_mut(entity) else {
+                    continue;
+                };
+                let enabled = labours.toggle(kind);
+                if !enabled {
+                    cancel_job_for_labour(&mut work_state, kind, &terrain_query);
+                }
+                logger.info(format!(
+                    "{} {}{}劳动",
+                    dwarf.name,
+                    if enabled { "允许" } else { "禁止" },
+                    kind.label()
+                ));
+            }
+            LabourButton::Column(kind) => {
+                // 全部允许时改为全部禁止，否则全部允许
+                let enable = !dwarves.iter().all(|(_, labours, _)| labours.allows(kind));
+                for (_, mut labours, mut work_state) in dwarves.iter_mut() {
+                    if labours.allows(kind) != enable {
+                        labours.set(kind, enable);
+                    }
+                    if !enable {
+                        cancel_job_for_labour(&mut work_state, kind, &terrain_query);
+                    }
+                }
+                logger.info(format!(
+                    "全体矮人{}{}劳动",
+                    if enable { "允许" } else { "禁止" },
+                    kind.label()
+                ));
+            }
+            LabourButton::Shift(entity) => {
+                let (Ok((dwarf, ..)), Ok(mut shift)) = (dwarves.get(entity), shifts.get_mut(entity)) else {
+                    continue;
+                };
+                *shift = shift.toggled();
+                logger.info(format!("{} 改上{}", dwarf.name, shift.label()));
+            }
+        }
+    }
+}
+
+/// 劳动被禁止时，放弃正在进行的同类工作
+fn cancel_job_for_labour(
+    work_state: &mut WorkState,
+    kind: SkillKind,
+    terrain_query: &Query<(&GridPosition, &Terrain)>,
+) {
+    let labour = match &work_state.current_task {
+        Some(Task::Gathering(target)) | Some(Task::Mining(target)) => terrain_query
+            .iter()
+            .find(|(pos, _)| *pos == target)
+            .map(|(_, terrain)| terrain.terrain_type.labour()),
+        Some(Task::Crafting(..)) => Some(SkillKind::Crafting),
+        Some(Task::Fishing(_)) => Some(SkillKind::Fishing),
+        Some(Task::PickUp(_)) => Some(SkillKind::Hauling),
+        _ => return,
+    };
+    if labour == Some(kind) {
+        work_state.current_task = Some(Task::Idle);
+        work_state.work_progress = 0.0;
+        work_state.cached_path.clear();
+        work_state.path_index = 0;
+        work_state.task_duration = 0.0;
+    }
+}
+
+/// 面板可见时，在劳动或技能变化后重建矮人列表
+#[allow(clippy::type_complexity)]
+pub fn labour_panel_refresh_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    panel_query: Query<Ref<UIPanel>, With<LabourPanel>>,
+    rows_query: Query<Entity, With<LabourPanelRows>>,
+    dwarves: Query<(Entity, &Dwarf, &Skills, &Labours, &Shift)>,
+    changed: Query<(), Or<(Changed<Labours>, Changed<Skills>, Changed<Shift>)>>,
+    mut last_count: Local<usize>,
+) {
+    let Ok(panel) = panel_query.single() else {
+        return;
+    };
+    if panel.state != PanelState::Visible {
+        return;
+    }
+    let count = dwarves.iter().count();
+    if !panel.is_changed() && changed.is_empty() && count == *last_count {
+        return;
+    }
+    *last_count = count;
+
+    let Ok(rows) = rows_query.single() else {
+        return;
+    };
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+
+    let mut sorted: Vec<_> = dwarves.iter().collect();
+    sorted.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+    commands.entity(rows).despawn_related::<Children>();
+    commands.entity(rows).with_children(|parent| {
+        // 表头：每列一个全体切换按钮
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_name_cell(row, &font, "矮人");
+                for kind in SkillKind::ALL {
+                    spawn_cell(row, &font, kind.label(), HEADER_COLOR, LabourButton::Column(kind));
+                }
+                spawn_name_cell(row, &font, "班次");
+            });
+
+        for (entity, dwarf, skills, labours, shift) in sorted {
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_name_cell(row, &font, &dwarf.name);
+                    for kind in SkillKind::ALL {
+                        let (label, color) = if labours.allows(kind) {
+                            (format!("● {}", skills.level(kind)), LABOUR_ON_COLOR)
+                        } else {
+                            (format!("○ {}", skills.level(kind)), LABOUR_OFF_COLOR)
+                        };
+                        spawn_cell(row, &font, &label, color, LabourButton::Toggle(entity, kind));
+                    }
+                    let shift_color = match shift {
+                        Shift::Day => HEADER_COLOR,
+                        Shift::Night => NIGHT_SHIFT_COLOR,
+                    };
+                    spawn_cell(row, &font, shift.label(), shift_color, LabourButton::Shift(entity));
+                });
+        }
+    });
+}
+
+fn spawn_name_cell(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, name: &str) {
+    parent.spawn((
+        Text::new(name),
+        TextFont {
+            font: font.clone(),
+            font_size: 15.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.95, 0.9, 0.75)),
+        Node {
+            width: Val::Px(80.0),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_cell(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    label: &str,
+    color: Color,
+    button: LabourButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(60.0),
+                padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                margin: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(color),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+        });
+}
@@ -0,0 +1,494 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy::window::{Ime, PrimaryWindow};
+
+use crate::legends::*;
+use crate::resources::{FortressChronicle, GameState};
+use crate::world_map_data::WorldAtlas;
+
+/// 列表中最多显示的条目数
+const MAX_LISTED_ENTRIES: usize = 60;
+/// 详情中最多显示的链接数
+const MAX_DETAIL_LINKS: usize = 24;
+
+const TXT_EXPORT_PATH: &str = "legends_export.txt";
+const JSON_EXPORT_PATH: &str = "legends_export.json";
+
+/// 传说浏览器状态
+#[derive(Resource)]
+pub struct LegendsBrowser {
+    pub category: LegendCategory,
+    pub query: String,
+    pub selected: Option<LegendRef>,
+    /// 关闭传说界面后返回的状态
+    pub return_state: GameState,
+    pub status: String,
+}
+
+impl Default for LegendsBrowser {
+    fn default() -> Self {
+        Self {
+            category: LegendCategory::Civilizations,
+            query: String::new(),
+            selected: None,
+            return_state: GameState::MainMenu,
+            status: String::new(),
+        }
+    }
+}
+
+/// 导出格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegendsExportFormat {
+    Text,
+    Json,
+}
+
+/// 传说界面根节点
+#[derive(Component)]
+pub struct LegendsUI;
+
+/// 传说界面按钮
+#[derive(Component, Clone, Copy)]
+pub enum LegendsButton {
+    Tab(LegendCategory),
+    Entry(LegendRef),
+    Export(LegendsExportFormat),
+    Back,
+}
+
+/// 条目列表容器
+#[derive(Component)]
+pub struct LegendsListRoot;
+
+/// 详情链接容器
+#[derive(Component)]
+pub struct LegendsLinkRoot;
+
+/// 传说界面中需要刷新的文本
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum LegendsText {
+    Search,
+    Status,
+    Detail,
+}
+
+const BUTTON_COLOR: Color = Color::srgb(0.2, 0.25, 0.32);
+const BUTTON_ACTIVE_COLOR: Color = Color::srgb(0.45, 0.38, 0.15);
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.32, 0.4, 0.5);
+const ENTRY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.0);
+const ENTRY_SELECTED_COLOR: Color = Color::srgba(0.45, 0.38, 0.15, 0.6);
+
+/// 进入传说界面
+pub fn setup_legends_view(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut browser: ResMut<LegendsBrowser>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut keyboard_events: ResMut<Messages<KeyboardInput>>,
+) {
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+
+    // 丢弃打开界面时的按键（如L键），避免被当作搜索词
+    keyboard_events.clear();
+
+    // 允许输入法输入中文搜索词
+    if let Ok(mut window) = windows.single_mut() {
+        window.ime_enabled = true;
+    }
+    browser.status = "输入文字进行搜索 | Backspace删除 | Esc返回".to_string();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.08, 0.08, 0.12)),
+            LegendsUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("◆ 传说 ◆"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.9, 0.5)),
+                Node {
+                    margin: UiRect::bottom(Val::Px(12.0)),
+                    ..default()
+                },
+            ));
+
+            // 分类标签与操作按钮
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for category in LegendCategory::ALL {
+                        spawn_button(row, &font, category.label(), LegendsButton::Tab(category));
+                    }
+                    spawn_button(row, &font, "导出文本", LegendsButton::Export(LegendsExportFormat::Text));
+                    spawn_button(row, &font, "导出JSON", LegendsButton::Export(LegendsExportFormat::Json));
+                    spawn_button(row, &font, "返回", LegendsButton::Back);
+                });
+
+            parent.spawn((
+                Text::new("搜索: _"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.95, 1.0)),
+                LegendsText::Search,
+            ));
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.8)),
+                Node {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                },
+                LegendsText::Status,
+            ));
+
+            // 主体：左侧条目列表，右侧详情
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    flex_grow: 1.0,
+                    min_height: Val::Px(0.0),
+                    ..default()
+                })
+                .with_children(|body| {
+                    body.spawn((
+                        Node {
+                            width: Val::Percent(45.0),
+                            flex_direction: FlexDirection::Column,
+                            padding: UiRect::all(Val::Px(8.0)),
+                            margin: UiRect::right(Val::Px(12.0)),
+                            overflow: Overflow::clip(),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+                        LegendsListRoot,
+                    ));
+
+                    body.spawn((
+                        Node {
+                            flex_grow: 1.0,
+                            flex_direction: FlexDirection::Column,
+                            padding: UiRect::all(Val::Px(12.0)),
+                            overflow: Overflow::clip(),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgba(0.08, 0.08, 0.15, 0.92)),
+                    ))
+                    .with_children(|detail| {
+                        detail.spawn((
+                            Text::new("选择一个条目查看详情"),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 18.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                            Node {
+                                margin: UiRect::bottom(Val::Px(10.0)),
+                                ..default()
+                            },
+                            LegendsText::Detail,
+                        ));
+                        detail.spawn((
+                            Node {
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            LegendsLinkRoot,
+                        ));
+                    });
+                });
+        });
+}
+
+/// 离开传说界面
+pub fn cleanup_legends_view(
+    mut commands: Commands,
+    roots: Query<Entity, With<LegendsUI>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for entity in roots.iter() {
+        if let Ok(mut entity_cmd) = commands.get_entity(entity) {
+            entity_cmd.despawn();
+        }
+    }
+    if let Ok(mut window) = windows.single_mut() {
+        window.ime_enabled = false;
+    }
+}
+
+/// 搜索输入与返回快捷键
+pub fn legends_keyboard_system(
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut ime_events: MessageReader<Ime>,
+    mut browser: ResMut<LegendsBrowser>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Escape => next_state.set(browser.return_state.clone()),
+            Key::Backspace => {
+                browser.query.pop();
+            }
+            Key::Character(text) => {
+                browser.query.push_str(text.as_str());
+            }
+            Key::Space => browser.query.push(' '),
+            _ => {}
+        }
+    }
+
+    for event in ime_events.read() {
+        if let Ime::Commit { value, .. } = event {
+            browser.query.push_str(value);
+        }
+    }
+}
+
+/// 传说界面按钮交互
+pub fn legends_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &LegendsButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut browser: ResMut<LegendsBrowser>,
+    world_atlas: Res<WorldAtlas>,
+    chronicle: Res<FortressChronicle>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    for (interaction, button, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => match *button {
+                LegendsButton::Tab(category) => {
+                    browser.category = category;
+                    browser.selected = None;
+                }
+                LegendsButton::Entry(entry) => {
+                    // 跳转到链接条目时同步切换分类
+                    browser.category = entry.category();
+                    browser.selected = Some(entry);
+                }
+                LegendsButton::Export(format) => {
+                    let (path, content) = match format {
+                        LegendsExportFormat::Text => (
+                            TXT_EXPORT_PATH,
+                            export_text(&world_atlas.history, &chronicle),
+                        ),
+                        LegendsExportFormat::Json => (
+                            JSON_EXPORT_PATH,
+                            export_json(&world_atlas.history, &chronicle),
+                        ),
+                    };
+                    browser.status = match std::fs::write(path, content) {
+                        Ok(()) => {
+                            logger.info(format!("传说已导出到 {}", path));
+                            format!("已导出到 {}", path)
+                        }
+                        Err(err) => {
+                            logger.error(format!("导出传说失败: {}", err));
+                            format!("导出失败: {}", err)
+                        }
+                    };
+                }
+                LegendsButton::Back => next_state.set(browser.return_state.clone()),
+            },
+            Interaction::Hovered => {
+                *color = BackgroundColor(BUTTON_HOVER_COLOR);
+            }
+            Interaction::None => {
+                *color = BackgroundColor(button_base_color(button, &browser));
+            }
+        }
+    }
+}
+
+/// 浏览器状态变化时刷新列表、详情与搜索框
+#[allow(clippy::too_many_arguments)]
+pub fn legends_refresh_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    browser: Res<LegendsBrowser>,
+    world_atlas: Res<WorldAtlas>,
+    chronicle: Res<FortressChronicle>,
+    list_root: Query<Entity, With<LegendsListRoot>>,
+    link_root: Query<Entity, With<LegendsLinkRoot>>,
+    mut texts: Query<(&mut Text, &LegendsText)>,
+    mut buttons: Query<(&LegendsButton, &Interaction, &mut BackgroundColor)>,
+) {
+    if !browser.is_changed() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let history = &world_atlas.history;
+
+
+    // 重建条目列表
+    let entries = list_entries(history, &chronicle, browser.category, &browser.query);
+    if let Ok(root) = list_root.single() {
+        commands.entity(root).despawn_related::<Children>();
+        commands.entity(root).with_children(|list| {
+            list.spawn((
+                Text::new(format!("{} - 共{}条", browser.category.label(), entries.len())),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.9, 0.5)),
+            ));
+            for (entry, label) in entries.iter().take(MAX_LISTED_ENTRIES) {
+                spawn_entry_button(list, &font, label, *entry, browser.selected == Some(*entry));
+            }
+            if entries.len() > MAX_LISTED_ENTRIES {
+                list.spawn((
+                    Text::new(format!("……还有{}条，请输入搜索词缩小范围", entries.len() - MAX_LISTED_ENTRIES)),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.6, 0.6, 0.7)),
+                ));
+            }
+        });
+    }
+
+    // 重建详情与交叉链接
+    let detail = browser.selected.map(|entry| describe(history, &chronicle, entry));
+    for (mut text, kind) in texts.iter_mut() {
+        **text = match kind {
+            LegendsText::Search => format!("搜索: {}_", browser.query),
+            LegendsText::Status => browser.status.clone(),
+            LegendsText::Detail => detail
+                .as_ref()
+                .map(|detail| detail.text.clone())
+                .unwrap_or_else(|| "选择一个条目查看详情".to_string()),
+        };
+    }
+    if let Ok(root) = link_root.single() {
+        commands.entity(root).despawn_related::<Children>();
+        if let Some(detail) = detail {
+            commands.entity(root).with_children(|links| {
+                if !detail.links.is_empty() {
+                    links.spawn((
+                        Text::new("━━━ 相关条目 ━━━"),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 0.9, 0.5)),
+                    ));
+                }
+                for (entry, label) in detail.links.iter().take(MAX_DETAIL_LINKS) {
+                    spawn_entry_button(links, &font, &format!("→ {}", label), *entry, false);
+                }
+            });
+        }
+    }
+
+    // 刷新分类标签高亮
+    for (button, interaction, mut color) in buttons.iter_mut() {
+        if *interaction == Interaction::None {
+            *color = BackgroundColor(button_base_color(button, &browser));
+        }
+    }
+}
+
+fn button_base_color(button: &LegendsButton, browser: &LegendsBrowser) -> Color {
+    match button {
+        LegendsButton::Tab(category) if *category == browser.category => BUTTON_ACTIVE_COLOR,
+        LegendsButton::Entry(entry) if browser.selected == Some(*entry) => ENTRY_SELECTED_COLOR,
+        LegendsButton::Entry(_) => ENTRY_COLOR,
+        _ => BUTTON_COLOR,
+    }
+}
+
+fn spawn_button(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, label: &str, button: LegendsButton) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(14.0), Val::Px(6.0)),
+                margin: UiRect::all(Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+        });
+}
+
+fn spawn_entry_button(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    label: &str,
+    entry: LegendRef,
+    selected: bool,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(if selected { ENTRY_SELECTED_COLOR } else { ENTRY_COLOR }),
+            LegendsButton::Entry(entry),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.95)),
+            ));
+        });
+}
@@ -46,7 +46,7 @@ pub fn setup_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                 },
                 TextColor(Color::srgb(0.7, 0.7, 0.8)),
                 Node {
-                    margin: UiRect::bottom(Val::Px(80.0)),
+                    margin: UiRect::bottom(Val::Px(40.0)),
                     ..default()
                 },
             ));
@@ -78,6 +78,60 @@ pub fn setup_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                     ));
                 });
 
+            // 创建世界按钮
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(80.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.3, 0.4, 0.5)),
+                    CreateWorldButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("创建世界"),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 36.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                    ));
+                });
+
+            // 传说按钮
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(80.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.3, 0.4, 0.5)),
+                    LegendsMenuButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("传说"),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 36.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                    ));
+                });
+
             // 游戏说明
             parent.spawn((
                 Text::new("操作提示:\n\n• WASD/方向键: 移动视角\n• 鼠标左键: 选择矮人\n• 鼠标右键: 指挥矮人移动\n• 空格: 暂停/继续\n• 数字键1-5: 调节时间速度"),
@@ -88,7 +142,7 @@ pub fn setup_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                 },
                 TextColor(Color::srgb(0.8, 0.8, 0.9)),
                 Node {
-                    margin: UiRect::top(Val::Px(60.0)),
+                    margin: UiRect::top(Val::Px(30.0)),
                     ..default()
                 },
             ));
@@ -123,17 +177,29 @@ pub fn cleanup_main_menu(mut commands: Commands, menu_query: Query<Entity, With<
 /// 菜单按钮交互系统
 pub fn menu_button_system(
     mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<StartButton>),
+        (Entity, &Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
     >,
+    legends_buttons: Query<(), With<LegendsMenuButton>>,
+    create_world_buttons: Query<(), With<CreateWorldButton>>,
+    mut legends_browser: ResMut<crate::systems::LegendsBrowser>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (interaction, mut color) in interaction_query.iter_mut() {
+    for (entity, interaction, mut color) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
-                // 点击开始游戏
                 *color = BackgroundColor(Color::srgb(0.2, 0.5, 0.3));
-                next_state.set(GameState::WorldView);
+                if legends_buttons.contains(entity) {
+                    // 点击查看传说，关闭后回到主菜单
+                    legends_browser.return_state = GameState::MainMenu;
+                    next_state.set(GameState::Legends);
+                } else if create_world_buttons.contains(entity) {
+                    // 点击进入创建世界界面
+                    next_state.set(GameState::CreateWorld);
+                } else {
+                    // 点击开始游戏
+                    next_state.set(GameState::WorldView);
+                }
             }
             Interaction::Hovered => {
                 // 鼠标悬停
@@ -0,0 +1,361 @@
+use crate::components::*;
+use crate::connectivity::RegionMap;
+use crate::resources::*;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// 值勤的队员会主动迎击多少格以内的敌人
+const ENGAGE_RADIUS: i32 = 6;
+/// 到达岗位或巡逻点的判定距离
+const POST_ARRIVAL_RADIUS: i32 = 1;
+/// 打造一把武器所需的金属
+const WEAPON_METAL_COST: u32 = 2;
+/// 打造一副护甲所需的金属
+const ARMOUR_METAL_COST: u32 = 3;
+/// 矮人徒手一击的基础伤害
+const DWARF_BASE_DAMAGE: f32 = 8.0;
+/// 矮人两次攻击之间的间隔（秒）
+const DWARF_ATTACK_INTERVAL: f32 = 1.0;
+
+fn distance(a: (i32, i32), b: (i32, i32)) -> i32 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+/// 相邻（含斜角）即可近身攻击
+fn in_reach(a: (i32, i32), b: (i32, i32)) -> bool {
+    (a.0 - b.0).abs() <= 1 && (a.1 - b.1).abs() <= 1
+}
+
+/// 小队命令与警戒：为队员安排驻守、巡逻、训练、击杀和撤退，领取制服；
+/// 警戒时平民撤进避难洞区，每人分到洞区里的一块空地。需在工作系统之前运行，以覆盖工作决策
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn squad_order_system(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    mut military: ResMut<Military>,
+    mut dwarves: Query<
+        (
+            Entity,
+            &Dwarf,
+            &GridPosition,
+            &mut WorkState,
+            &mut Equipment,
+            Option<&FollowFlowField>,
+            Has<Tantrum>,
+        ),
+        Without<Hostile>,
+    >,
+    hostiles: Query<(Entity, &GridPosition), With<Hostile>>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    region_map: Res<RegionMap>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    // 击杀目标已经倒下的小队结束命令（只在需要时修改，避免每帧触发面板刷新）
+    let finished: Vec<usize> = military
+        .squads
+        .iter()
+        .enumerate()
+        .filter(|(_, squad)| matches!(squad.order, SquadOrder::Kill(target) if !hostiles.contains(target)))
+        .map(|(index, _)| index)
+        .collect();
+    for index in finished {
+        let squad = &mut military.squads[index];
+        squad.order = SquadOrder::None;
+        logger.info(format!("{} 完成了击杀命令", squad.name));
+    }
+
+    let enemies: Vec<(Entity, (i32, i32))> = hostiles.iter().map(|(entity, pos)| (entity, (pos.x, pos.y))).collect();
+    let safe_point = map_registry.fortress_stockpile(active_local.coord);
+    let alert = military.alert == AlertLevel::Alert;
+    let mut reached_patrol_point = vec![false; military.squads.len()];
+
+    // 警戒时没有任务的平民和队员、撤退的小队进洞区避难
+    let seekers: Vec<RefugeSeeker> = dwarves
+        .iter()
+        .filter(|(_, dwarf, _, _, _, _, in_tantrum)| {
+            !in_tantrum
+                && match military.squad_of(&dwarf.name).map(|index| &military.squads[index]) {
+                    None => alert,
+                    Some(squad) => {
+                        squad.order == SquadOrder::Retreat
+                            || (alert && squad.order == SquadOrder::None && !squad.training.on_duty(game_time.hour))
+                    }
+                }
+        })
+        .map(|(entity, _, pos, work_state, _, _, _)| {
+            let post = match &work_state.current_task {
+                Some(Task::Stand(post)) => Some((post.x, post.y)),
+                _ => None,
+            };
+            RefugeSeeker {
+                entity,
+                here: (pos.x, pos.y),
+                post,
+            }
+        })
+        .collect();
+    let refuge_posts = map_registry
+        .refuge_tiles(active_local.coord)
+        .map(|refuge| assign_refuge_posts(refuge, &seekers, &region_map))
+        .unwrap_or_default();
+
+    for (entity, dwarf, pos, mut work_state, mut equipment, follow, in_tantrum) in dwarves.iter_mut() {
+        // 发脾气的矮人不听指挥
+        if in_tantrum {
+            continue;
+        }
+        let here = (pos.x, pos.y);
+        let squad_index = military.squad_of(&dwarf.name);
+
+        let duty = squad_index.and_then(|index| {
+            let squad = &military.squads[index];
+            for item in issue_uniform(&mut equipment, squad.uniform, &mut inventory) {
+                logger.info(format!("{} 领取了{}", dwarf.name, item.name()));
+            }
+            match &squad.order {
+                SquadOrder::Kill(target) => Some(Task::Attack(*target)),
+                // 撤退到避难洞区，没有避难洞区时回仓库
+                SquadOrder::Retreat => refuge_posts
+                    .get(&entity)
+                    .copied()
+                    .or(safe_point)
+                    .map(|(x, y)| Task::Stand(GridPosition { x, y })),
+                order => {
+                    let post = match order {
+                        SquadOrder::Station(point) => Some(*point),
+                        SquadOrder::Patrol => squad.patrol_point(),
+                        // 没有命令时按训练安排到训练场（要塞仓库）操练
+                        _ if squad.training.on_duty(game_time.hour) => safe_point,
+                        _ => None,
+                    }?;
+                    if *order == SquadOrder::Patrol && distance(here, post) <= POST_ARRIVAL_RADIUS {
+                        reached_patrol_point[index] = true;
+                    }
+                    // 值勤时主动迎击附近的敌人
+                    let nearby_enemy = enemies
+                        .iter()
+                        .filter(|(_, enemy_pos)| distance(here, *enemy_pos) <= ENGAGE_RADIUS)
+                        .min_by_key(|(_, enemy_pos)| distance(here, *enemy_pos));
+                    Some(match nearby_enemy {
+                        Some((enemy, _)) => Task::Attack(*enemy),
+                        None => Task::Stand(GridPosition { x: post.0, y: post.1 }),
+                    })
+                }
+            }
+        });
+        let task = duty.or_else(|| {
+            refuge_posts
+                .get(&entity)
+                .filter(|_| alert)
+                .map(|(x, y)| Task::Stand(GridPosition { x: *x, y: *y }))
+        });
+
+        // 目标位置（敌人的当前位置或岗位），不可达的命令不执行
+        let goal = match &task {
+            Some(Task::Stand(post)) => Some((post.x, post.y)),
+            Some(Task::Attack(target)) => enemies.iter().find(|(enemy, _)| enemy == target).map(|(_, p)| *p),
+            _ => None,
+        }
+        .filter(|goal| region_map.connected(here, *goal));
+
+        let (Some(task), Some(goal)) = (task, goal) else {
+            // 命令解除或警报结束后回去干活
+            if matches!(work_state.current_task, Some(Task::Stand(_)) | Some(Task::Attack(_))) {
+                commands.entity(entity).remove::<FollowFlowField>();
+                reset_task(&mut work_state, Task::Idle);
+            }
+            continue;
+        };
+
+        if work_state.current_task.as_ref() != Some(&task) {
+            reset_task(&mut work_state, task.clone());
+        }
+        // 岗位是固定的目的地，沿共享流场前往；追击会移动的敌人由工作系统按寻路路径进行
+        let flow_goal = match task {
+            Task::Stand(_) if here != goal => Some(goal),
+            _ => None,
+        };
+        match flow_goal {
+            Some(goal) if follow.is_none_or(|follow| follow.goal != goal) => {
+                commands.entity(entity).insert(FollowFlowField { goal });
+            }
+            Some(_) => {}
+            None if follow.is_some() => {
+                commands.entity(entity).remove::<FollowFlowField>();
+            }
+            None => {}
+        }
+    }
+
+    for (index, reached) in reached_patrol_point.into_iter().enumerate() {
+        if reached {
+            military.squads[index].advance_patrol();
+        }
+    }
+}
+
+/// 需要进洞区避难的矮人
+struct RefugeSeeker {
+    entity: Entity,
+    here: (i32, i32),
+    /// 当前待命的位置
+    post: Option<(i32, i32)>,
+}
+
+/// 在避难洞区里给每名避难者分一块可达的空地：已经站在或正前往洞区某格的保留原位，
+/// 其余的分到离自己最近的空地；洞区站满后剩下的人没有位置
+fn assign_refuge_posts(
+    refuge: &HashSet<(i32, i32)>,
+    seekers: &[RefugeSeeker],
+    region_map: &RegionMap,
+) -> HashMap<Entity, (i32, i32)> {
+    let mut posts = HashMap::new();
+    let mut taken = HashSet::new();
+    for seeker in seekers {
+        if let Some(post) = seeker.post.filter(|post| refuge.contains(post) && taken.insert(*post)) {
+            posts.insert(seeker.entity, post);
+        }
+    }
+    for seeker in seekers {
+        if posts.contains_key(&seeker.entity) {
+            continue;
+        }
+        let free = refuge
+            .iter()
+            .filter(|tile| !taken.contains(*tile) && region_map.connected(seeker.here, **tile))
+            .min_by_key(|tile| (distance(seeker.here, **tile), **tile))
+            .copied();
+        if let Some(tile) = free {
+            taken.insert(tile);
+            posts.insert(seeker.entity, tile);
+        }
+    }
+    posts
+}
+
+fn reset_task(work_state: &mut WorkState, task: Task) {
+    work_state.current_task = Some(task);
+    work_state.work_progress = 0.0;
+    work_state.cached_path.clear();
+    work_state.path_index = 0;
+    work_state.task_duration = 0.0;
+}
+
+/// 按制服要求用库存金属为队员打造缺少的武器和护甲，返回新领取的装备
+fn issue_uniform(equipment: &mut Equipment, uniform: Uniform, inventory: &mut GlobalInventory) -> Vec<Item> {
+    let mut issued = Vec::new();
+    if uniform.needs_weapon() && !equipment.holds_weapon() && inventory.metal >= WEAPON_METAL_COST {
+        inventory.metal -= WEAPON_METAL_COST;
+        let weapon = Item::new(ItemKind::Weapon, ItemMaterial::Iron);
+        if let Some(old) = equipment.hands.replace(weapon) {
+            super::return_to_stock(old, inventory);
+        }
+        issued.push(weapon);
+    }
+    let wears_armour = matches!(equipment.body, Some(item) if item.kind == ItemKind::Armour);
+    if uniform.needs_armour() && !wears_armour && inventory.metal >= ARMOUR_METAL_COST {
+        inventory.metal -= ARMOUR_METAL_COST;
+        let armour = Item::new(ItemKind::Armour, ItemMaterial::Iron);
+        equipment.body = Some(armour);
+        issued.push(armour);
+    }
+    issued
+}
+
+/// 训练：每个游戏小时，在训练场操练的队员获得战斗经验
+#[allow(clippy::type_complexity)]
+pub fn military_training_system(
+    game_time: Res<GameTime>,
+    military: Res<Military>,
+    mut dwarves: Query<(&Dwarf, &GridPosition, &WorkState, &mut Skills, &mut Thoughts)>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut last_hour: Local<Option<(u32, u32)>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let now = (game_time.day, game_time.hour);
+    if *last_hour == Some(now) {
+        return;
+    }
+    let first_run = last_hour.is_none();
+    *last_hour = Some(now);
+    let Some(training_ground) = map_registry.fortress_stockpile(active_local.coord) else {
+        return;
+    };
+    if first_run {
+        return;
+    }
+
+    for (dwarf, pos, work_state, mut skills, mut thoughts) in dwarves.iter_mut() {
+        let Some(squad) = military.squad_of(&dwarf.name).map(|index| &military.squads[index]) else {
+            continue;
+        };
+        let training = squad.order == SquadOrder::None && squad.training.on_duty(game_time.hour);
+        let on_ground = matches!(&work_state.current_task, Some(Task::Stand(post)) if (post.x, post.y) == training_ground)
+            && distance((pos.x, pos.y), training_ground) <= POST_ARRIVAL_RADIUS + 1;
+        if training && on_ground {
+            super::gain_experience(dwarf, &mut skills, &mut thoughts, SkillKind::Fighting, &mut logger);
+        }
+    }
+}
+
+/// 战斗：执行攻击任务的矮人近身攻击目标，武器和战斗技能决定伤害，杀死的野兽屠宰成肉入库
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn combat_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut dwarves: Query<
+        (Entity, &Dwarf, &GridPosition, &WorkState, &Equipment, &mut Skills, &mut Thoughts),
+        Without<Hostile>,
+    >,
+    mut hostiles: Query<(&mut Hostile, &GridPosition), Without<Dwarf>>,
+    mut cooldowns: Local<HashMap<Entity, f32>>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0.0001 {
+        return;
+    }
+
+    cooldowns.retain(|entity, remaining| {
+        *remaining -= delta;
+        *remaining > 0.0 && dwarves.contains(*entity)
+    });
+
+    // 矮人出手
+    for (entity, dwarf, pos, work_state, equipment, mut skills, mut thoughts) in dwarves.iter_mut() {
+        let Some(Task::Attack(target)) = work_state.current_task else {
+            continue;
+        };
+        if cooldowns.contains_key(&entity) {
+            continue;
+        }
+        let Ok((mut hostile, hostile_pos)) = hostiles.get_mut(target) else {
+            continue;
+        };
+        if hostile.health <= 0.0 || !in_reach((pos.x, pos.y), (hostile_pos.x, hostile_pos.y)) {
+            continue;
+        }
+
+        let damage = DWARF_BASE_DAMAGE * equipment.attack_multiplier() * skills.work_speed(SkillKind::Fighting);
+        hostile.health -= damage;
+        cooldowns.insert(entity, DWARF_ATTACK_INTERVAL);
+        if hostile.health <= 0.0 {
+            commands.entity(target).despawn();
+            logger.info(format!("{} 杀死了{}", dwarf.name, hostile.kind.label()));
+            // 猎物屠宰后直接入库
+            inventory.food.add(FoodItem::Raw(FoodKind::Meat), hostile.kind.meat());
+            chronicle.record(
+                &game_time,
+                ChronicleKind::Battle,
+                format!("{} 在要塞附近杀死了{}", dwarf.name, hostile.kind.label()),
+            );
+            super::gain_experience(dwarf, &mut skills, &mut thoughts, SkillKind::Fighting, &mut logger);
+        }
+    }
+}
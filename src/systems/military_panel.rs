@@ -0,0 +1,467 @@
+use crate::components::*;
+use crate::resources::*;
+use crate::ui_framework::*;
+use crate::world::*;
+use bevy::prelude::*;
+
+/// 普通按钮颜色
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.3, 0.4);
+/// 正在等待点选地图的按钮颜色
+const PENDING_COLOR: Color = Color::srgb(0.6, 0.5, 0.15);
+/// 警戒按钮颜色
+const ALERT_COLOR: Color = Color::srgb(0.6, 0.15, 0.15);
+
+/// 军事面板中小队列表的容器
+#[derive(Component)]
+pub struct MilitaryPanelRows;
+
+/// 军事面板按钮
+#[derive(Component, Clone, Copy)]
+pub enum MilitaryButton {
+    /// 切换要塞警戒等级
+    ToggleAlert,
+    /// 新建小队
+    AddSquad,
+    /// 切换小队制服
+    CycleUniform(usize),
+    /// 切换训练安排
+    CycleTraining(usize),
+    /// 把选中的矮人编入小队
+    Enlist(usize),
+    /// 把选中的矮人移出小队
+    Discharge(usize),
+    /// 在地图上点选驻守位置、巡逻点或击杀目标
+    Place(usize, SquadPlacementKind),
+    /// 开始巡逻
+    Patrol(usize),
+    /// 清除巡逻路线
+    ClearPatrol(usize),
+    /// 撤退
+    Retreat(usize),
+    /// 取消命令
+    CancelOrder(usize),
+}
+
+/// 设置军事面板（初始隐藏，K键切换）
+pub fn setup_military_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<UITheme>,
+) {
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let mut builder = PanelBuilder::new(commands.reborrow(), font.clone(), theme.clone());
+
+    // 创建军事面板（左侧中间）
+    let military_config = PanelConfig {
+        anchor: PanelAnchor::MiddleLeft,
+        offset: Vec2::new(15.0, 0.0),
+        min_width: 520.0,
+        min_height: 160.0,
+        background_color: Color::srgba(0.1, 0.06, 0.06, 0.94),
+        border_color: Some(Color::srgba(0.8, 0.3, 0.3, 0.7)),
+        padding: 12.0,
+    };
+
+    let military_panel = builder.create_hidden_panel("military_panel", military_config, MilitaryPanel);
+    builder.add_title(military_panel, "◆ 军事 ◆");
+    builder.add_small_text(
+        military_panel,
+        "编入/移出作用于当前选中的矮人；驻守、巡逻点和击杀需要再点击地图",
+    );
+
+    let rows = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::top(Val::Px(8.0)),
+                ..default()
+            },
+            MilitaryPanelRows,
+        ))
+        .id();
+    commands.entity(military_panel).add_child(rows);
+}
+
+/// 切换军事面板显示/隐藏（K键）
+pub fn toggle_military_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<(&mut UIPanel, &mut Node)>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyK) {
+        toggle_panel_visibility(&mut panel_query, "military_panel");
+    }
+}
+
+/// 处理军事面板按钮点击
+#[allow(clippy::too_many_arguments)]
+pub fn military_button_system(
+    interaction_query: Query<(&Interaction, &MilitaryButton), Changed<Interaction>>,
+    selected: Res<SelectedDwarf>,
+    dwarves: Query<(&Dwarf, &Age)>,
+    mut military: ResMut<Military>,
+    mut placement: ResMut<SquadPlacement>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let selected_dwarf = selected.entity.and_then(|entity| dwarves.get(entity).ok());
+        match *button {
+            MilitaryButton::ToggleAlert => {
+                military.alert = match military.alert {
+                    AlertLevel::Normal => AlertLevel::Alert,
+                    AlertLevel::Alert => AlertLevel::Normal,
+                };
+                match military.alert {
+                    AlertLevel::Alert if map_registry.refuge_tiles(active_local.coord).is_none() => logger.warning(
+                        "要塞进入警戒，但还没有指定避难洞区，平民无处可躲（在区域面板中把一个洞区设为避难所）".to_string(),
+                    ),
+                    AlertLevel::Alert => logger.warning("要塞进入警戒，平民撤进避难洞区".to_string()),
+                    AlertLevel::Normal => logger.info("警戒解除，平民恢复工作".to_string()),
+                }
+            }
+            MilitaryButton::AddSquad => match military.add_squad() {
+                Some(index) => logger.info(format!("组建了{}", military.squads[index].name)),
+                None => logger.warning(format!("最多只能组建 {} 支小队", MAX_SQUADS)),
+            },
+            MilitaryButton::CycleUniform(index) => {
+                if let Some(squad) = military.squads.get_mut(index) {
+                    squad.uniform = squad.uniform.next();
+                    logger.info(format!("{} 的制服改为{}", squad.name, squad.uniform.label()));
+                }
+            }
+            MilitaryButton::CycleTraining(index) => {
+                if let Some(squad) = military.squads.get_mut(index) {
+                    squad.training = squad.training.next();
+                    logger.info(format!("{} 改为{}", squad.name, squad.training.label()));
+                }
+            }
+            MilitaryButton::Enlist(index) => {
+                let Some((dwarf, age)) = selected_dwarf else {
+                    logger.warning("请先选中一名矮人".to_string());
+                    continue;
+                };
+                if age.is_child() {
+                    logger.warning(format!("{} 还是个孩子，不能参军", dwarf.name));
+                    continue;
+                }
+                military.enlist(index, &dwarf.name);
+                logger.info(format!("{} 加入了{}", dwarf.name, military.squads[index].name));
+            }
+            MilitaryButton::Discharge(index) => {
+                let Some((dwarf, _)) = selected_dwarf else {
+                    logger.warning("请先选中一名矮人".to_string());
+                    continue;
+                };
+                if military.squad_of(&dwarf.name) == Some(index) && military.discharge(&dwarf.name) {
+                    logger.info(format!("{} 离开了{}", dwarf.name, military.squads[index].name));
+                }
+            }
+            MilitaryButton::Place(index, kind) => {
+                // 再次点击同一按钮取消点选
+                placement.pending = if placement.pending == Some((index, kind)) {
+                    None
+                } else {
+                    Some((index, kind))
+                };
+            }
+            MilitaryButton::Patrol(index) => {
+                let Some(squad) = military.squads.get_mut(index) else {
+                    continue;
+                };
+                if squad.patrol_route.is_empty() {
+                    logger.warning(format!("{} 还没有巡逻点", squad.name));
+                } else {
+                    squad.order = SquadOrder::Patrol;
+                    squad.patrol_index = 0;
+                    logger.info(format!("{} 开始巡逻", squad.name));
+                }
+            }
+            MilitaryButton::ClearPatrol(index) => {
+                if let Some(squad) = military.squads.get_mut(index) {
+                    squad.patrol_route.clear();
+                    squad.patrol_index = 0;
+                    if squad.order == SquadOrder::Patrol {
+                        squad.order = SquadOrder::None;
+                    }
+                }
+            }
+            MilitaryButton::Retreat(index) => {
+                if let Some(squad) = military.squads.get_mut(index) {
+                    squad.order = SquadOrder::Retreat;
+                    logger.info(format!("{} 撤退！", squad.name));
+                }
+            }
+            MilitaryButton::CancelOrder(index) => {
+                if let Some(squad) = military.squads.get_mut(index) {
+                    squad.order = SquadOrder::None;
+                    logger.info(format!("{} 的命令已取消", squad.name));
+                }
+            }
+        }
+    }
+}
+
+/// 在地图上点选驻守位置、巡逻点或击杀目标。
+/// 需在矮人选择系统之前运行，点选消耗掉这次左键点击
+#[allow(clippy::too_many_arguments)]
+pub fn squad_placement_system(
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    hostiles: Query<(Entity, &Hostile, &GridPosition)>,
+    mut placement: ResMut<SquadPlacement>,
+    mut military: ResMut<Military>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let Some((index, kind)) = placement.pending else {
+        return;
+    };
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // 点在界面按钮上的不算
+    if interactions.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+    let Ok(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
+        return;
+    };
+
+    // 转换为网格坐标
+    let grid_x = ((world_position.x + (WORLD_WIDTH as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+    let grid_y = ((world_position.y + (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+    if !(0..WORLD_WIDTH).contains(&grid_x) || !(0..WORLD_HEIGHT).contains(&grid_y) {
+        return;
+    }
+    mouse_button.clear_just_pressed(MouseButton::Left);
+
+    let Some(squad) = military.squads.get_mut(index) else {
+        placement.pending = None;
+        return;
+    };
+    match kind {
+        SquadPlacementKind::Station => {
+            squad.order = SquadOrder::Station((grid_x, grid_y));
+            placement.pending = None;
+            logger.info(format!("{} 前往 ({}, {}) 驻守", squad.name, grid_x, grid_y));
+        }
+        SquadPlacementKind::PatrolPoint => {
+            // 可以连续点选多个巡逻点，再次点击按钮结束
+            squad.patrol_route.push((grid_x, grid_y));
+        }
+        SquadPlacementKind::KillTarget => {
+            let target = hostiles
+                .iter()
+                .filter(|(_, _, pos)| (pos.x - grid_x).abs() <= 1 && (pos.y - grid_y).abs() <= 1)
+                .min_by_key(|(_, _, pos)| (pos.x - grid_x).abs() + (pos.y - grid_y).abs());
+            match target {
+                Some((entity, hostile, _)) => {
+                    squad.order = SquadOrder::Kill(entity);
+                    placement.pending = None;
+                    logger.info(format!("{} 奉命击杀{}", squad.name, hostile.kind.label()));
+                }
+                None => logger.warning("那里没有敌人".to_string()),
+            }
+        }
+    }
+}
+
+/// 面板可见时，在军事编制、点选状态、选中矮人或敌人数量变化后重建小队列表
+#[allow(clippy::too_many_arguments)]
+pub fn military_panel_refresh_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    panel_query: Query<Ref<UIPanel>, With<MilitaryPanel>>,
+    rows_query: Query<Entity, With<MilitaryPanelRows>>,
+    military: Res<Military>,
+    placement: Res<SquadPlacement>,
+    selected: Res<SelectedDwarf>,
+    hostiles: Query<(), With<Hostile>>,
+    mut last_hostiles: Local<usize>,
+) {
+    let Ok(panel) = panel_query.single() else {
+        return;
+    };
+    if panel.state != PanelState::Visible {
+        return;
+    }
+    let hostile_count = hostiles.iter().count();
+    if !panel.is_changed()
+        && !military.is_changed()
+        && !placement.is_changed()
+        && !selected.is_changed()
+        && hostile_count == *last_hostiles
+    {
+        return;
+    }
+    *last_hostiles = hostile_count;
+
+    let Ok(rows) = rows_query.single() else {
+        return;
+    };
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+
+    commands.entity(rows).despawn_related::<Children>();
+    commands.entity(rows).with_children(|parent| {
+        spawn_row(parent, |row| {
+            let alert_color = match military.alert {
+                AlertLevel::Normal => BUTTON_COLOR,
+                AlertLevel::Alert => ALERT_COLOR,
+            };
+            spawn_button(
+                row,
+                &font,
+                &format!("警戒等级: {}", military.alert.label()),
+                alert_color,
+                MilitaryButton::ToggleAlert,
+            );
+            spawn_button(row, &font, "新建小队", BUTTON_COLOR, MilitaryButton::AddSquad);
+            spawn_label(row, &font, &format!("地图上的敌人: {}", hostile_count));
+        });
+        if let Some((_, kind)) = placement.pending {
+            spawn_row(parent, |row| spawn_label(row, &font, kind.prompt()));
+        }
+
+        for (index, squad) in military.squads.iter().enumerate() {
+            let button_color = |kind: SquadPlacementKind| {
+                if placement.pending == Some((index, kind)) {
+                    PENDING_COLOR
+                } else {
+                    BUTTON_COLOR
+                }
+            };
+            spawn_row(parent, |row| {
+                spawn_label(
+                    row,
+                    &font,
+                    &format!("━━ {}（{} 人）命令: {}", squad.name, squad.members.len(), squad.order.describe()),
+                );
+            });
+            spawn_row(parent, |row| {
+                let members = if squad.members.is_empty() {
+                    "暂无队员".to_string()
+                } else {
+                    squad.members.join("、")
+                };
+                spawn_label(row, &font, &members);
+            });
+            spawn_row(parent, |row| {
+                spawn_button(
+                    row,
+                    &font,
+                    &format!("制服: {}", squad.uniform.label()),
+                    BUTTON_COLOR,
+                    MilitaryButton::CycleUniform(index),
+                );
+                spawn_button(row, &font, squad.training.label(), BUTTON_COLOR, MilitaryButton::CycleTraining(index));
+                spawn_button(row, &font, "编入选中", BUTTON_COLOR, MilitaryButton::Enlist(index));
+                spawn_button(row, &font, "移出选中", BUTTON_COLOR, MilitaryButton::Discharge(index));
+            });
+            spawn_row(parent, |row| {
+                spawn_button(
+                    row,
+                    &font,
+                    "驻守",
+                    button_color(SquadPlacementKind::Station),
+                    MilitaryButton::Place(index, SquadPlacementKind::Station),
+                );
+                spawn_button(
+                    row,
+                    &font,
+                    &format!("巡逻点({})", squad.patrol_route.len()),
+                    button_color(SquadPlacementKind::PatrolPoint),
+                    MilitaryButton::Place(index, SquadPlacementKind::PatrolPoint),
+                );
+                spawn_button(row, &font, "巡逻", BUTTON_COLOR, MilitaryButton::Patrol(index));
+                spawn_button(row, &font, "清除路线", BUTTON_COLOR, MilitaryButton::ClearPatrol(index));
+            });
+            spawn_row(parent, |row| {
+                spawn_button(
+                    row,
+                    &font,
+                    "击杀",
+                    button_color(SquadPlacementKind::KillTarget),
+                    MilitaryButton::Place(index, SquadPlacementKind::KillTarget),
+                );
+                spawn_button(row, &font, "撤退", BUTTON_COLOR, MilitaryButton::Retreat(index));
+                spawn_button(row, &font, "取消命令", BUTTON_COLOR, MilitaryButton::CancelOrder(index));
+            });
+        }
+    });
+}
+
+fn spawn_row(parent: &mut ChildSpawnerCommands, build: impl FnOnce(&mut ChildSpawnerCommands)) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            margin: UiRect::top(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(build);
+}
+
+fn spawn_label(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, text: &str) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font: font.clone(),
+            font_size: 15.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.95, 0.9, 0.75)),
+        Node {
+            margin: UiRect::horizontal(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    label: &str,
+    color: Color,
+    button: MilitaryButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                margin: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(color),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+        });
+}
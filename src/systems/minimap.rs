@@ -67,6 +67,8 @@ pub fn setup_minimap(
             TerrainType::Stone => Color::srgb(0.5, 0.5, 0.5),
             TerrainType::Water => Color::srgb(0.2, 0.4, 0.8),
             TerrainType::Mountain => Color::srgb(0.4, 0.4, 0.4),
+            TerrainType::Dirt => Color::srgb(0.45, 0.35, 0.22),
+            TerrainType::Sapling => Color::srgb(0.3, 0.6, 0.25),
         };
 
         let terrain_pixel = commands
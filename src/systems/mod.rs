@@ -2,6 +2,10 @@
 mod movement;
 pub use movement::*;
 
+// 导航网格更新系统
+mod navigation;
+pub use navigation::*;
+
 // 工作和资源系统
 mod work;
 pub use work::*;
@@ -53,3 +57,79 @@ pub use debug_panel::*;
 // 通知消息系统
 mod notification;
 pub use notification::*;
+
+// 劳动管理面板
+mod labour_panel;
+pub use labour_panel::*;
+
+// 饥饿、口渴、天气、心情与发脾气
+mod mood;
+pub use mood::*;
+
+// 社会关系：相处、聊天、恋爱与婚姻
+mod social;
+pub use social::*;
+
+// 人口：移民潮、出生、成长与死亡
+mod population;
+pub use population::*;
+
+// 装备：取用工具与搬运货物
+mod equipment;
+pub use equipment::*;
+
+// 军事：小队命令、训练、战斗与袭击
+mod military;
+pub use military::*;
+
+// 军事面板
+mod military_panel;
+pub use military_panel::*;
+
+// 区域：涂抹与地图覆盖层
+mod zones;
+pub use zones::*;
+
+// 区域面板
+mod zone_panel;
+pub use zone_panel::*;
+
+// 房间：家具与房间检测
+mod rooms;
+pub use rooms::*;
+
+// 睡眠：疲劳、作息班次与睡眠质量
+mod sleep;
+pub use sleep::*;
+
+// 食物：厨房烹饪、酒坊酿酒与食物腐烂
+mod food;
+pub use food::*;
+
+// 钓鱼：鱼群、岸边垂钓与渔获
+mod fishing;
+pub use fishing::*;
+
+// 植被：树苗播种、青草再生与踩出的土路
+mod vegetation;
+pub use vegetation::*;
+
+// 建筑结构：墙、地板、道路、桥和门
+mod constructions;
+pub use constructions::*;
+
+// 建造面板
+mod build_panel;
+pub use build_panel::*;
+
+// 传说浏览系统
+mod legends_view;
+pub use legends_view::*;
+
+// 创建世界系统
+mod world_creation;
+pub use world_creation::*;
+
+// 出发准备系统
+mod embark;
+pub use embark::*;
@@ -0,0 +1,287 @@
+use crate::components::*;
+use crate::connectivity::RegionMap;
+use crate::resources::*;
+use crate::world::*;
+use bevy::prelude::*;
+use rand::Rng;
+
+/// 饥饿度每秒增加量（约一个游戏日从饱腹到想吃饭）
+const HUNGER_PER_SECOND: f32 = 0.25;
+/// 饥饿度达到该值时去吃饭
+const EAT_THRESHOLD: f32 = 70.0;
+/// 一份饭菜降低的饥饿度
+const MEAL_NOURISHMENT: f32 = 60.0;
+/// 生吃一份原料降低的饥饿度
+const RAW_NOURISHMENT: f32 = 40.0;
+/// 饥饿度达到该值且没有食物时产生"饿着肚子"的想法
+const STARVING_THRESHOLD: f32 = 90.0;
+/// 口渴度每秒增加量
+const THIRST_PER_SECOND: f32 = 0.3;
+/// 口渴度达到该值时去喝酒
+const DRINK_THRESHOLD: f32 = 70.0;
+/// 喝一份酒或一口水降低的口渴度
+const DRINK_QUENCH: f32 = 60.0;
+/// 心情低于阈值时每秒发脾气的概率
+const TANTRUM_CHANCE_PER_SECOND: f32 = 0.05;
+/// 发脾气持续时间（秒）
+const TANTRUM_DURATION: f32 = 20.0;
+/// 多少格以内的矮人会目睹发脾气
+const TANTRUM_WITNESS_RADIUS: i32 = 5;
+
+/// 从仓库里挑一份最好的食物：先吃饭菜（从好到差），没有饭菜才生吃原料
+fn take_best_food(food: &mut FoodStock) -> Option<FoodItem> {
+    MealKind::ALL
+        .iter()
+        .find_map(|meal| food.take_where(|item| matches!(item, FoodItem::Meal(kind, _) if kind == *meal)))
+        .or_else(|| food.take_where(|item| matches!(item, FoodItem::Raw(_))))
+}
+
+/// 饥饿与进食：饿了就从仓库里吃一份食物，饭菜越好心情越好，生吃原料会不开心，
+/// 吃到喜爱的食物（饭菜看主料）心情更好；在有桌子的餐厅里吃饭也会有好心情，餐厅足够漂亮时还会赞赏一番
+pub fn hunger_system(
+    time: Res<Time>,
+    mut dwarves: Query<(&mut Dwarf, &Personality, &mut Thoughts, &GridPosition)>,
+    mut inventory: ResMut<GlobalInventory>,
+    rooms: Res<Rooms>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+
+    for (mut dwarf, personality, mut thoughts, pos) in dwarves.iter_mut() {
+        dwarf.hunger = (dwarf.hunger + HUNGER_PER_SECOND * time.delta_secs()).min(100.0);
+        if dwarf.hunger < EAT_THRESHOLD {
+            continue;
+        }
+
+        let Some(meal) = take_best_food(&mut inventory.food) else {
+            if dwarf.hunger >= STARVING_THRESHOLD {
+                thoughts.add(ThoughtKind::Hungry);
+            }
+            continue;
+        };
+        match meal {
+            FoodItem::Meal(kind, _) => {
+                dwarf.hunger = (dwarf.hunger - MEAL_NOURISHMENT).max(0.0);
+                thoughts.add(kind.thought());
+            }
+            _ => {
+                dwarf.hunger = (dwarf.hunger - RAW_NOURISHMENT).max(0.0);
+                thoughts.add(ThoughtKind::AteRawFood);
+            }
+        }
+        if meal.ingredient() == Some(personality.favourite_food) {
+            thoughts.add(ThoughtKind::AteFavouriteFood);
+        }
+        let dining_hall = rooms
+            .room_at((pos.x, pos.y))
+            .filter(|room| room.tables > 0 && room.usage == Some(RoomUse::DiningHall));
+        if let Some(hall) = dining_hall {
+            thoughts.add(ThoughtKind::AteAtTable);
+            if hall.quality >= GOOD_ROOM_QUALITY {
+                thoughts.add(ThoughtKind::AdmiredBuilding);
+            }
+        }
+    }
+}
+
+/// 口渴与饮酒：渴了就从仓库里喝一份酒，心情变好；没有酒只能喝水，心情变差
+pub fn thirst_system(
+    time: Res<Time>,
+    mut dwarves: Query<(&mut Dwarf, &mut Thoughts)>,
+    mut inventory: ResMut<GlobalInventory>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+
+    for (mut dwarf, mut thoughts) in dwarves.iter_mut() {
+        dwarf.thirst = (dwarf.thirst + THIRST_PER_SECOND * time.delta_secs()).min(100.0);
+        if dwarf.thirst < DRINK_THRESHOLD {
+            continue;
+        }
+        dwarf.thirst = (dwarf.thirst - DRINK_QUENCH).max(0.0);
+        if inventory.food.take_where(|item| item.category() == FoodCategory::Drink).is_some() {
+            thoughts.add(ThoughtKind::HadDrink);
+        } else {
+            thoughts.add(ThoughtKind::DrankWater);
+        }
+    }
+}
+
+/// 天气变化：每个游戏小时检查一次，到期后随机变天
+pub fn weather_system(
+    game_time: Res<GameTime>,
+    mut weather: ResMut<CurrentWeather>,
+    mut last_hour: Local<Option<(u32, u32)>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let now = (game_time.day, game_time.hour);
+    if *last_hour == Some(now) {
+        return;
+    }
+    let first_run = last_hour.is_none();
+    *last_hour = Some(now);
+    if first_run {
+        return;
+    }
+
+    weather.hours_left = weather.hours_left.saturating_sub(1);
+    if weather.hours_left > 0 {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    // 晴:阴:雨:雪 = 4:3:2:1
+    let roll = rng.gen_range(0..10);
+    let next = match roll {
+        0..=3 => Weather::Sunny,
+        4..=6 => Weather::Cloudy,
+        7..=8 => Weather::Rain,
+        _ => Weather::Snow,
+    };
+    weather.hours_left = rng.gen_range(3..=12);
+    if next != weather.weather {
+        weather.weather = next;
+        logger.info(format!("天气转为{}", next.label()));
+    }
+}
+
+/// 天气想法：每个游戏小时，喜欢当前天气的矮人心情变好，其余矮人被雨雪折腾
+pub fn weather_thought_system(
+    game_time: Res<GameTime>,
+    weather: Res<CurrentWeather>,
+    mut dwarves: Query<(&Personality, &mut Thoughts)>,
+    mut last_hour: Local<Option<(u32, u32)>>,
+) {
+    let now = (game_time.day, game_time.hour);
+    if *last_hour == Some(now) {
+        return;
+    }
+    *last_hour = Some(now);
+
+    for (personality, mut thoughts) in dwarves.iter_mut() {
+        if weather.weather == personality.favourite_weather {
+            thoughts.add(ThoughtKind::NiceWeather);
+        } else if weather.weather.is_harsh() {
+            thoughts.add(ThoughtKind::CaughtInBadWeather);
+        }
+    }
+}
+
+/// 心情系统：想法随时间淡化，心情由当前想法和性格计算
+pub fn mood_system(time: Res<Time>, mut dwarves: Query<(&mut Dwarf, &Personality, &mut Thoughts)>) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+
+    for (mut dwarf, personality, mut thoughts) in dwarves.iter_mut() {
+        thoughts.tick(time.delta_secs());
+        dwarf.happiness = thoughts.mood(personality);
+    }
+}
+
+/// 发脾气：心情跌破阈值的矮人有概率大发脾气，砸坏库存、拒绝工作并四处乱走；
+/// 附近的矮人会因此心情变差。需在工作系统之前运行，以覆盖工作决策
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn tantrum_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut dwarves: Query<(
+        Entity,
+        &Dwarf,
+        &Personality,
+        &mut Thoughts,
+        &GridPosition,
+        &mut WorkState,
+        Option<&mut Tantrum>,
+    )>,
+    region_map: Res<RegionMap>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut outbursts: Vec<(Entity, (i32, i32))> = Vec::new();
+
+    for (entity, dwarf, personality, mut thoughts, pos, mut work_state, tantrum) in dwarves.iter_mut() {
+        if let Some(mut tantrum) = tantrum {
+            tantrum.remaining -= time.delta_secs();
+            if tantrum.remaining <= 0.0 {
+                commands.entity(entity).remove::<Tantrum>();
+                thoughts.add(ThoughtKind::VentedAnger);
+                work_state.current_task = Some(Task::Idle);
+                work_state.cached_path.clear();
+                work_state.path_index = 0;
+                work_state.task_duration = 0.0;
+                logger.info(format!("{} 终于平静了下来", dwarf.name));
+                continue;
+            }
+            // 发脾气期间拒绝一切工作和命令，只在附近乱走
+            if !matches!(work_state.current_task, Some(Task::Wandering(_))) {
+                let target_x = (pos.x + rng.gen_range(-4..=4)).clamp(0, WORLD_WIDTH - 1);
+                let target_y = (pos.y + rng.gen_range(-4..=4)).clamp(0, WORLD_HEIGHT - 1);
+                work_state.current_task = if region_map.connected((pos.x, pos.y), (target_x, target_y)) {
+                    Some(Task::Wandering(GridPosition {
+                        x: target_x,
+                        y: target_y,
+                    }))
+                } else {
+                    Some(Task::Wandering(pos.clone()))
+                };
+                work_state.work_progress = 0.0;
+                work_state.cached_path.clear();
+                work_state.path_index = 0;
+                work_state.task_duration = 0.0;
+            }
+            continue;
+        }
+
+        if dwarf.happiness >= personality.tantrum_threshold()
+            || !rng.gen_bool((TANTRUM_CHANCE_PER_SECOND * time.delta_secs()).clamp(0.0, 1.0) as f64)
+        {
+            continue;
+        }
+
+        commands.entity(entity).insert(Tantrum {
+            remaining: TANTRUM_DURATION,
+        });
+        work_state.current_task = Some(Task::Idle);
+        outbursts.push((entity, (pos.x, pos.y)));
+
+        // 砸坏一些库存
+        let smashed = rng.gen_range(1..=5);
+        let (label, stock) = match rng.gen_range(0..3) {
+            0 => ("石头", &mut inventory.stone),
+            1 => ("木材", &mut inventory.wood),
+            _ => ("工具", &mut inventory.tools),
+        };
+        let smashed = smashed.min(*stock);
+        *stock -= smashed;
+        if smashed > 0 {
+            logger.warning(format!("{} 大发脾气，砸坏了 {} 份{}！", dwarf.name, smashed, label));
+        } else {
+            logger.warning(format!("{} 大发脾气！", dwarf.name));
+        }
+        chronicle.record(
+            &game_time,
+            ChronicleKind::Tantrum,
+            format!("{} 因心情恶劣大发脾气", dwarf.name),
+        );
+    }
+
+    // 附近的矮人目睹了这一幕
+    for (entity, _, _, mut thoughts, pos, _, _) in dwarves.iter_mut() {
+        let witnessed = outbursts.iter().any(|(source, (x, y))| {
+            *source != entity && (pos.x - x).abs() + (pos.y - y).abs() <= TANTRUM_WITNESS_RADIUS
+        });
+        if witnessed {
+            thoughts.add(ThoughtKind::WitnessedTantrum);
+        }
+    }
+}
@@ -1,27 +1,117 @@
 use crate::components::*;
+use crate::flow_field::FlowFieldCache;
+use crate::pathfinding::NavGrid;
 use crate::world::*;
 use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// 被站立的矮人挡住且无处侧让时，最多等待多久后直接穿过
+const YIELD_TIMEOUT: f32 = 1.5;
+
+/// 每个格子上的矮人及其移动方向
+type Occupancy = HashMap<(i32, i32), Vec<(Entity, (i32, i32))>>;
+
+/// 把矮人在占用表中的记录从出发的格子挪到本帧要进入（或留在）的格子，
+/// 并记为不动，后面结算的矮人不会再挤进这一格
+fn settle(occupancy: &mut Occupancy, entity: Entity, from: (i32, i32), to: (i32, i32)) {
+    if let Some(others) = occupancy.get_mut(&from) {
+        others.retain(|(other, _)| *other != entity);
+        if others.is_empty() {
+            occupancy.remove(&from);
+        }
+    }
+    occupancy.entry(to).or_default().push((entity, (0, 0)));
+}
+
+/// 速度对应的网格移动方向（每个分量为 -1、0 或 1）
+fn move_direction(velocity: &Velocity) -> (i32, i32) {
+    let axis = |v: f32| if v.abs() < 0.01 { 0 } else { v.signum() as i32 };
+    (axis(velocity.x), axis(velocity.y))
+}
 
 /// 矮人移动系统 - 基于网格的离散移动，GridPosition始终反映实际位置
+#[allow(clippy::type_complexity)]
 pub fn dwarf_movement_system(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &mut GridPosition, &Velocity), With<Dwarf>>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut GridPosition,
+            &mut Velocity,
+            Option<&FollowFlowField>,
+        ),
+        With<Dwarf>,
+    >,
     terrain_query: Query<(&GridPosition, &Terrain), Without<Dwarf>>,
+    mut flow_fields: ResMut<FlowFieldCache>,
+    nav_grid: Res<NavGrid>,
+    mut wait_timers: Local<HashMap<Entity, f32>>,
 ) {
-    for (mut transform, mut grid_pos, velocity) in query.iter_mut() {
+    // 记录每个格子上的矮人及其移动方向，用于互相避让；
+    // 每名矮人结算后把记录挪到它要进入的格子，同一帧里两人不会走进同一格
+    let mut occupancy = Occupancy::new();
+    for (entity, _, grid_pos, velocity, _) in query.iter() {
+        occupancy
+            .entry((grid_pos.x, grid_pos.y))
+            .or_default()
+            .push((entity, move_direction(velocity)));
+    }
+    wait_timers.retain(|entity, _| query.contains(*entity));
+
+    for (entity, mut transform, mut grid_pos, mut velocity, follow_flow) in query.iter_mut() {
+        let start = (grid_pos.x, grid_pos.y);
+        let mut destination = start;
+        // 沿流场前进：方向取流场中代价更低的相邻格子，所有同路矮人共享同一张流场
+        if let Some(follow) = follow_flow {
+            let step = flow_fields
+                .field(follow.goal, &nav_grid)
+                .next_step((grid_pos.x, grid_pos.y));
+            let (vx, vy) = match step {
+                Some((x, y)) => ((x - grid_pos.x) as f32, (y - grid_pos.y) as f32),
+                None => (0.0, 0.0),
+            };
+            velocity.x = vx;
+            velocity.y = vy;
+        }
+        if move_direction(&velocity) == (0, 0) {
+            wait_timers.remove(&entity);
+        }
+
         // 只有在有速度时才移动
         if velocity.x.abs() > 0.01 || velocity.y.abs() > 0.01 {
             // 计算移动方向（规范化到-1, 0, 1）
-            let dir_x = if velocity.x.abs() < 0.01 {
-                0
-            } else {
-                velocity.x.signum() as i32
+            let (mut dir_x, mut dir_y) = move_direction(&velocity);
+
+            // 软避让：前方格子有站着不动、或本帧已有人抢先走进去的矮人时尝试侧让，无处可让则原地等待片刻；
+            // 迎面相遇（对方正朝本格移动）或对方正在走开时直接交换/跟随通过
+            let ahead = (grid_pos.x + dir_x, grid_pos.y + dir_y);
+            let is_blocked = |tile: (i32, i32)| {
+                occupancy.get(&tile).is_some_and(|others| {
+                    others
+                        .iter()
+                        .any(|(other, other_dir)| *other != entity && *other_dir == (0, 0))
+                })
             };
-            let dir_y = if velocity.y.abs() < 0.01 {
-                0
+            if is_blocked(ahead) {
+                let waited = wait_timers.entry(entity).or_insert(0.0);
+                *waited += time.delta_secs();
+                // 斜向移动不侧让；侧让格子需可行走且无人站立
+                let sidestep = [(dir_y, dir_x), (-dir_y, -dir_x)]
+                    .into_iter()
+                    .filter(|_| dir_x == 0 || dir_y == 0)
+                    .map(|(sx, sy)| (grid_pos.x + sx, grid_pos.y + sy))
+                    .find(|tile| nav_grid.is_walkable(tile.0, tile.1) && !occupancy.contains_key(tile));
+                if let Some((side_x, side_y)) = sidestep {
+                    dir_x = side_x - grid_pos.x;
+                    dir_y = side_y - grid_pos.y;
+                } else if *waited < YIELD_TIMEOUT {
+                    dir_x = 0;
+                    dir_y = 0;
+                }
             } else {
-                velocity.y.signum() as i32
-            };
+                wait_timers.remove(&entity);
+            }
 
             // 计算目标网格位置
             let target_grid_x = grid_pos.x + dir_x;
@@ -39,13 +129,14 @@ pub fn dwarf_movement_system(
                 for (terrain_pos, terrain) in terrain_query.iter() {
                     if terrain_pos.x == target_grid_x && terrain_pos.y == target_grid_y {
                         can_move = terrain.walkable;
-                        terrain_speed = terrain.terrain_type.movement_speed();
+                        terrain_speed = terrain.movement_speed();
                         break;
                     }
                 }
             }
 
             if can_move {
+                destination = (target_grid_x, target_grid_y);
                 // 计算目标世界坐标（与地形对齐）
                 let target_x = target_grid_x as f32 * TILE_SIZE
                     - (WORLD_WIDTH as f32 * TILE_SIZE / 2.0)
@@ -76,6 +167,8 @@ pub fn dwarf_movement_system(
             }
         }
 
+        settle(&mut occupancy, entity, start, destination);
+
         // 重要：每帧都根据transform计算GridPosition，确保GridPosition反映实际位置
         // 反向计算：pos = grid * TILE_SIZE - (WIDTH * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0)
         // 所以：grid = (pos + (WIDTH * TILE_SIZE / 2.0) - (TILE_SIZE / 2.0)) / TILE_SIZE
@@ -0,0 +1,105 @@
+use crate::components::*;
+use crate::connectivity::RegionMap;
+use crate::flow_field::FlowFieldCache;
+use crate::pathfinding::{find_path, NavGrid};
+use bevy::prelude::*;
+use bevy::tasks::{futures::check_ready, AsyncComputeTaskPool, Task};
+use std::sync::Arc;
+
+/// 每帧最多派发的寻路请求数
+const PATH_REQUESTS_PER_FRAME: usize = 16;
+/// 同时在任务池中计算的寻路请求上限
+const MAX_PATHS_IN_FLIGHT: usize = 64;
+
+/// 正在任务池中计算的寻路任务
+#[derive(Component)]
+pub struct PendingPath {
+    goal: (i32, i32),
+    task: Task<Option<Vec<(i32, i32)>>>,
+}
+
+/// 供寻路任务共享的导航网格快照（导航网格变化后重新拍摄）
+#[derive(Resource, Default)]
+pub struct NavGridSnapshot {
+    grid: Option<Arc<NavGrid>>,
+}
+
+/// 地形变化（采矿、建造、资源耗尽等）时增量更新连通区域和分层寻路图，并丢弃过期的流场
+pub fn navigation_update_system(
+    changed_terrain: Query<(&GridPosition, &Terrain), Changed<Terrain>>,
+    mut nav_grid: ResMut<NavGrid>,
+    mut region_map: ResMut<RegionMap>,
+    mut flow_fields: ResMut<FlowFieldCache>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let mut changed_tiles = 0;
+    for (pos, terrain) in changed_terrain.iter() {
+        // 新生成的地形也会被视为"已改变"，与导航网格一致时直接跳过
+        let (walkable_changed, cost_changed) =
+            nav_grid.bypass_change_detection().set_terrain(pos.x, pos.y, terrain);
+        if walkable_changed {
+            region_map.set_walkable(pos.x, pos.y, terrain.walkable);
+        }
+        if walkable_changed || cost_changed {
+            changed_tiles += 1;
+        }
+    }
+
+    if changed_tiles > 0 {
+        // 区块内距离按移动代价计算，只改变代价的区块也要重建；
+        // 重建会把导航网格标记为已改变，寻路任务随之拿到新的快照
+        let rebuilt = nav_grid.rebuild_dirty();
+        logger.debug(format!(
+            "地形变化 {} 格，重建 {} 个寻路区块",
+            changed_tiles, rebuilt
+        ));
+    }
+
+    // 进入新地图（导航网格被整体替换）或地形代价改变后，流场全部失效
+    if nav_grid.is_changed() {
+        flow_fields.clear();
+    }
+}
+
+/// 按每帧预算把寻路请求派发到异步计算任务池
+pub fn dispatch_path_requests_system(
+    mut commands: Commands,
+    nav_grid: Res<NavGrid>,
+    mut snapshot: ResMut<NavGridSnapshot>,
+    requests: Query<(Entity, &PathRequest), Without<PendingPath>>,
+    in_flight: Query<(), With<PendingPath>>,
+) {
+    if nav_grid.is_changed() || snapshot.grid.is_none() {
+        snapshot.grid = Some(Arc::new(nav_grid.clone()));
+    }
+    let Some(grid) = snapshot.grid.clone() else {
+        return;
+    };
+
+    let budget = PATH_REQUESTS_PER_FRAME.min(MAX_PATHS_IN_FLIGHT.saturating_sub(in_flight.iter().count()));
+    let pool = AsyncComputeTaskPool::get();
+    for (entity, request) in requests.iter().take(budget) {
+        let grid = grid.clone();
+        let (start, goal) = (request.start, request.goal);
+        let task = pool.spawn(async move { find_path(start, goal, &grid) });
+        commands.entity(entity).insert(PendingPath { goal, task });
+    }
+}
+
+/// 收集已完成的寻路任务，把结果作为组件交给工作系统
+pub fn collect_path_results_system(
+    mut commands: Commands,
+    mut pending: Query<(Entity, &mut PendingPath)>,
+) {
+    for (entity, mut pending_path) in pending.iter_mut() {
+        if let Some(path) = check_ready(&mut pending_path.task) {
+            commands
+                .entity(entity)
+                .remove::<(PendingPath, PathRequest)>()
+                .insert(PathResult {
+                    goal: pending_path.goal,
+                    path,
+                });
+        }
+    }
+}
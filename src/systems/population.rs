@@ -0,0 +1,438 @@
+use crate::components::*;
+use crate::connectivity::RegionMap;
+use crate::name_generator::DwarfNameGenerator;
+use crate::resources::*;
+use crate::world::{spawn_stored_dwarf, WORLD_HEIGHT, WORLD_WIDTH};
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// 要塞人口上限，达到后不再有移民和新生儿
+const MAX_POPULATION: usize = 30;
+/// 要塞财富低于该值时没有移民愿意前来
+const MIGRANT_MIN_WEALTH: u32 = 150;
+/// 每多少财富吸引一名移民
+const WEALTH_PER_MIGRANT: u32 = 200;
+/// 每波移民的最大人数
+const MAX_MIGRANTS_PER_WAVE: usize = 6;
+/// 已婚夫妻每个游戏小时生育的概率
+const BIRTH_CHANCE_PER_HOUR: f64 = 0.01;
+/// 饥饿度满值时每秒损失的健康
+const STARVATION_DAMAGE_PER_SECOND: f32 = 0.5;
+/// 吃饱时每秒恢复的健康
+const HEAL_PER_SECOND: f32 = 0.05;
+/// 在医院休养时健康恢复速度的倍数
+const HOSPITAL_HEAL_MULTIPLIER: f32 = 4.0;
+/// 饥饿度低于该值时才会恢复健康
+const HEAL_HUNGER_THRESHOLD: f32 = 70.0;
+/// 超过 OLD_AGE 后每多一岁，每个游戏小时寿终的概率增加该值
+const OLD_AGE_DEATH_CHANCE_PER_YEAR: f64 = 0.002;
+/// 多少格以内的矮人会看到遗体
+const CORPSE_WITNESS_RADIUS: i32 = 5;
+
+/// 死因
+#[derive(Clone, Copy, Debug)]
+enum DeathCause {
+    OldAge,
+    Starvation,
+    Wounds,
+}
+
+impl DeathCause {
+    fn label(&self) -> &'static str {
+        match self {
+            DeathCause::OldAge => "寿终正寝",
+            DeathCause::Starvation => "饿死",
+            DeathCause::Wounds => "伤重不治",
+        }
+    }
+}
+
+/// 长大：每过一个游戏日所有矮人年龄加一天，儿童到了成年年龄后开始工作
+pub fn aging_system(
+    game_time: Res<GameTime>,
+    mut dwarves: Query<(&Dwarf, &mut Age)>,
+    mut last_day: Local<Option<u32>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let previous = last_day.replace(game_time.day);
+    let Some(previous) = previous else {
+        return;
+    };
+    let days_passed = game_time.day.saturating_sub(previous);
+    if days_passed == 0 {
+        return;
+    }
+
+    for (dwarf, mut age) in dwarves.iter_mut() {
+        let was_child = age.is_child();
+        age.days += days_passed;
+        if was_child && !age.is_child() {
+            logger.info(format!("{} 长大成人了，开始参加劳动", dwarf.name));
+        }
+    }
+}
+
+/// 死亡：饿肚子会损伤健康，健康耗尽或年老都会死去。
+/// 死者从实体和已保存的矮人数据中移除并写入编年史，亲友悲痛，附近的矮人看到遗体
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn mortality_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut dwarves: Query<(Entity, &mut Dwarf, &Age, &GridPosition, &mut Relationships, &mut Thoughts)>,
+    mut selected: ResMut<SelectedDwarf>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut military: ResMut<Military>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut last_hour: Local<Option<(u32, u32)>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0.0001 {
+        return;
+    }
+
+    let now = (game_time.day, game_time.hour);
+    let new_hour = last_hour.is_some_and(|hour| hour != now);
+    *last_hour = Some(now);
+
+    let mut rng = rand::thread_rng();
+    let mut deaths: Vec<(Entity, String, (i32, i32), u32, DeathCause)> = Vec::new();
+
+    for (entity, mut dwarf, age, pos, _, _) in dwarves.iter_mut() {
+        if dwarf.hunger >= 100.0 {
+            dwarf.health -= STARVATION_DAMAGE_PER_SECOND * delta;
+        } else if dwarf.hunger < HEAL_HUNGER_THRESHOLD && dwarf.health < 100.0 {
+            let heal = if map_registry.in_zone(active_local.coord, ZoneKind::Hospital, (pos.x, pos.y)) {
+                HEAL_PER_SECOND * HOSPITAL_HEAL_MULTIPLIER
+            } else {
+                HEAL_PER_SECOND
+            };
+            dwarf.health = (dwarf.health + heal * delta).min(100.0);
+        }
+
+        let cause = if dwarf.health <= 0.0 {
+            Some(if dwarf.hunger >= 100.0 {
+                DeathCause::Starvation
+            } else {
+                DeathCause::Wounds
+            })
+        } else if new_hour
+            && age.years() >= OLD_AGE
+            && rng.gen_bool(
+                (OLD_AGE_DEATH_CHANCE_PER_YEAR * (age.years() - OLD_AGE + 1) as f64).min(1.0),
+            )
+        {
+            Some(DeathCause::OldAge)
+        } else {
+            None
+        };
+
+        if let Some(cause) = cause {
+            deaths.push((entity, dwarf.name.clone(), (pos.x, pos.y), age.years(), cause));
+        }
+    }
+
+    for (entity, name, (x, y), years, cause) in deaths {
+        commands.entity(entity).despawn();
+        if selected.entity == Some(entity) {
+            selected.entity = None;
+        }
+        for stored in map_registry.dwarves.values_mut() {
+            stored.retain(|dwarf| dwarf.name != name);
+        }
+        military.discharge(&name);
+        map_registry.forget_dwarf(&name);
+
+        logger.warning(format!("{} {}，享年 {} 岁", name, cause.label(), years));
+        chronicle.record(
+            &game_time,
+            ChronicleKind::Death,
+            format!("{} {}，享年 {} 岁", name, cause.label(), years),
+        );
+
+        for (other, _, _, pos, mut relationships, mut thoughts) in dwarves.iter_mut() {
+            if other == entity {
+                continue;
+            }
+            if relationships.forget(&name).is_some_and(|r| r.grieves()) {
+                thoughts.add(ThoughtKind::LostLovedOne);
+            }
+            if (pos.x - x).abs() + (pos.y - y).abs() <= CORPSE_WITNESS_RADIUS {
+                thoughts.add(ThoughtKind::SawCorpse);
+            }
+        }
+    }
+}
+
+/// 出生：同在要塞的已婚夫妻偶尔会生下孩子
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn birth_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_time: Res<GameTime>,
+    mut dwarves: Query<(Entity, &Dwarf, &Identity, &Age, &GridPosition, &mut Relationships, &mut Thoughts)>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    mut names: ResMut<DwarfNameGenerator>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut last_hour: Local<Option<(u32, u32)>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let now = (game_time.day, game_time.hour);
+    if last_hour.replace(now).is_none_or(|hour| hour == now) {
+        return;
+    }
+
+    let mut population = dwarves.iter().count();
+    if population >= MAX_POPULATION {
+        return;
+    }
+
+    let adults: Vec<(Entity, String, Identity, (i32, i32), Option<String>)> = dwarves
+        .iter()
+        .filter(|(_, _, _, age, ..)| !age.is_child())
+        .map(|(entity, dwarf, identity, _, pos, relationships, _)| {
+            (
+                entity,
+                dwarf.name.clone(),
+                identity.clone(),
+                (pos.x, pos.y),
+                relationships.with_bond(Bond::Spouse).next().map(str::to_string),
+            )
+        })
+        .collect();
+
+    let mut taken = taken_names(dwarves.iter().map(|(_, dwarf, ..)| dwarf.name.as_str()), &map_registry);
+    let mut rng = rand::thread_rng();
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+
+    for (parent, parent_name, parent_identity, pos, spouse) in &adults {
+        // 每对夫妻只处理一次
+        let Some(spouse) = spouse.as_ref().filter(|spouse| parent_name < *spouse) else {
+            continue;
+        };
+        let Some((other_parent, ..)) = adults.iter().find(|(_, name, ..)| name == spouse) else {
+            continue;
+        };
+        if population >= MAX_POPULATION || !rng.gen_bool(BIRTH_CHANCE_PER_HOUR) {
+            continue;
+        }
+
+        // 孩子随父母一方的文化和家族名
+        let identity = names.generate(game_time.day, 0..=0, Some(parent_identity), &taken);
+        let name = identity.full_name();
+        taken.insert(name.clone());
+        population += 1;
+
+        // 孩子与父母、兄弟姐妹的关系
+        let mut relationships = Relationships::default();
+        let mut siblings = Vec::new();
+        for entity in [*parent, *other_parent] {
+            let Ok((_, parent_dwarf, _, _, _, mut parent_relationships, mut thoughts)) = dwarves.get_mut(entity) else {
+                continue;
+            };
+            siblings.extend(parent_relationships.with_bond(Bond::Child).map(str::to_string));
+            parent_relationships.adjust(&name, 60.0);
+            parent_relationships.set_bond(&name, Bond::Child);
+            thoughts.add(ThoughtKind::ChildBorn);
+            relationships.adjust(&parent_dwarf.name, 60.0);
+            relationships.set_bond(&parent_dwarf.name, Bond::Parent);
+        }
+        siblings.sort();
+        siblings.dedup();
+        for (_, sibling, _, _, _, mut sibling_relationships, _) in dwarves.iter_mut() {
+            if siblings.contains(&sibling.name) {
+                sibling_relationships.adjust(&name, 30.0);
+                sibling_relationships.set_bond(&name, Bond::Sibling);
+                relationships.adjust(&sibling.name, 30.0);
+                relationships.set_bond(&sibling.name, Bond::Sibling);
+            }
+        }
+
+        let record = new_dwarf_record(
+            identity,
+            *pos,
+            Skills::default(),
+            relationships,
+            Equipment::default(),
+            &game_time,
+        );
+        spawn_stored_dwarf(&mut commands, &font, &record);
+
+        logger.info(format!("{} 和 {} 的孩子 {} 出生了！", parent_name, spouse, name));
+        chronicle.record(
+            &game_time,
+            ChronicleKind::Birth,
+            format!("{} 出生，父母是 {} 和 {}", name, parent_name, spouse),
+        );
+    }
+}
+
+/// 移民潮：每个季节开始时，根据要塞财富和季节决定有多少移民从地图边缘到来
+#[allow(clippy::too_many_arguments)]
+pub fn migrant_wave_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_time: Res<GameTime>,
+    dwarves: Query<&Dwarf>,
+    inventory: Res<GlobalInventory>,
+    region_map: Res<RegionMap>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    mut names: ResMut<DwarfNameGenerator>,
+    active_local: Res<ActiveLocalMap>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut last_season: Local<Option<(u32, Season)>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let now = (game_time.year(), game_time.season());
+    if last_season.replace(now).is_none_or(|season| season == now) {
+        return;
+    }
+    // 移民只会前往要塞所在的出生地块
+    if active_local.coord.is_none() || active_local.coord != map_registry.spawn_location {
+        return;
+    }
+
+    let wealth = fortress_wealth(&inventory);
+    if wealth < MIGRANT_MIN_WEALTH {
+        logger.info(format!("要塞财富 {} 太少，这个季节没有移民前来", wealth));
+        return;
+    }
+    // 春夏赶路的移民更多，冬天几乎没人上路
+    let season_bonus = match game_time.season() {
+        Season::Spring | Season::Summer => 2,
+        Season::Autumn => 1,
+        Season::Winter => 0,
+    };
+    let population = dwarves.iter().count();
+    let count = ((wealth / WEALTH_PER_MIGRANT) as usize + season_bonus)
+        .min(MAX_MIGRANTS_PER_WAVE)
+        .min(MAX_POPULATION.saturating_sub(population));
+    if count == 0 {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let arrival_tiles = pick_arrival_tiles(&region_map, count, &mut rng);
+    if arrival_tiles.is_empty() {
+        return;
+    }
+
+    let mut taken = taken_names(dwarves.iter().map(|dwarf| dwarf.name.as_str()), &map_registry);
+    let identities: Vec<Identity> = arrival_tiles
+        .iter()
+        .map(|_| {
+            let identity = names.generate(game_time.day, ADULT_AGE + 6..=100, None, &taken);
+            taken.insert(identity.full_name());
+            identity
+        })
+        .collect();
+    let arrivals: Vec<String> = identities.iter().map(Identity::full_name).collect();
+
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    for ((identity, name), tile) in identities.into_iter().zip(&arrivals).zip(&arrival_tiles) {
+        let mut skills = Skills::default();
+        let specialty = SkillKind::ALL[rng.gen_range(0..SkillKind::ALL.len())];
+        skills.set_level(specialty, rng.gen_range(0..=3));
+
+        // 同一波移民一路同行，彼此相识
+        let mut relationships = Relationships::default();
+        for companion in arrivals.iter().filter(|other| *other != name) {
+            relationships.adjust(companion, rng.gen_range(5.0..20.0));
+        }
+
+        let record = new_dwarf_record(
+            identity,
+            *tile,
+            skills,
+            relationships,
+            Equipment::for_specialty(specialty),
+            &game_time,
+        );
+        spawn_stored_dwarf(&mut commands, &font, &record);
+        chronicle.record(&game_time, ChronicleKind::Migrant, format!("{} 随移民潮抵达要塞", name));
+    }
+
+    logger.info(format!(
+        "第{}年{}，{} 名移民被要塞的财富（{}）吸引而来",
+        game_time.year() + 1,
+        game_time.season().label(),
+        arrivals.len(),
+        wealth
+    ));
+}
+
+/// 要塞财富：库存按价值加权求和
+pub fn fortress_wealth(inventory: &GlobalInventory) -> u32 {
+    inventory.stone + inventory.wood + inventory.food.total() + inventory.seeds + inventory.metal * 3 + inventory.tools * 5
+}
+
+/// 已被使用的名字（在场的和保存在其他地块的矮人）
+fn taken_names<'a>(present: impl Iterator<Item = &'a str>, map_registry: &GeneratedMapsRegistry) -> HashSet<String> {
+    present
+        .map(str::to_string)
+        .chain(map_registry.dwarves.values().flatten().map(|stored| stored.name.clone()))
+        .collect()
+}
+
+/// 在最大连通区域中挑选靠近地图边缘某处的若干格子
+pub fn pick_arrival_tiles(region_map: &RegionMap, count: usize, rng: &mut impl Rng) -> Vec<(i32, i32)> {
+    let Some(main) = region_map.largest_region() else {
+        return Vec::new();
+    };
+    let mut tiles = region_map.tiles_in(main);
+    let edge: Vec<(i32, i32)> = tiles
+        .iter()
+        .copied()
+        .filter(|(x, y)| *x == 0 || *y == 0 || *x == WORLD_WIDTH - 1 || *y == WORLD_HEIGHT - 1)
+        .collect();
+    let pool = if edge.is_empty() { &tiles } else { &edge };
+    let Some(&(ax, ay)) = pool.get(rng.gen_range(0..pool.len().max(1))) else {
+        return Vec::new();
+    };
+
+    tiles.sort_by_key(|(x, y)| (x - ax).abs() + (y - ay).abs());
+    tiles.truncate(count);
+    tiles
+}
+
+/// 新矮人的存储数据（移民与新生儿）
+fn new_dwarf_record(
+    identity: Identity,
+    (grid_x, grid_y): (i32, i32),
+    skills: Skills,
+    relationships: Relationships,
+    equipment: Equipment,
+    game_time: &GameTime,
+) -> StoredDwarf {
+    let age = identity.age_on(game_time.day);
+    let mut dwarf = Dwarf::new(identity.full_name());
+    if age.is_child() {
+        dwarf.hunger = 0.0;
+    }
+    StoredDwarf {
+        name: dwarf.name,
+        grid_x,
+        grid_y,
+        health: dwarf.health,
+        hunger: dwarf.hunger,
+        happiness: dwarf.happiness,
+        current_task: Some(Task::Idle),
+        work_progress: 0.0,
+        skills,
+        labours: Labours::default(),
+        personality: Personality::random(&mut rand::thread_rng()),
+        thoughts: Thoughts::default(),
+        relationships,
+        age,
+        identity,
+        equipment,
+        fatigue: dwarf.fatigue,
+        thirst: dwarf.thirst,
+        shift: Shift::default(),
+        last_update_day: game_time.day,
+        last_update_hour: game_time.hour,
+    }
+}
@@ -0,0 +1,230 @@
+use crate::components::*;
+use crate::pathfinding::NavGrid;
+use crate::resources::*;
+use crate::world::*;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// 家具字符的高度：在区域覆盖层之上、矮人之下
+const FURNITURE_Z: f32 = 0.2;
+/// 家具底板的高度，遮住地形字符
+const FURNITURE_BACKDROP_Z: f32 = 0.18;
+/// 等待建造的订单显示得淡一些
+const ORDER_ALPHA: f32 = 0.4;
+/// 传世之作的颜色
+const ARTIFACT_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+/// 散落在地上的食物的颜色
+const LOOSE_FOOD_COLOR: Color = Color::srgb(0.95, 0.8, 0.45);
+
+/// 地图上的家具、作坊、散落的食物和材料以及建造订单字符
+#[derive(Component)]
+pub struct FurnitureGlyph;
+
+fn material_color(material: ItemMaterial) -> Color {
+    match material {
+        ItemMaterial::Wood => Color::srgb(0.75, 0.5, 0.25),
+        ItemMaterial::Stone => Color::srgb(0.75, 0.75, 0.75),
+        ItemMaterial::Copper => Color::srgb(0.9, 0.55, 0.3),
+        ItemMaterial::Iron => Color::srgb(0.6, 0.7, 0.85),
+    }
+}
+
+/// 地形、家具或房间用途变化后重新检测房间：
+/// 从每件家具出发向四周填充可行走的地块，门和不可行走的地块（墙）挡住填充；
+/// 填充范围不超过上限、没有碰到地图边缘、且至少有一扇门的才算房间
+pub fn room_detection_system(
+    nav_grid: Res<NavGrid>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut rooms: ResMut<Rooms>,
+) {
+    if !nav_grid.is_changed() && !map_registry.is_changed() && !active_local.is_changed() {
+        return;
+    }
+
+    let furniture = map_registry.local_furniture(active_local.coord);
+    let doors: HashMap<(i32, i32), Construction> = map_registry
+        .local_constructions(active_local.coord)
+        .filter(|(_, construction)| construction.kind == ConstructionKind::Door)
+        .collect();
+    let uses = active_local.coord.and_then(|coord| map_registry.room_uses.get(&coord));
+
+    let mut detected = Vec::new();
+    let mut visited: HashSet<(i32, i32)> = HashSet::new();
+    for item in furniture.iter() {
+        if visited.contains(&item.pos) {
+            continue;
+        }
+        let Some(tiles) = flood_room(item.pos, &nav_grid, &doors) else {
+            visited.insert(item.pos);
+            continue;
+        };
+        visited.extend(tiles.iter().copied());
+
+        let room_doors: Vec<&Construction> = doors
+            .iter()
+            .filter(|(pos, _)| touches(&tiles, **pos))
+            .map(|(_, door)| door)
+            .collect();
+        if room_doors.is_empty() {
+            continue;
+        }
+        let contents: Vec<&PlacedFurniture> = furniture.iter().filter(|piece| tiles.contains(&piece.pos)).collect();
+
+        let anchor = *tiles.iter().min().expect("房间至少包含起点");
+        detected.push(Room {
+            anchor,
+            quality: contents.iter().map(|piece| piece.value()).sum::<f32>()
+                + room_doors.iter().map(|door| door.room_value()).sum::<f32>(),
+            beds: contents
+                .iter()
+                .filter(|piece| piece.kind == FurnitureKind::Bed)
+                .map(|piece| piece.pos)
+                .collect(),
+            tables: contents.iter().filter(|piece| piece.kind == FurnitureKind::Table).count(),
+            usage: uses.and_then(|uses| uses.get(&anchor)).cloned(),
+            tiles,
+        });
+    }
+    detected.sort_by_key(|room| room.anchor);
+    rooms.rooms = detected;
+}
+
+/// 从起点填充房间，碰到地图边缘或超过大小上限时返回 None
+fn flood_room(
+    start: (i32, i32),
+    nav_grid: &NavGrid,
+    doors: &HashMap<(i32, i32), Construction>,
+) -> Option<HashSet<(i32, i32)>> {
+    let mut tiles = HashSet::from([start]);
+    let mut open = VecDeque::from([start]);
+    while let Some((x, y)) = open.pop_front() {
+        if x == 0 || y == 0 || x == WORLD_WIDTH - 1 || y == WORLD_HEIGHT - 1 || tiles.len() > MAX_ROOM_TILES {
+            return None;
+        }
+        for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if nav_grid.is_walkable(next.0, next.1) && !doors.contains_key(&next) && tiles.insert(next) {
+                open.push_back(next);
+            }
+        }
+    }
+    Some(tiles)
+}
+
+fn touches(tiles: &HashSet<(i32, i32)>, (x, y): (i32, i32)) -> bool {
+    [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+        .iter()
+        .any(|neighbour| tiles.contains(neighbour))
+}
+
+/// 家具、作坊、散落的食物和材料或订单变化后重建地图上的这些字符；订单显示为半透明
+pub fn furniture_glyph_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    glyphs: Query<Entity, With<FurnitureGlyph>>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+) {
+    if !map_registry.is_changed() && !active_local.is_changed() {
+        return;
+    }
+
+    for entity in glyphs.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let placed = map_registry
+        .local_furniture(active_local.coord)
+        .iter()
+        .map(|item| {
+            let color = if item.artifact { ARTIFACT_COLOR } else { material_color(item.material) };
+            (item.pos, item.kind.glyph(), color, 1.0)
+        });
+    let workshops = map_registry
+        .local_workshops(active_local.coord)
+        .iter()
+        .map(|workshop| (workshop.pos, workshop.kind.glyph(), material_color(workshop.material), 1.0));
+    let loose_food = map_registry
+        .local_loose_food(active_local.coord)
+        .iter()
+        .map(|food| (food.pos, food.kind.glyph(), LOOSE_FOOD_COLOR, 1.0));
+    let loose_materials = map_registry.local_loose_materials(active_local.coord).iter().map(|loose| {
+        let material = match loose.kind {
+            MaterialKind::Wood => ItemMaterial::Wood,
+            MaterialKind::Stone => ItemMaterial::Stone,
+            MaterialKind::Metal => ItemMaterial::Iron,
+        };
+        (loose.pos, loose.kind.glyph(), material_color(material), 1.0)
+    });
+    // 建筑结构建成后画在地形上，这里只显示它们的订单
+    let ordered = map_registry.local_build_orders(active_local.coord).iter().map(|order| {
+        let glyph = match order.building {
+            BuildingType::Construction(kind) => construction_visual(Construction {
+                kind,
+                material: order.material,
+            })
+            .1,
+            BuildingType::Furniture(kind) => kind.glyph(),
+            BuildingType::Workshop(kind) => kind.glyph(),
+            _ => '?',
+        };
+        (order.pos, glyph, material_color(order.material), ORDER_ALPHA)
+    });
+    for ((x, y), glyph, color, alpha) in placed.chain(workshops).chain(loose_food).chain(loose_materials).chain(ordered) {
+        let pos_x = x as f32 * TILE_SIZE - (WORLD_WIDTH as f32 * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0);
+        let pos_y = y as f32 * TILE_SIZE - (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0);
+        commands.spawn((
+            Sprite {
+                color: Color::srgba(0.12, 0.1, 0.08, 0.9 * alpha),
+                custom_size: Some(Vec2::new(TILE_SIZE - 1.0, TILE_SIZE - 1.0)),
+                ..default()
+            },
+            Transform::from_xyz(pos_x, pos_y, FURNITURE_BACKDROP_Z),
+            FurnitureGlyph,
+        ));
+        commands.spawn((
+            Text2d::new(glyph.to_string()),
+            TextFont {
+                font: font.clone(),
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(color.with_alpha(alpha)),
+            Transform::from_xyz(pos_x, pos_y, FURNITURE_Z),
+            FurnitureGlyph,
+        ));
+    }
+}
+
+/// 离开局部地图时移除家具字符，清空房间并收起建造面板的选择（家具数据保留在注册表中）
+pub fn cleanup_furniture_glyphs(
+    mut commands: Commands,
+    glyphs: Query<Entity, With<FurnitureGlyph>>,
+    mut rooms: ResMut<Rooms>,
+    mut build_menu: ResMut<BuildMenu>,
+) {
+    for entity in glyphs.iter() {
+        commands.entity(entity).despawn();
+    }
+    rooms.rooms.clear();
+    build_menu.selected = None;
+}
+
+/// 房间用途按房间标识地块保存；房间消失后保留的用途在房间重新围起来时恢复
+pub fn assign_room(
+    map_registry: &mut GeneratedMapsRegistry,
+    coord: IVec2,
+    anchor: (i32, i32),
+    usage: Option<RoomUse>,
+) {
+    let uses: &mut HashMap<(i32, i32), RoomUse> = map_registry.room_uses.entry(coord).or_default();
+    // 每名矮人只有一间卧室
+    if let Some(RoomUse::Bedroom(owner)) = &usage {
+        uses.retain(|_, existing| *existing != RoomUse::Bedroom(owner.clone()));
+    }
+    match usage {
+        Some(usage) => uses.insert(anchor, usage),
+        None => uses.remove(&anchor),
+    };
+}
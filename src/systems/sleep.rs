@@ -0,0 +1,200 @@
+use crate::components::*;
+use crate::resources::*;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// 醒着时每秒增加的疲劳（一个白天约积累到 80）
+const FATIGUE_PER_SECOND: f32 = 0.5;
+/// 睡眠质量为 1 时每秒恢复的疲劳
+const SLEEP_RECOVERY_PER_SECOND: f32 = 1.2;
+/// 到了睡觉时间、疲劳达到该值才去睡
+const BEDTIME_FATIGUE: f32 = 25.0;
+/// 疲劳低于该值时到了起床时间就起床；在此之上会一直睡到缓过来
+const RESTED_FATIGUE: f32 = 60.0;
+/// 疲劳达到该值时心情变差
+const EXHAUSTED_FATIGUE: f32 = 85.0;
+/// 疲劳达到该值时不管在干什么都会就地倒头睡下
+const COLLAPSE_FATIGUE: f32 = 98.0;
+/// 睡在地上的睡眠质量
+const FLOOR_SLEEP_QUALITY: f32 = 0.5;
+/// 房间品质对睡眠质量的加成上限
+const MAX_ROOM_COMFORT: f32 = 0.5;
+/// 房间里的气温（墙挡住了风雪）
+const INDOOR_TEMPERATURE: f32 = 18.0;
+/// 低于该气温睡觉会冻着
+const COLD_TEMPERATURE: f32 = 10.0;
+/// 高于该气温睡觉会热得睡不好
+const HOT_TEMPERATURE: f32 = 26.0;
+
+/// 睡觉地点的体感气温：房间里恒温，露天取室外气温
+fn sleeping_temperature(room: Option<&Room>, outdoor: f32) -> f32 {
+    if room.is_some() {
+        INDOOR_TEMPERATURE
+    } else {
+        outdoor
+    }
+}
+
+/// 睡眠质量：床比地面好，好房间更舒服，太冷太热都睡不好
+fn sleep_quality(in_bed: bool, room: Option<&Room>, temperature: f32) -> f32 {
+    let base = if in_bed { 1.0 } else { FLOOR_SLEEP_QUALITY };
+    let comfort = room.map_or(0.0, |room| (room.quality / 100.0).min(MAX_ROOM_COMFORT));
+    let warmth = if temperature < 0.0 {
+        0.5
+    } else if temperature < COLD_TEMPERATURE {
+        0.75
+    } else if temperature > HOT_TEMPERATURE {
+        0.85
+    } else {
+        1.0
+    };
+    (base + comfort) * warmth
+}
+
+/// 疲劳：醒着时上升，睡着时按床、房间和气温决定的睡眠质量恢复；太累时心情变差
+pub fn fatigue_system(
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    weather: Res<CurrentWeather>,
+    rooms: Res<Rooms>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut dwarves: Query<(&mut Dwarf, &WorkState, &GridPosition, &mut Thoughts)>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+
+    let outdoor = game_time.temperature(weather.weather);
+    let beds: HashSet<(i32, i32)> = local_beds(&map_registry, active_local.coord);
+    for (mut dwarf, work_state, pos, mut thoughts) in dwarves.iter_mut() {
+        let here = (pos.x, pos.y);
+        if is_asleep(work_state, here) {
+            let room = rooms.room_at(here);
+            let quality = sleep_quality(beds.contains(&here), room, sleeping_temperature(room, outdoor));
+            dwarf.fatigue = (dwarf.fatigue - SLEEP_RECOVERY_PER_SECOND * quality * time.delta_secs()).max(0.0);
+        } else {
+            dwarf.fatigue = (dwarf.fatigue + FATIGUE_PER_SECOND * time.delta_secs()).min(100.0);
+            if dwarf.fatigue >= EXHAUSTED_FATIGUE {
+                thoughts.add(ThoughtKind::Exhausted);
+            }
+        }
+    }
+}
+
+/// 作息：到了所在班次的睡觉时间，空闲且有些累的矮人去睡觉——先找自己卧室的床，
+/// 再找没人占的公共床位，都没有就睡在地上；累垮的矮人放下手头的工作就地睡下。
+/// 睡够了或到了起床时间就起床，按睡得好坏留下想法。需在工作系统之前运行
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn sleep_schedule_system(
+    game_time: Res<GameTime>,
+    weather: Res<CurrentWeather>,
+    rooms: Res<Rooms>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut dwarves: Query<(&Dwarf, &Shift, &GridPosition, &mut WorkState, &mut Thoughts, Has<Tantrum>)>,
+) {
+    let outdoor = game_time.temperature(weather.weather);
+    let beds = local_beds(&map_registry, active_local.coord);
+    // 已经有人睡（或正走去睡）的床
+    let mut claimed: HashSet<(i32, i32)> = dwarves
+        .iter()
+        .filter_map(|(_, _, _, work_state, ..)| match &work_state.current_task {
+            Some(Task::Sleep(spot)) => Some((spot.x, spot.y)),
+            _ => None,
+        })
+        .filter(|spot| beds.contains(spot))
+        .collect();
+
+    for (dwarf, shift, pos, mut work_state, mut thoughts, in_tantrum) in dwarves.iter_mut() {
+        let here = (pos.x, pos.y);
+        let sleep_time = shift.is_sleep_time(game_time.hour);
+        match &work_state.current_task {
+            Some(Task::Sleep(spot)) => {
+                let spot = (spot.x, spot.y);
+                let asleep = spot == here;
+                let rested = dwarf.fatigue <= 0.0 || (!sleep_time && dwarf.fatigue < RESTED_FATIGUE);
+                // 还没走到床边就到了起床时间的，直接起床
+                if !rested && (asleep || sleep_time) {
+                    continue;
+                }
+                if asleep {
+                    let room = rooms.room_at(here);
+                    if !beds.contains(&here) {
+                        thoughts.add(ThoughtKind::SleptOutside);
+                    } else if room.is_some_and(|room| room.quality >= GOOD_ROOM_QUALITY) {
+                        thoughts.add(ThoughtKind::SleptInGoodBedroom);
+                    }
+                    if sleeping_temperature(room, outdoor) < COLD_TEMPERATURE {
+                        thoughts.add(ThoughtKind::SleptInCold);
+                    }
+                }
+                claimed.remove(&spot);
+                work_state.current_task = Some(Task::Idle);
+                work_state.task_cooldown = 0.0;
+                work_state.task_duration = 0.0;
+            }
+            _ if in_tantrum => {}
+            // 累垮了：放下手头的工作就地睡下（军事任务除外）
+            Some(Task::Gathering(_))
+            | Some(Task::Mining(_))
+            | Some(Task::Building(..))
+            | Some(Task::Crafting(..))
+            | Some(Task::Fishing(_))
+            | Some(Task::PickUp(_))
+            | Some(Task::Hauling(_))
+                if dwarf.fatigue >= COLLAPSE_FATIGUE =>
+            {
+                start_sleep(&mut work_state, here);
+            }
+            None | Some(Task::Idle) | Some(Task::Wandering(_))
+                if (sleep_time && dwarf.fatigue >= BEDTIME_FATIGUE) || dwarf.fatigue >= COLLAPSE_FATIGUE =>
+            {
+                let own_bed = rooms
+                    .bedroom_of(&dwarf.name)
+                    .and_then(|room| room.beds.iter().find(|bed| !claimed.contains(*bed)))
+                    .copied();
+                // 公共床位：不在别人卧室里、也没人占的床，挑最近的
+                let shared_bed = || {
+                    beds.iter()
+                        .filter(|bed| !claimed.contains(*bed))
+                        .filter(|bed| {
+                            !rooms
+                                .room_at(**bed)
+                                .is_some_and(|room| matches!(room.usage, Some(RoomUse::Bedroom(_))))
+                        })
+                        .min_by_key(|bed| (bed.0 - here.0).abs() + (bed.1 - here.1).abs())
+                        .copied()
+                };
+                let spot = own_bed.or_else(shared_bed).unwrap_or(here);
+                if beds.contains(&spot) {
+                    claimed.insert(spot);
+                }
+                start_sleep(&mut work_state, spot);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 矮人是否已经躺在睡觉的地方
+pub fn is_asleep(work_state: &WorkState, pos: (i32, i32)) -> bool {
+    matches!(&work_state.current_task, Some(Task::Sleep(spot)) if (spot.x, spot.y) == pos)
+}
+
+fn start_sleep(work_state: &mut WorkState, (x, y): (i32, i32)) {
+    work_state.current_task = Some(Task::Sleep(GridPosition { x, y }));
+    work_state.cached_path.clear();
+    work_state.path_index = 0;
+    work_state.work_progress = 0.0;
+    work_state.task_duration = 0.0;
+}
+
+fn local_beds(map_registry: &GeneratedMapsRegistry, coord: Option<IVec2>) -> HashSet<(i32, i32)> {
+    map_registry
+        .local_furniture(coord)
+        .iter()
+        .filter(|item| item.kind == FurnitureKind::Bed)
+        .map(|item| item.pos)
+        .collect()
+}
@@ -0,0 +1,245 @@
+use crate::components::*;
+use crate::resources::*;
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// 多少格以内算作待在一起
+const COMPANION_RADIUS: i32 = 3;
+/// 每个游戏小时待在一起增加的基础好感度
+const COMPANION_AFFINITY_PER_HOUR: f32 = 1.0;
+/// 多少格以内可以聊天
+const CONVERSATION_RADIUS: i32 = 2;
+/// 两名空闲矮人靠近时每秒开始聊天的概率
+const CONVERSATION_CHANCE_PER_SECOND: f32 = 0.2;
+/// 聊完之后多久才会再次聊天（秒）
+const CONVERSATION_COOLDOWN: f32 = 30.0;
+/// 相互好感度达到该值才可能成为恋人
+const ROMANCE_AFFINITY: f32 = 70.0;
+/// 恋人相互好感度达到该值才可能结婚
+const MARRIAGE_AFFINITY: f32 = 90.0;
+/// 满足条件时每个游戏小时坠入爱河的概率
+const ROMANCE_CHANCE_PER_HOUR: f64 = 0.15;
+/// 满足条件时每个游戏小时结婚的概率
+const MARRIAGE_CHANCE_PER_HOUR: f64 = 0.1;
+
+/// 社交系统共用的矮人查询
+type SocialQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Dwarf,
+        &'static Personality,
+        &'static GridPosition,
+        &'static WorkState,
+        &'static Age,
+        Has<Tantrum>,
+        &'static mut Relationships,
+        &'static mut Thoughts,
+    ),
+>;
+
+/// 社交系统使用的矮人快照
+struct SocialSnapshot {
+    entity: Entity,
+    name: String,
+    pos: (i32, i32),
+    personality: Personality,
+    /// 空闲或闲逛中（可以聊天）
+    at_leisure: bool,
+    is_child: bool,
+}
+
+fn distance(a: (i32, i32), b: (i32, i32)) -> i32 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+/// 相处：每个游戏小时，待在一起的矮人根据性格是否合得来增减好感；
+/// 情投意合的矮人可能成为恋人，恋人可能结婚
+pub fn companionship_system(
+    game_time: Res<GameTime>,
+    mut dwarves: SocialQuery,
+    mut last_hour: Local<Option<(u32, u32)>>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let now = (game_time.day, game_time.hour);
+    if *last_hour == Some(now) {
+        return;
+    }
+    let first_run = last_hour.is_none();
+    *last_hour = Some(now);
+    if first_run {
+        return;
+    }
+
+    let snapshot: Vec<SocialSnapshot> = dwarves
+        .iter()
+        .map(|(entity, dwarf, personality, pos, _, age, ..)| SocialSnapshot {
+            entity,
+            name: dwarf.name.clone(),
+            pos: (pos.x, pos.y),
+            personality: personality.clone(),
+            at_leisure: false,
+            is_child: age.is_child(),
+        })
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    for (i, a) in snapshot.iter().enumerate() {
+        for b in snapshot.iter().skip(i + 1) {
+            if distance(a.pos, b.pos) > COMPANION_RADIUS {
+                continue;
+            }
+            let delta = COMPANION_AFFINITY_PER_HOUR + 0.5 * a.personality.compatibility(&b.personality);
+            adjust_pair(&mut dwarves, a, b, delta);
+
+            let Some((a_view, b_view, a_partner, b_partner)) = pair_state(&dwarves, a, b) else {
+                continue;
+            };
+
+            // 坠入爱河：双方都是单身成年人、不是亲属，且相互好感足够高
+            if !a.is_child
+                && !b.is_child
+                && a_view.bond == Bond::None
+                && b_view.bond == Bond::None
+                && !a_partner
+                && !b_partner
+                && a_view.affinity.min(b_view.affinity) >= ROMANCE_AFFINITY
+                && rng.gen_bool(ROMANCE_CHANCE_PER_HOUR)
+            {
+                bond_pair(&mut dwarves, a, b, Bond::Lover, ThoughtKind::FellInLove);
+                logger.info(format!("{} 和 {} 坠入了爱河", a.name, b.name));
+            } else if a_view.bond == Bond::Lover
+                && b_view.bond == Bond::Lover
+                && a_view.affinity.min(b_view.affinity) >= MARRIAGE_AFFINITY
+                && rng.gen_bool(MARRIAGE_CHANCE_PER_HOUR)
+            {
+                bond_pair(&mut dwarves, a, b, Bond::Spouse, ThoughtKind::GotMarried);
+                logger.info(format!("{} 和 {} 结婚了！", a.name, b.name));
+                chronicle.record(
+                    &game_time,
+                    ChronicleKind::Marriage,
+                    format!("{} 与 {} 结为夫妻", a.name, b.name),
+                );
+            }
+        }
+    }
+}
+
+/// 聊天：空闲或闲逛的矮人碰到一起时会聊上几句，
+/// 聊得投机增进好感，话不投机则吵上一架
+pub fn conversation_system(
+    time: Res<Time>,
+    mut dwarves: SocialQuery,
+    mut cooldowns: Local<HashMap<Entity, f32>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0.0001 {
+        return;
+    }
+
+    cooldowns.retain(|entity, remaining| {
+        *remaining -= delta;
+        *remaining > 0.0 && dwarves.contains(*entity)
+    });
+
+    let snapshot: Vec<SocialSnapshot> = dwarves
+        .iter()
+        .map(|(entity, dwarf, personality, pos, work_state, age, in_tantrum, ..)| SocialSnapshot {
+            entity,
+            name: dwarf.name.clone(),
+            pos: (pos.x, pos.y),
+            personality: personality.clone(),
+            // 发脾气的矮人不和人聊天
+            at_leisure: !in_tantrum
+                && matches!(
+                    work_state.current_task,
+                    None | Some(Task::Idle) | Some(Task::Wandering(_))
+                ),
+            is_child: age.is_child(),
+        })
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    for (i, a) in snapshot.iter().enumerate() {
+        if !a.at_leisure || cooldowns.contains_key(&a.entity) {
+            continue;
+        }
+        for b in snapshot.iter().skip(i + 1) {
+            if !b.at_leisure
+                || cooldowns.contains_key(&b.entity)
+                || distance(a.pos, b.pos) > CONVERSATION_RADIUS
+                || !rng.gen_bool((CONVERSATION_CHANCE_PER_SECOND * delta).clamp(0.0, 1.0) as f64)
+            {
+                continue;
+            }
+
+            let mutual = pair_state(&dwarves, a, b)
+                .map(|(a_view, b_view, _, _)| (a_view.affinity + b_view.affinity) / 2.0)
+                .unwrap_or(0.0);
+            let mut good_chance = 0.65 + 0.1 * a.personality.compatibility(&b.personality) + 0.002 * mutual;
+            if a.personality.has(PersonalityTrait::HotTempered) || b.personality.has(PersonalityTrait::HotTempered) {
+                good_chance -= 0.15;
+            }
+
+            if rng.gen_bool(good_chance.clamp(0.1, 0.95) as f64) {
+                adjust_pair(&mut dwarves, a, b, 6.0);
+                add_pair_thought(&mut dwarves, a, b, ThoughtKind::ChattedWithFriend);
+                logger.debug(format!("{} 和 {} 聊得很投机", a.name, b.name));
+            } else {
+                adjust_pair(&mut dwarves, a, b, -8.0);
+                add_pair_thought(&mut dwarves, a, b, ThoughtKind::Argued);
+                logger.info(format!("{} 和 {} 吵了一架", a.name, b.name));
+            }
+            cooldowns.insert(a.entity, CONVERSATION_COOLDOWN);
+            cooldowns.insert(b.entity, CONVERSATION_COOLDOWN);
+            break;
+        }
+    }
+}
+
+/// 双方互相调整好感度
+fn adjust_pair(dwarves: &mut SocialQuery, a: &SocialSnapshot, b: &SocialSnapshot, delta: f32) {
+    for (me, other) in [(a, b), (b, a)] {
+        if let Ok((.., mut relationships, _)) = dwarves.get_mut(me.entity) {
+            relationships.adjust(&other.name, delta);
+        }
+    }
+}
+
+/// 为双方建立关系纽带并各自产生想法
+fn bond_pair(dwarves: &mut SocialQuery, a: &SocialSnapshot, b: &SocialSnapshot, bond: Bond, thought: ThoughtKind) {
+    for (me, other) in [(a, b), (b, a)] {
+        if let Ok((.., mut relationships, mut thoughts)) = dwarves.get_mut(me.entity) {
+            relationships.set_bond(&other.name, bond);
+            thoughts.add(thought);
+        }
+    }
+}
+
+fn add_pair_thought(dwarves: &mut SocialQuery, a: &SocialSnapshot, b: &SocialSnapshot, thought: ThoughtKind) {
+    for me in [a, b] {
+        if let Ok((.., mut thoughts)) = dwarves.get_mut(me.entity) {
+            thoughts.add(thought);
+        }
+    }
+}
+
+/// 双方对彼此的看法，以及各自是否已有恋人或配偶
+fn pair_state(
+    dwarves: &SocialQuery,
+    a: &SocialSnapshot,
+    b: &SocialSnapshot,
+) -> Option<(Relationship, Relationship, bool, bool)> {
+    let (.., a_relationships, _) = dwarves.get(a.entity).ok()?;
+    let (.., b_relationships, _) = dwarves.get(b.entity).ok()?;
+    Some((
+        a_relationships.get(&b.name).cloned().unwrap_or_default(),
+        b_relationships.get(&a.name).cloned().unwrap_or_default(),
+        a_relationships.partner().is_some(),
+        b_relationships.partner().is_some(),
+    ))
+}
@@ -64,7 +64,7 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     let help_panel = builder.create_panel("help_info", help_config, HelpPanel);
     builder.add_text(
         help_panel,
-        "操作说明:\nWASD/方向键: 移动视角\n鼠标滚轮: 缩放视角\n鼠标左键: 选择矮人\n鼠标右键: 指挥矮人移动\nM: 返回世界地图\n黄色边框 = 选中的矮人\n\n时间控制:\n空格: 暂停/继续\n1: 暂停 | 2: 半速 | 3: 正常\n4: 2倍速 | 5: 5倍速\n\nF1: 切换帮助显示\nF2: 切换调试模式 | F4: 消息面板 | F5: 清除日志\nF3: 切换调试面板",
+        "操作说明:\nWASD/方向键: 移动视角\n鼠标滚轮: 缩放视角\n鼠标左键: 选择矮人\n鼠标右键: 指挥矮人移动\nG: 全体集合到鼠标位置\nL: 劳动管理\nK: 军事 | Z: 区域 | B: 建造\nM: 返回世界地图\n黄色边框 = 选中的矮人\n\n时间控制:\n空格: 暂停/继续\n1: 暂停 | 2: 半速 | 3: 正常\n4: 2倍速 | 5: 5倍速\n\nF1: 切换帮助显示\nF2: 切换调试模式 | F4: 消息面板 | F5: 清除日志\nF3: 切换调试面板",
         HelpDisplay,
     );
 
@@ -95,6 +95,7 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
 pub fn ui_update_system(
     inventory: Res<GlobalInventory>,
     game_time: Res<GameTime>,
+    weather: Res<CurrentWeather>,
     dwarves: Query<(&Dwarf, &WorkState)>,
     mut query: Query<&mut Text, With<ResourceDisplay>>,
 ) {
@@ -128,15 +129,40 @@ pub fn ui_update_system(
             &format!("▶{}x", game_time.time_scale)
         };
 
+        // 食物按分类列出：原料、饭菜、饮料
+        let food_summary = FoodCategory::ALL
+            .iter()
+            .map(|category| {
+                let entries = inventory.food.summary(*category);
+                let listed = if entries.is_empty() {
+                    "无".to_string()
+                } else {
+                    entries
+                        .iter()
+                        .map(|(label, amount)| format!("{} {}", label, amount))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                format!("{}: {}", category.label(), listed)
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+
         **text = format!(
-            "第{}天 {}时 {} | 石头: {} | 木材: {} | 食物: {} | 金属: {}\n矮人状态: 空闲{} 采集{} 挖矿{}",
+            "第{}年{} 第{}天 {}时 {} {} | 石头: {} | 木材: {} | 食物: {} | 金属: {} | 工具: {} | 种子: {}\n{}\n矮人状态: 空闲{} 采集{} 挖矿{}",
+            game_time.year() + 1,
+            game_time.season().label(),
             game_time.day,
             game_time.hour,
+            weather.weather.label(),
             speed_text,
             inventory.stone,
             inventory.wood,
-            inventory.food,
+            inventory.food.total(),
             inventory.metal,
+            inventory.tools,
+            inventory.seeds,
+            food_summary,
             idle_count,
             gathering_count,
             mining_count,
@@ -145,9 +171,25 @@ pub fn ui_update_system(
 }
 
 /// 更新矮人详情面板
+#[allow(clippy::type_complexity)]
 pub fn update_dwarf_panel(
     selected: Res<SelectedDwarf>,
-    dwarves: Query<(&Dwarf, &WorkState, &GridPosition)>,
+    dwarves: Query<(
+        &Dwarf,
+        &Skills,
+        &Labours,
+        &Personality,
+        &Thoughts,
+        &Relationships,
+        &Age,
+        &Identity,
+        &Equipment,
+        &Shift,
+        &WorkState,
+        &GridPosition,
+        Has<PathRequest>,
+        Has<Tantrum>,
+    )>,
     mut text_query: Query<&mut Text, With<DwarfPanel>>,
     mut panel_query: Query<(&mut UIPanel, &mut Node), With<DwarfDetailPanel>>,
 ) {
@@ -163,7 +205,9 @@ pub fn update_dwarf_panel(
     };
 
     // 如果无法获取矮人数据，隐藏面板
-    let Ok((dwarf, work_state, pos)) = dwarves.get(selected_entity) else {
+    let Ok((dwarf, skills, labours, personality, thoughts, relationships, age, identity, equipment, shift, work_state, pos, thinking, in_tantrum)) =
+        dwarves.get(selected_entity)
+    else {
         for (mut panel, mut node) in panel_query.iter_mut() {
             if panel.state != PanelState::Hidden {
                 node.display = Display::None;
@@ -184,12 +228,25 @@ pub fn update_dwarf_panel(
     // 更新面板内容
     for mut text in text_query.iter_mut() {
         // 构建任务信息
-        let (task_name, task_detail) = match &work_state.current_task {
+        let (task_name, mut task_detail) = match &work_state.current_task {
             Some(Task::Idle) => ("空闲", "正在休息".to_string()),
             Some(Task::Wandering(target)) => (
                 "闲逛",
                 format!("目标位置: ({}, {})", target.x, target.y),
             ),
+            Some(Task::Rally(target)) => (
+                "集合",
+                format!("集合点: ({}, {})", target.x, target.y),
+            ),
+            Some(Task::Hauling(target)) => (
+                "搬运",
+                format!("仓库: ({}, {})", target.x, target.y),
+            ),
+            Some(Task::Stand(target)) => (
+                "待命",
+                format!("位置: ({}, {})", target.x, target.y),
+            ),
+            Some(Task::Attack(_)) => ("战斗", "正在追击敌人".to_string()),
             Some(Task::Gathering(target)) => {
                 let progress = (work_state.work_progress * 100.0) as i32;
                 (
@@ -204,15 +261,57 @@ pub fn update_dwarf_panel(
                     format!("位置: ({}, {})\n进度: {}%", target.x, target.y, progress),
                 )
             }
-            Some(Task::Building(target, building_type)) => (
-                "建造建筑",
+            Some(Task::Building(target, building_type)) => {
+                let progress = (work_state.work_progress * 100.0) as i32;
+                (
+                    "建造建筑",
+                    format!(
+                        "位置: ({}, {})\n类型: {}\n进度: {}%",
+                        target.x,
+                        target.y,
+                        building_type.label(),
+                        progress
+                    ),
+                )
+            }
+            Some(Task::Crafting(target, workshop)) => {
+                let progress = (work_state.work_progress * 100.0) as i32;
+                (
+                    workshop.action(),
+                    format!(
+                        "{}: ({}, {})\n进度: {}%",
+                        workshop.label(),
+                        target.x,
+                        target.y,
+                        progress
+                    ),
+                )
+            }
+            Some(Task::Fishing(target)) => {
+                let progress = (work_state.work_progress * 100.0) as i32;
+                (
+                    "钓鱼",
+                    format!("水面: ({}, {})\n进度: {}%", target.x, target.y, progress),
+                )
+            }
+            Some(Task::PickUp(target)) => (
+                "捡拾",
+                format!("位置: ({}, {})", target.x, target.y),
+            ),
+            Some(Task::Sleep(target)) => (
+                "睡觉",
                 format!(
-                    "位置: ({}, {})\n类型: {:?}",
-                    target.x, target.y, building_type
+                    "地点: ({}, {})\n{}",
+                    target.x,
+                    target.y,
+                    if super::is_asleep(work_state, (pos.x, pos.y)) { "正在睡觉" } else { "正走去睡觉" }
                 ),
             ),
             None => ("无任务", "等待指令".to_string()),
         };
+        if thinking {
+            task_detail.push_str("\n思考中: 正在规划路线…");
+        }
 
         // 计算健康状态
         let health_status = if dwarf.health >= 80.0 {
@@ -232,28 +331,137 @@ pub fn update_dwarf_panel(
             "饥饿"
         };
 
-        // 计算快乐状态
-        let happiness_status = if dwarf.happiness >= 75.0 {
-            "愉快"
-        } else if dwarf.happiness >= 50.0 {
-            "一般"
-        } else if dwarf.happiness >= 25.0 {
-            "沮丧"
+        // 计算口渴状态
+        let thirst_status = if dwarf.thirst < 30.0 {
+            "解渴"
+        } else if dwarf.thirst < 70.0 {
+            "正常"
+        } else {
+            "口渴"
+        };
+
+        // 计算疲劳状态
+        let fatigue_status = if dwarf.fatigue < 30.0 {
+            "精神"
+        } else if dwarf.fatigue < 60.0 {
+            "有点累"
+        } else if dwarf.fatigue < 85.0 {
+            "疲惫"
         } else {
-            "痛苦"
+            "筋疲力尽"
         };
 
+        // 心情由想法计算，发脾气时特别标注
+        let mut mood_status = MoodLevel::from_mood(dwarf.happiness).label().to_string();
+        if in_tantrum {
+            mood_status.push_str("，正在发脾气！");
+        }
+
+        // 性格与喜好
+        let traits: Vec<&str> = personality.traits.iter().map(|t| t.label()).collect();
+        let personality_text = format!(
+            "特质: {}\n喜爱: {}、{}、{}天",
+            traits.join("、"),
+            personality.favourite_material.label(),
+            personality.favourite_food.label(),
+            personality.favourite_weather.label(),
+        );
+
+        // 影响最大的几条想法
+        let mut recent: Vec<&Thought> = thoughts.iter().collect();
+        recent.sort_by(|a, b| b.current_strength().abs().total_cmp(&a.current_strength().abs()));
+        let mut thought_lines: Vec<String> = recent
+            .iter()
+            .take(5)
+            .map(|t| format!("{:+.0} {}", t.current_strength(), t.kind.label()))
+            .collect();
+        if thought_lines.is_empty() {
+            thought_lines.push("暂无".to_string());
+        }
+
+        // 最亲近（或最敌对）的几段关系
+        let mut relationship_lines: Vec<String> = relationships
+            .closest(4)
+            .into_iter()
+            .map(|(name, r)| format!("{}: {} ({:+.0})", name, r.label(), r.affinity))
+            .collect();
+        if relationship_lines.is_empty() {
+            relationship_lines.push("暂无".to_string());
+        }
+
+        // 各栏位装备与随身携带的资源
+        let mut equipment_lines: Vec<String> = EquipSlot::ALL
+            .iter()
+            .map(|slot| {
+                format!(
+                    "{}: {}",
+                    slot.label(),
+                    equipment.slot(*slot).map(|item| item.name()).unwrap_or_else(|| "无".to_string())
+                )
+            })
+            .collect();
+        equipment_lines.push(format!(
+            "携带 ({}/{}): {}",
+            equipment.cargo.total(),
+            equipment.carry_capacity(),
+            equipment.cargo.summary()
+        ));
+
+        // 技能等级与升级进度（只列出练过的技能）
+        let mut skill_lines: Vec<String> = SkillKind::ALL
+            .iter()
+            .filter(|kind| skills.level(**kind) > 0 || skills.xp(**kind) > 0)
+            .map(|kind| match skills.xp_to_next(*kind) {
+                Some(needed) => format!(
+                    "{} {}级 ({}/{})",
+                    kind.label(),
+                    skills.level(*kind),
+                    skills.xp(*kind),
+                    needed
+                ),
+                None => format!("{} {}级 (满级)", kind.label(), skills.level(*kind)),
+            })
+            .collect();
+        if skill_lines.is_empty() {
+            skill_lines.push("无".to_string());
+        }
+        let disabled: Vec<&str> = SkillKind::ALL
+            .iter()
+            .filter(|kind| !labours.allows(**kind))
+            .map(|kind| kind.label())
+            .collect();
+        if !disabled.is_empty() {
+            skill_lines.push(format!("禁止劳动: {}", disabled.join("、")));
+        }
+
         **text = format!(
-            "姓名: {}\n位置: ({}, {})\n\n━━━ 状态 ━━━\n健康: {:.0}% ({})\n饥饿: {:.0}% ({})\n快乐: {:.0}% ({})\n\n━━━ 任务 ━━━\n{}\n{}",
+            "姓名: {}\n{}（{}）\n绰号: {}\n年龄: {} 岁{}，生于{}\n外貌: {}\n位置: ({}, {})\n\n━━━ 状态 ━━━\n健康: {:.0}% ({})\n饥饿: {:.0}% ({})\n口渴: {:.0}% ({})\n疲劳: {:.0}% ({})，{}\n心情: {:.0} ({})\n\n━━━ 性格 ━━━\n{}\n\n━━━ 想法 ━━━\n{}\n\n━━━ 关系 ━━━\n{}\n\n━━━ 装备 ━━━\n{}\n\n━━━ 技能 ━━━\n{}\n\n━━━ 任务 ━━━\n{}\n{}",
             dwarf.name,
+            identity.culture.label(),
+            identity.family_meaning,
+            identity.epithet,
+            age.years(),
+            if age.is_child() { " (儿童)" } else { "" },
+            identity.birth_date_label(),
+            identity.appearance.join("，"),
             pos.x,
             pos.y,
             dwarf.health,
             health_status,
             dwarf.hunger,
             hunger_status,
+            dwarf.thirst,
+            thirst_status,
+            dwarf.fatigue,
+            fatigue_status,
+            shift.label(),
             dwarf.happiness,
-            happiness_status,
+            mood_status,
+            personality_text,
+            thought_lines.join("\n"),
+            relationship_lines.join("\n"),
+            equipment_lines.join("\n"),
+            skill_lines.join("\n"),
             task_name,
             task_detail,
         );
@@ -262,16 +470,27 @@ pub fn update_dwarf_panel(
 
 /// 更新工作指示器
 pub fn update_work_indicators(
-    dwarves: Query<(&WorkState, &Children), With<Dwarf>>,
+    time: Res<Time>,
+    dwarves: Query<(&WorkState, &GridPosition, &Children, Has<PathRequest>), With<Dwarf>>,
     mut indicators: Query<&mut Sprite, With<WorkIndicator>>,
 ) {
-    for (work_state, children) in dwarves.iter() {
+    for (work_state, pos, children, thinking) in dwarves.iter() {
         for child in children.iter() {
             if let Ok(mut sprite) = indicators.get_mut(child) {
                 // 根据任务类型和进度改变颜色和透明度
                 sprite.color = match &work_state.current_task {
+                    _ if thinking => Color::srgba(0.8, 0.5, 1.0, 0.8), // 紫色 = 思考中（等待寻路）
+                    _ if super::is_asleep(work_state, (pos.x, pos.y)) => {
+                        // 靛蓝色缓慢明灭 = 睡着了
+                        let alpha = 0.55 + 0.35 * (time.elapsed_secs() * 1.5).sin();
+                        Color::srgba(0.3, 0.25, 0.85, alpha)
+                    }
                     Some(Task::Idle) => Color::srgba(0.5, 0.5, 0.5, 0.6), // 灰色 = 空闲
                     Some(Task::Wandering(_)) => Color::srgba(0.7, 0.7, 1.0, 0.5), // 淡蓝色 = 闲逛
+                    Some(Task::Rally(_)) => Color::srgba(1.0, 0.9, 0.2, 0.8), // 金色 = 集合
+                    Some(Task::Hauling(_)) => Color::srgba(0.6, 0.4, 0.2, 0.8), // 棕色 = 搬运
+                    Some(Task::Stand(_)) => Color::srgba(0.4, 0.6, 0.9, 0.8), // 钢蓝色 = 待命
+                    Some(Task::Attack(_)) => Color::srgba(1.0, 0.1, 0.1, 0.9), // 红色 = 战斗
                     Some(Task::Gathering(_)) => {
                         // 绿色，透明度随进度变化
                         let alpha = 0.5 + work_state.work_progress * 0.5;
@@ -282,6 +501,23 @@ pub fn update_work_indicators(
                         let alpha = 0.5 + work_state.work_progress * 0.5;
                         Color::srgba(1.0, 0.5, 0.0, alpha)
                     }
+                    Some(Task::Building(_, _)) => {
+                        // 紫色，透明度随进度变化
+                        let alpha = 0.5 + work_state.work_progress * 0.5;
+                        Color::srgba(0.8, 0.5, 1.0, alpha)
+                    }
+                    Some(Task::Crafting(..)) => {
+                        // 黄褐色，透明度随进度变化
+                        let alpha = 0.5 + work_state.work_progress * 0.5;
+                        Color::srgba(0.9, 0.7, 0.3, alpha)
+                    }
+                    Some(Task::Fishing(_)) => {
+                        // 青色，透明度随进度变化
+                        let alpha = 0.5 + work_state.work_progress * 0.5;
+                        Color::srgba(0.2, 0.8, 0.9, alpha)
+                    }
+                    Some(Task::PickUp(_)) => Color::srgba(0.8, 0.6, 0.3, 0.8), // 浅棕色 = 捡拾
+                    Some(Task::Sleep(_)) => Color::srgba(0.2, 0.2, 0.6, 0.7), // 深蓝色 = 走去睡觉
                     _ => Color::srgba(1.0, 1.0, 1.0, 0.6),
                 };
             }
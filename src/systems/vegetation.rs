@@ -0,0 +1,364 @@
+use crate::components::*;
+use crate::resources::*;
+use crate::world::{WORLD_HEIGHT, WORLD_WIDTH};
+use crate::world_map_data::{WorldAtlas, WorldBiome};
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+/// 成年树木每小时向周围播下一棵树苗的概率（春天、土壤完全湿润时）
+const SEEDING_CHANCE: f64 = 0.004;
+/// 树苗落地离母树的最远距离
+const SEEDING_RADIUS: i32 = 2;
+/// 落点周围 3×3 范围内的树木和树苗达到该数量时太挤，长不出树苗
+const CROWDED_TREES: usize = 5;
+/// 树苗长成树木所需的生长小时数（冬天不长，约两个季节）
+const SAPLING_GROWTH_HOURS: u32 = DAYS_PER_SEASON * 24 * 2;
+/// 泥土每小时重新长草的概率（春天、土壤完全湿润、四周都是草时）
+const GRASS_REGROWTH_CHANCE: f64 = 0.02;
+/// 踩踏次数达到该值的草地和树苗被踩成土路
+const PATH_TRAMPLING: f32 = 15.0;
+/// 踩踏次数高于该值的地块长不出草和树苗
+const TRAMPLED: f32 = 1.0;
+/// 踩踏次数每小时保留的比例
+const TRAMPLING_DECAY: f32 = 0.8;
+/// 水边几格以内的土壤更湿润
+const WATER_REACH: i32 = 2;
+/// 水边土壤增加的湿度
+const WATER_MOISTURE_BONUS: f64 = 0.4;
+/// 离线补算植被时最多补算的小时数（两年）
+const MAX_CATCH_UP_HOURS: u32 = DAYS_PER_YEAR * 24 * 2;
+
+/// 各季节的生长速度：春天最旺，冬天停止生长
+fn season_growth(season: Season) -> f64 {
+    match season {
+        Season::Spring => 1.0,
+        Season::Summer => 0.7,
+        Season::Autumn => 0.3,
+        Season::Winter => 0.0,
+    }
+}
+
+/// 一张地图的植被环境
+struct VegetationSite {
+    biome: Option<WorldBiome>,
+    /// 世界地图格子的湿度（-1 到 1）
+    moisture: f32,
+    /// 摆着家具、作坊或建造订单的地块，不长植被
+    blocked: HashSet<(i32, i32)>,
+}
+
+impl VegetationSite {
+    fn new(map_registry: &GeneratedMapsRegistry, world_atlas: &WorldAtlas, coord: IVec2) -> Self {
+        let cell = world_atlas.cell_at(coord);
+        let active = Some(coord);
+        let blocked = map_registry
+            .local_furniture(active)
+            .iter()
+            .map(|item| item.pos)
+            .chain(map_registry.local_workshops(active).iter().map(|workshop| workshop.pos))
+            .chain(map_registry.local_build_orders(active).iter().map(|order| order.pos))
+            .collect();
+        Self {
+            biome: cell.map(|cell| cell.biome),
+            moisture: cell.map_or(0.0, |cell| cell.moisture),
+            blocked,
+        }
+    }
+}
+
+/// 按坐标查找地块在存储数组中的位置
+fn tile_index(tiles: &[StoredMapTile]) -> Vec<Option<usize>> {
+    let mut index = vec![None; (WORLD_WIDTH * WORLD_HEIGHT) as usize];
+    for (i, tile) in tiles.iter().enumerate() {
+        if let Some(slot) = grid_slot(tile.x, tile.y) {
+            index[slot] = Some(i);
+        }
+    }
+    index
+}
+
+fn grid_slot(x: i32, y: i32) -> Option<usize> {
+    ((0..WORLD_WIDTH).contains(&x) && (0..WORLD_HEIGHT).contains(&y)).then(|| (y * WORLD_WIDTH + x) as usize)
+}
+
+/// 每个地块的土壤湿度（0 到 1）：取世界地图格子的湿度，水边更湿润
+fn soil_moisture(tiles: &[StoredMapTile], index: &[Option<usize>], base: f32) -> Vec<f64> {
+    let base = ((base as f64 + 1.0) / 2.0).clamp(0.0, 1.0);
+    let mut moisture = vec![base; tiles.len()];
+    for tile in tiles.iter().filter(|tile| tile.terrain_type == TerrainType::Water) {
+        for dx in -WATER_REACH..=WATER_REACH {
+            for dy in -WATER_REACH..=WATER_REACH {
+                if let Some(i) = grid_slot(tile.x + dx, tile.y + dy).and_then(|slot| index[slot]) {
+                    moisture[i] = (base + WATER_MOISTURE_BONUS).min(1.0);
+                }
+            }
+        }
+    }
+    moisture
+}
+
+/// 让一张地图的植被生长若干小时，返回变化的地块数：
+/// 成年树木在湿润的季节向周围不太拥挤的草地或泥土播下树苗，树苗生长够了长成树木，
+/// 没人踩的泥土按四周的草重新长草。建有建筑结构、摆着东西或刚被踩过的地块不受影响
+fn grow_vegetation(
+    tiles: &mut [StoredMapTile],
+    site: &VegetationSite,
+    trampling: &HashMap<(i32, i32), f32>,
+    start_hour: u32,
+    hours: u32,
+    rng: &mut impl Rng,
+) -> usize {
+    let index = tile_index(tiles);
+    let moisture = soil_moisture(tiles, &index, site.moisture);
+    let at = |x: i32, y: i32| grid_slot(x, y).and_then(|slot| index[slot]);
+    let untouched = |tile: &StoredMapTile| {
+        let pos = (tile.x, tile.y);
+        tile.construction.is_none()
+            && !site.blocked.contains(&pos)
+            && trampling.get(&pos).is_none_or(|steps| *steps < TRAMPLED)
+    };
+
+    let mut changed = 0;
+    for hour in start_hour..start_hour + hours {
+        let growth = season_growth(Season::of_day(hour / 24));
+        if growth <= 0.0 {
+            continue;
+        }
+        for i in 0..tiles.len() {
+            if !untouched(&tiles[i]) {
+                continue;
+            }
+            let (x, y) = (tiles[i].x, tiles[i].y);
+            match tiles[i].terrain_type {
+                TerrainType::Sapling => {
+                    tiles[i].growth_hours += 1;
+                    if tiles[i].growth_hours >= SAPLING_GROWTH_HOURS {
+                        tiles[i].set_vegetation(TerrainType::Tree, site.biome, rng);
+                        changed += 1;
+                    }
+                }
+                TerrainType::Tree => {
+                    if !rng.gen_bool((SEEDING_CHANCE * growth * moisture[i]).min(1.0)) {
+                        continue;
+                    }
+                    let dx = rng.gen_range(-SEEDING_RADIUS..=SEEDING_RADIUS);
+                    let dy = rng.gen_range(-SEEDING_RADIUS..=SEEDING_RADIUS);
+                    let Some(target) = at(x + dx, y + dy) else {
+                        continue;
+                    };
+                    let (tx, ty) = (tiles[target].x, tiles[target].y);
+                    let crowd = (-1..=1)
+                        .flat_map(|cx| (-1..=1).map(move |cy| (tx + cx, ty + cy)))
+                        .filter_map(|(nx, ny)| at(nx, ny))
+                        .filter(|j| matches!(tiles[*j].terrain_type, TerrainType::Tree | TerrainType::Sapling))
+                        .count();
+                    if matches!(tiles[target].terrain_type, TerrainType::Grass | TerrainType::Dirt)
+                        && untouched(&tiles[target])
+                        && crowd < CROWDED_TREES
+                    {
+                        tiles[target].set_vegetation(TerrainType::Sapling, site.biome, rng);
+                        changed += 1;
+                    }
+                }
+                TerrainType::Dirt => {
+                    let grass = [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                        .into_iter()
+                        .filter_map(|(nx, ny)| at(nx, ny))
+                        .filter(|j| tiles[*j].terrain_type == TerrainType::Grass)
+                        .count();
+                    let chance = GRASS_REGROWTH_CHANCE * growth * moisture[i] * (grass + 1) as f64 / 5.0;
+                    if rng.gen_bool(chance.min(1.0)) {
+                        tiles[i].set_vegetation(TerrainType::Grass, site.biome, rng);
+                        changed += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    changed
+}
+
+/// 把一张地图的植被补算到 `now`（`GameTime::total_hours`），返回变化的地块数；
+/// 第一次见到的地图只记下时间。当前地图和离线地图共用
+pub fn advance_vegetation(
+    map_registry: &mut GeneratedMapsRegistry,
+    world_atlas: &WorldAtlas,
+    coord: IVec2,
+    now: u32,
+    trampling: &HashMap<(i32, i32), f32>,
+    rng: &mut impl Rng,
+) -> usize {
+    let Some(since) = map_registry.vegetation_clock.insert(coord, now) else {
+        return 0;
+    };
+    let hours = now.saturating_sub(since).min(MAX_CATCH_UP_HOURS);
+    if hours == 0 {
+        return 0;
+    }
+    let site = VegetationSite::new(map_registry, world_atlas, coord);
+    let Some(tiles) = map_registry.maps.get_mut(&coord) else {
+        return 0;
+    };
+    grow_vegetation(tiles, &site, trampling, now - hours, hours, rng)
+}
+
+/// 砍倒树木：地块变成泥土
+pub fn fell_tree(
+    map_registry: &mut GeneratedMapsRegistry,
+    world_atlas: &WorldAtlas,
+    coord: IVec2,
+    pos: (i32, i32),
+    rng: &mut impl Rng,
+) {
+    let biome = world_atlas.cell_at(coord).map(|cell| cell.biome);
+    let Some(tile) = map_registry
+        .maps
+        .get_mut(&coord)
+        .and_then(|tiles| tiles.iter_mut().find(|tile| (tile.x, tile.y) == pos))
+    else {
+        return;
+    };
+    if tile.terrain_type == TerrainType::Tree && tile.construction.is_none() {
+        tile.set_vegetation(TerrainType::Dirt, biome, rng);
+    }
+}
+
+/// 踩踏：矮人每走进一个地块记一次踩踏；换了地图就重新计数
+pub fn trampling_system(
+    active_local: Res<ActiveLocalMap>,
+    dwarves: Query<(Entity, &GridPosition), With<Dwarf>>,
+    mut trampling: ResMut<Trampling>,
+    mut last_tiles: Local<HashMap<Entity, (i32, i32)>>,
+) {
+    if active_local.is_changed() {
+        trampling.steps.clear();
+        last_tiles.clear();
+    }
+    last_tiles.retain(|entity, _| dwarves.contains(*entity));
+    for (entity, pos) in dwarves.iter() {
+        let here = (pos.x, pos.y);
+        if last_tiles.insert(entity, here).is_some_and(|previous| previous != here) {
+            *trampling.steps.entry(here).or_default() += 1.0;
+        }
+    }
+}
+
+/// 植被：每个游戏小时把被踩得多的草地和树苗踩成土路，再让当前地图的植被生长，踩踏次数随后衰减
+pub fn vegetation_system(
+    game_time: Res<GameTime>,
+    world_atlas: Res<WorldAtlas>,
+    active_local: Res<ActiveLocalMap>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut trampling: ResMut<Trampling>,
+    mut last_hour: Local<Option<u32>>,
+) {
+    let now = game_time.total_hours();
+    if *last_hour == Some(now) {
+        return;
+    }
+    let first_run = last_hour.is_none();
+    *last_hour = Some(now);
+    let Some(coord) = active_local.coord else {
+        return;
+    };
+    if first_run {
+        map_registry.vegetation_clock.entry(coord).or_insert(now);
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let biome = world_atlas.cell_at(coord).map(|cell| cell.biome);
+    if let Some(tiles) = map_registry.maps.get_mut(&coord) {
+        for tile in tiles.iter_mut() {
+            let steps = trampling.steps.get(&(tile.x, tile.y)).copied().unwrap_or(0.0);
+            if steps >= PATH_TRAMPLING
+                && tile.construction.is_none()
+                && matches!(tile.terrain_type, TerrainType::Grass | TerrainType::Sapling)
+            {
+                tile.set_vegetation(TerrainType::Dirt, biome, &mut rng);
+            }
+        }
+    }
+    advance_vegetation(&mut map_registry, &world_atlas, coord, now, &trampling.steps, &mut rng);
+
+    for steps in trampling.steps.values_mut() {
+        *steps *= TRAMPLING_DECAY;
+    }
+    trampling.steps.retain(|_, steps| *steps >= 0.1);
+}
+
+/// 注册表中的植被变化同步到地图上的地形实体：更新地形组件（导航系统据此更新移动代价）、
+/// 底色和字符，长成的树开始随风摇晃，砍倒的树停下
+#[allow(clippy::type_complexity)]
+pub fn vegetation_sync_system(
+    mut commands: Commands,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    mut terrain_query: Query<(&GridPosition, &mut Terrain, &mut Sprite)>,
+    mut glyph_query: Query<
+        (
+            Entity,
+            &GridPosition,
+            &mut AsciiChar,
+            &mut Text2d,
+            &mut TextColor,
+            &mut Transform,
+            Option<&TreeSway>,
+        ),
+        Without<Hostile>,
+    >,
+) {
+    if !map_registry.is_changed() {
+        return;
+    }
+    let Some(tiles) = active_local.coord.and_then(|coord| map_registry.maps.get(&coord)) else {
+        return;
+    };
+    let stored: HashMap<(i32, i32), &StoredMapTile> = tiles
+        .iter()
+        .filter(|tile| tile.construction.is_none())
+        .map(|tile| ((tile.x, tile.y), tile))
+        .collect();
+
+    let mut changed: HashSet<(i32, i32)> = HashSet::new();
+    for (pos, mut terrain, mut sprite) in terrain_query.iter_mut() {
+        let Some(tile) = stored.get(&(pos.x, pos.y)) else {
+            continue;
+        };
+        if terrain.terrain_type == tile.terrain_type || terrain.construction.is_some() {
+            continue;
+        }
+        terrain.terrain_type = tile.terrain_type;
+        terrain.walkable = tile.walkable;
+        sprite.color = tile.color;
+        changed.insert((pos.x, pos.y));
+    }
+    if changed.is_empty() {
+        return;
+    }
+
+    for (entity, pos, mut ascii, mut text, mut color, mut transform, sway) in glyph_query.iter_mut() {
+        if !changed.contains(&(pos.x, pos.y)) {
+            continue;
+        }
+        let tile = stored[&(pos.x, pos.y)];
+        ascii.character = tile.ascii_char;
+        *text = Text2d::new(tile.ascii_char.to_string());
+        color.0 = tile.char_color;
+        match (sway, tile.has_tree_sway) {
+            (Some(sway), false) => {
+                transform.translation.x = sway.base_x;
+                commands.entity(entity).remove::<TreeSway>();
+            }
+            (None, true) => {
+                commands.entity(entity).insert(TreeSway {
+                    offset: tile.tree_offset,
+                    base_x: transform.translation.x,
+                });
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,16 +1,215 @@
 use crate::components::*;
-use crate::pathfinding::{find_path, simplify_path};
+use crate::connectivity::RegionMap;
+use crate::pathfinding::{simplify_path, NavGrid};
 use crate::resources::*;
 use crate::world::*;
 use crate::debug_entity;
+use super::PendingPath;
 use bevy::prelude::*;
 use rand::Rng;
 
+/// 距集合点多少格以内算作到达（中心格可能已被先到的矮人占据）
+const RALLY_ARRIVAL_RADIUS: i32 = 2;
+/// 完成一次采集/挖矿获得的技能经验
+const XP_PER_JOB: u32 = 25;
+/// 搬运途中超过该时间（秒）仍未到达仓库则放弃本次搬运
+const HAULING_TIMEOUT: f32 = 60.0;
+/// 健康低于该值的矮人闲逛时会去医院休养
+const HOSPITAL_HEALTH_THRESHOLD: f32 = 60.0;
+/// 闲逛时前往会客区的概率
+const MEETING_AREA_CHANCE: f64 = 0.4;
+/// 牧场内农耕目标的评分倍数
+const PASTURE_SCORE_BONUS: f32 = 2.0;
+/// 建造订单的基础评分（与地形评分相当）
+const BUILD_ORDER_SCORE: f32 = 3.0;
+/// 作坊工作的基础评分（与建造订单相当）
+const WORKSHOP_SCORE: f32 = 3.0;
+/// 钓鱼的基础评分（鱼比野果难得，略高于草地）
+const FISHING_SCORE: f32 = 2.0;
+/// 捡拾散落食物的基础评分（鱼放不了多久，要尽快捡回去）
+const PICK_UP_SCORE: f32 = 3.0;
+/// 饥饿度达到该值后闲逛时先去餐厅
+const DINING_HALL_HUNGER: f32 = 55.0;
+/// 建造一件家具或一处建筑结构的基础速度（每秒进度）
+const BUILD_SPEED: f32 = 0.2;
+/// 砍倒一棵树得到的木材是普通采集的几倍（树砍倒后就没了）
+const FELLED_TREE_WOOD: u32 = 3;
+/// 收获谷物时留下一粒新种子的概率
+const SEED_RETURN_CHANCE: f64 = 0.5;
+/// 建造技能达到该等级的工匠才可能造出传世之作
+const ARTIFACT_MIN_LEVEL: u32 = 8;
+/// 大师级工匠造一件家具时造出传世之作的概率
+const ARTIFACT_CHANCE: f64 = 0.1;
+/// 追击时敌人离上次寻路的终点超过多少格才重新寻路
+const CHASE_REPATH_DISTANCE: i32 = 2;
+
+/// 路径状态
+enum PathStatus {
+    /// 已有可用路径
+    Ready,
+    /// 等待异步寻路结果（思考中）
+    Waiting,
+    /// 目标不可达
+    Unreachable,
+}
+
+/// 矮人身上的寻路组件：已完成的结果、排队中的请求，以及是否正在任务池中计算
+#[derive(Clone, Copy)]
+struct PathComponents<'a> {
+    result: Option<&'a PathResult>,
+    queued: Option<&'a PathRequest>,
+    in_flight: bool,
+}
+
+/// 读取异步寻路结果，需要新路径或路径过期时提交寻路请求；
+/// 目标改变时直接覆盖还在排队的旧请求，已在计算的请求等结果回来后作为过期结果丢弃
+fn poll_path(
+    commands: &mut Commands,
+    entity: Entity,
+    work_state: &mut WorkState,
+    current_pos: (i32, i32),
+    target_pos: (i32, i32),
+    paths: PathComponents,
+) -> PathStatus {
+    if let Some(result) = paths.result {
+        commands.entity(entity).remove::<PathResult>();
+        // 目标已改变的过期结果直接丢弃
+        if result.goal == target_pos {
+            let Some(path) = &result.path else {
+                return PathStatus::Unreachable;
+            };
+            // 请求发出后矮人可能已沿旧路径前进，跳过已经走过的部分
+            let mut path = path.clone();
+            if let Some(index) = path.iter().position(|step| *step == current_pos) {
+                path.drain(..=index);
+            }
+            work_state.cached_path = simplify_path(path);
+            work_state.path_index = 0;
+            work_state.path_recalc_timer = 0.0;
+            return PathStatus::Ready;
+        }
+    }
+
+    let needs_path = work_state.cached_path.is_empty()
+        || work_state.path_index >= work_state.cached_path.len();
+    let stale_request = paths.queued.is_some_and(|request| request.goal != target_pos);
+    let thinking = paths.in_flight || (paths.queued.is_some() && !stale_request);
+    // 每5秒重新计算一次，计算期间继续沿旧路径移动
+    if (needs_path || work_state.path_recalc_timer > 5.0 || stale_request) && !thinking {
+        commands.entity(entity).insert(PathRequest {
+            start: current_pos,
+            goal: target_pos,
+        });
+        work_state.path_recalc_timer = 0.0;
+    }
+
+    if needs_path {
+        PathStatus::Waiting
+    } else {
+        PathStatus::Ready
+    }
+}
+
+/// 沿缓存的路径朝下一个路径点设置速度，到达路径点后前进到下一个
+fn steer_along_path(work_state: &mut WorkState, current_pos: (i32, i32), velocity: &mut Velocity) {
+    if work_state.cached_path.is_empty() || work_state.path_index >= work_state.cached_path.len() {
+        velocity.x = 0.0;
+        velocity.y = 0.0;
+        return;
+    }
+
+    let mut next_waypoint = work_state.cached_path[work_state.path_index];
+    if next_waypoint == current_pos {
+        work_state.path_index += 1;
+        if work_state.path_index >= work_state.cached_path.len() {
+            work_state.cached_path.clear();
+            work_state.path_index = 0;
+            return;
+        }
+        next_waypoint = work_state.cached_path[work_state.path_index];
+    }
+
+    // 标准化方向，支持简化路径的非相邻点
+    let dx = next_waypoint.0 - current_pos.0;
+    let dy = next_waypoint.1 - current_pos.1;
+    let distance = ((dx * dx + dy * dy) as f32).sqrt();
+    if distance > 0.01 {
+        velocity.x = (dx as f32 / distance).round();
+        velocity.y = (dy as f32 / distance).round();
+    } else {
+        velocity.x = 0.0;
+        velocity.y = 0.0;
+    }
+}
+
+/// 建造时的站位：家具和作坊站在订单地块上摆放；建筑结构从相邻的地块施工，
+/// 免得被自己砌的墙困住，也才能给水面架桥。按固定顺序取第一个与矮人连通的相邻地块，
+/// 保证站位稳定，不会边走边换寻路目标（途经其他相邻地块时也能直接开工，见 `in_build_reach`）
+fn build_approach(
+    building: BuildingType,
+    site: (i32, i32),
+    from: (i32, i32),
+    nav_grid: &NavGrid,
+    region_map: &RegionMap,
+) -> Option<(i32, i32)> {
+    match building {
+        BuildingType::Construction(_) => adjacent_approach(site, from, nav_grid, region_map),
+        _ => region_map.connected(from, site).then_some(site),
+    }
+}
+
+/// 从相邻地块干活（砌墙架桥、站在岸边钓鱼）时的站位：
+/// 按固定顺序取第一个可行走且与矮人连通的相邻地块
+fn adjacent_approach(
+    site: (i32, i32),
+    from: (i32, i32),
+    nav_grid: &NavGrid,
+    region_map: &RegionMap,
+) -> Option<(i32, i32)> {
+    let (x, y) = site;
+    [(x, y - 1), (x - 1, y), (x + 1, y), (x, y + 1)]
+        .into_iter()
+        .find(|tile| nav_grid.is_walkable(tile.0, tile.1) && region_map.connected(from, *tile))
+}
+
+/// 矮人是否站在能施工的位置上
+fn in_build_reach(building: BuildingType, pos: (i32, i32), site: (i32, i32)) -> bool {
+    let distance = (pos.0 - site.0).abs() + (pos.1 - site.1).abs();
+    match building {
+        BuildingType::Construction(_) => distance == 1,
+        _ => distance == 0,
+    }
+}
+
 /// 矮人工作系统 - 优化版，智能目标选择和持续工作
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn dwarf_work_system(
+    mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(&mut WorkState, &GridPosition, &mut Velocity, &Dwarf)>,
+    mut query: Query<(
+        Entity,
+        &Dwarf,
+        &mut WorkState,
+        &GridPosition,
+        &mut Velocity,
+        &Skills,
+        &Labours,
+        &Age,
+        &Equipment,
+        Option<&PathResult>,
+        Option<&PathRequest>,
+        Has<PendingPath>,
+        Has<FollowFlowField>,
+    )>,
     terrain_query: Query<(&GridPosition, &Terrain)>,
+    hostiles: Query<&GridPosition, With<Hostile>>,
+    region_map: Res<RegionMap>,
+    nav_grid: Res<NavGrid>,
+    inventory: Res<GlobalInventory>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    rooms: Res<Rooms>,
+    mut reservations: ResMut<TileReservations>,
 ) {
     // 如果时间暂停,AI不做决策
     if time.delta_secs() <= 0.0001 {
@@ -19,24 +218,112 @@ pub fn dwarf_work_system(
 
     let mut rng = rand::thread_rng();
 
-    for (mut work_state, pos, mut velocity, _dwarf) in query.iter_mut() {
+    reservations.retain_holders(|entity| query.contains(entity));
+    // 仓库里还有工具，或者石头够打制一把石制工具
+    let tools_in_stock = inventory.tools > 0 || inventory.stone >= STONE_TOOL_COST;
+
+    let zones = map_registry.local_zones(active_local.coord);
+
+    for (entity, dwarf, mut work_state, pos, mut velocity, skills, labours, age, equipment, path_result, queued_path, in_flight, following) in query.iter_mut() {
+        let paths = PathComponents {
+            result: path_result,
+            queued: queued_path,
+            in_flight,
+        };
         // 更新计时器
         work_state.path_recalc_timer += time.delta_secs();
         work_state.task_cooldown -= time.delta_secs();
         work_state.task_duration += time.delta_secs();
 
+        // 任务被改派（如玩家右键指挥）后不再跟随集合/搬运/军事流场
+        if following
+            && !matches!(
+                work_state.current_task,
+                Some(Task::Rally(_)) | Some(Task::Hauling(_)) | Some(Task::Stand(_))
+            )
+        {
+            commands.entity(entity).remove::<FollowFlowField>();
+        }
+
+        // 同步工作目标的预约：任务完成、取消或改派后释放；
+        // 目标被玩家指派给了其他矮人时放弃当前任务
+        match &work_state.current_task {
+            Some(Task::Gathering(target))
+            | Some(Task::Mining(target))
+            | Some(Task::Building(target, _))
+            | Some(Task::Crafting(target, _))
+            | Some(Task::Fishing(target))
+            | Some(Task::PickUp(target)) => {
+                let target_pos = (target.x, target.y);
+                if reservations.is_reserved_by_other(target_pos, entity) {
+                    debug_entity!("工作目标 {:?} 已被其他矮人预约，放弃", target_pos);
+                    velocity.x = 0.0;
+                    velocity.y = 0.0;
+                    work_state.current_task = Some(Task::Idle);
+                    work_state.cached_path.clear();
+                    work_state.path_index = 0;
+                    work_state.work_progress = 0.0;
+                    work_state.task_cooldown = 0.5;
+                    work_state.task_duration = 0.0;
+                    reservations.release(entity);
+                    continue;
+                }
+                if !reservations.holds(entity, target_pos) {
+                    reservations.reserve(target_pos, entity);
+                }
+            }
+            _ => reservations.release(entity),
+        }
+
         match &work_state.current_task {
             Some(Task::Idle) => {
                 // 空闲状态：30%概率寻找工作，70%概率闲逛
                 if work_state.task_cooldown <= 0.0 {
-                    let should_work = rng.gen_ratio(3, 10); // 30%概率工作
-                    let mut candidates: Vec<(GridPosition, TerrainType, f32)> = Vec::new();
+                    let should_work = !age.is_child() && rng.gen_ratio(3, 10); // 30%概率工作，儿童只玩耍
+                    // 洞区成员只在所属洞区内工作和闲逛
+                    let burrow = map_registry.burrow_tiles(active_local.coord, &dwarf.name);
+                    let mut candidates: Vec<(GridPosition, Task, f32)> = Vec::new();
                     
                     if should_work {
                         // 寻找工作目标
                         
                         for (terrain_pos, terrain) in terrain_query.iter() {
-                            if !terrain.walkable {
+                            // 不在同一连通区域的目标不可达，无需寻路；已被其他矮人预约的目标跳过；
+                            // 需要工具的工作只有手上有工具或仓库能提供工具时才考虑（手持武器的矮人不换工具）
+                            let labour = terrain.terrain_type.labour();
+                            // 水面：站在连通的岸边钓鱼，鱼被钓光的地块不再考虑
+                            if terrain.terrain_type == TerrainType::Water {
+                                if terrain.construction.is_some()
+                                    || !labours.allows(SkillKind::Fishing)
+                                    || !map_registry.local_has_fish(active_local.coord)
+                                    || reservations.is_reserved_by_other((terrain_pos.x, terrain_pos.y), entity)
+                                {
+                                    continue;
+                                }
+                                let Some(shore) = adjacent_approach((terrain_pos.x, terrain_pos.y), (pos.x, pos.y), &nav_grid, &region_map) else {
+                                    continue;
+                                };
+                                if burrow.as_ref().is_some_and(|tiles| !tiles.contains(&shore)) {
+                                    continue;
+                                }
+                                let dx = (shore.0 - pos.x).abs();
+                                let dy = (shore.1 - pos.y).abs();
+                                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                                if distance <= 20.0 {
+                                    let score = FISHING_SCORE * skills.work_speed(SkillKind::Fishing) / (distance + 1.0);
+                                    candidates.push((terrain_pos.clone(), Task::Fishing(terrain_pos.clone()), score));
+                                }
+                                continue;
+                            }
+                            if !terrain.walkable
+                                || !terrain.terrain_type.is_harvestable()
+                                || terrain.construction.is_some()
+                                || !region_map.connected((pos.x, pos.y), (terrain_pos.x, terrain_pos.y))
+                                || reservations.is_reserved_by_other((terrain_pos.x, terrain_pos.y), entity)
+                                || !labours.allows(labour)
+                                || !(equipment.has_tool_for(labour) || (tools_in_stock && !equipment.holds_weapon()))
+                                || burrow.as_ref().is_some_and(|tiles| !tiles.contains(&(terrain_pos.x, terrain_pos.y)))
+                            {
                                 continue;
                             }
                             
@@ -53,13 +340,92 @@ pub fn dwarf_work_system(
                                     TerrainType::Stone => 2.5,     // 石头较高优先级
                                     TerrainType::Mountain => 2.0,  // 山脉（如果可走）
                                     TerrainType::Grass => 1.5,     // 草地中等优先级
-                                    TerrainType::Water => 0.5,     // 水域低优先级
+                                    TerrainType::Water => 0.0,     // 水域只能钓鱼，见上
+                                    TerrainType::Dirt | TerrainType::Sapling => 0.0, // 没有可采集的资源
                                 };
                                 
-                                // 综合评分：地形分 * 资源丰富度 / (距离 + 1)
-                                let score = terrain_score * terrain.resource_richness / (distance + 1.0);
+                                // 综合评分：地形分 * 资源丰富度 * 技能速度 / (距离 + 1)，矮人更倾向于擅长的工作
+                                let skill_bonus = skills.work_speed(labour) * equipment.tool_speed(labour);
+                                // 牧场里的农耕目标更受青睐
+                                let zone_bonus = if labour == SkillKind::Farming
+                                    && map_registry.in_zone(active_local.coord, ZoneKind::Pasture, (terrain_pos.x, terrain_pos.y))
+                                {
+                                    PASTURE_SCORE_BONUS
+                                } else {
+                                    1.0
+                                };
+                                let score = terrain_score * terrain.resource_richness * skill_bonus * zone_bonus / (distance + 1.0);
                                 
-                                candidates.push((terrain_pos.clone(), terrain.terrain_type, score));
+                                let task = match terrain.terrain_type {
+                                    TerrainType::Stone | TerrainType::Mountain => Task::Mining(terrain_pos.clone()),
+                                    _ => Task::Gathering(terrain_pos.clone()),
+                                };
+                                candidates.push((terrain_pos.clone(), task, score));
+                            }
+                        }
+
+                        // 建造订单：库存材料够用时才考虑
+                        if labours.allows(SkillKind::Building) {
+                            for order in map_registry.local_build_orders(active_local.coord) {
+                                if build_approach(order.building, order.pos, (pos.x, pos.y), &nav_grid, &region_map).is_none()
+                                    || reservations.is_reserved_by_other(order.pos, entity)
+                                    || inventory.material_stock(order.material) < order.building.material_cost()
+                                    || burrow.as_ref().is_some_and(|tiles| !tiles.contains(&order.pos))
+                                {
+                                    continue;
+                                }
+                                let dx = (order.pos.0 - pos.x).abs();
+                                let dy = (order.pos.1 - pos.y).abs();
+                                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                                if distance <= 20.0 {
+                                    let score = BUILD_ORDER_SCORE * skills.work_speed(SkillKind::Building) / (distance + 1.0);
+                                    let target = GridPosition { x: order.pos.0, y: order.pos.1 };
+                                    candidates.push((target.clone(), Task::Building(target, order.building), score));
+                                }
+                            }
+                        }
+
+                        // 作坊：有原料、且库存的饭菜或酒还不够时去烹饪或酿酒
+                        if labours.allows(SkillKind::Crafting) {
+                            for workshop in map_registry.local_workshops(active_local.coord) {
+                                if !super::workshop_has_job(workshop.kind, &inventory)
+                                    || !region_map.connected((pos.x, pos.y), workshop.pos)
+                                    || reservations.is_reserved_by_other(workshop.pos, entity)
+                                    || burrow.as_ref().is_some_and(|tiles| !tiles.contains(&workshop.pos))
+                                {
+                                    continue;
+                                }
+                                let dx = (workshop.pos.0 - pos.x).abs();
+                                let dy = (workshop.pos.1 - pos.y).abs();
+                                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                                if distance <= 20.0 {
+                                    let score = WORKSHOP_SCORE * skills.work_speed(SkillKind::Crafting) / (distance + 1.0);
+                                    let target = GridPosition { x: workshop.pos.0, y: workshop.pos.1 };
+                                    candidates.push((target.clone(), Task::Crafting(target, workshop.kind), score));
+                                }
+                            }
+                        }
+
+                        // 散落的食物和材料：随身还装得下时捡回仓库，免得食物烂在地上
+                        if labours.allows(SkillKind::Hauling) && equipment.free_capacity() > 0 {
+                            let loose_food = map_registry.local_loose_food(active_local.coord).iter().map(|food| food.pos);
+                            let loose_materials =
+                                map_registry.local_loose_materials(active_local.coord).iter().map(|material| material.pos);
+                            for loose_pos in loose_food.chain(loose_materials) {
+                                if !region_map.connected((pos.x, pos.y), loose_pos)
+                                    || reservations.is_reserved_by_other(loose_pos, entity)
+                                    || burrow.as_ref().is_some_and(|tiles| !tiles.contains(&loose_pos))
+                                {
+                                    continue;
+                                }
+                                let dx = (loose_pos.0 - pos.x).abs();
+                                let dy = (loose_pos.1 - pos.y).abs();
+                                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                                if distance <= 20.0 {
+                                    let score = PICK_UP_SCORE * skills.work_speed(SkillKind::Hauling) / (distance + 1.0);
+                                    let target = GridPosition { x: loose_pos.0, y: loose_pos.1 };
+                                    candidates.push((target.clone(), Task::PickUp(target), score));
+                                }
                             }
                         }
                     }
@@ -71,43 +437,17 @@ pub fn dwarf_work_system(
                         // 从前30%的候选中随机选择，增加多样性
                         let top_count = (candidates.len() / 3).max(1);
                         
-                        // 尝试找一个可达的目标（最多尝试5次）
-                        let mut found_reachable = false;
-                        for attempt in 0..5.min(top_count) {
-                            let chosen_idx = if attempt == 0 {
-                                rng.gen_range(0..top_count)
-                            } else {
-                                rng.gen_range(0..candidates.len())
-                            };
-                            
-                            let (target_pos, terrain_type, _) = &candidates[chosen_idx];
-                            let current_pos = (pos.x, pos.y);
-                            let target = (target_pos.x, target_pos.y);
-                            
-                            // 快速路径验证
-                            if let Some(_path) = find_path(current_pos, target, &terrain_query) {
-                                // 路径存在，分配任务
-                                let new_task = match terrain_type {
-                                    TerrainType::Tree | TerrainType::Grass => Task::Gathering(target_pos.clone()),
-                                    TerrainType::Stone | TerrainType::Mountain => Task::Mining(target_pos.clone()),
-                                    _ => Task::Gathering(target_pos.clone()),
-                                };
-                                
-                                debug_entity!("矮人找到工作目标: {:?} at {:?}", terrain_type, target);
-                                work_state.current_task = Some(new_task);
-                                work_state.cached_path.clear();
-                                work_state.path_index = 0;
-                                work_state.task_cooldown = 1.0;
-                                work_state.task_duration = 0.0;
-                                found_reachable = true;
-                                break;
-                            }
-                        }
-                        
-                        if !found_reachable {
-                            debug_entity!("矮人找不到可达的工作目标，开始闲逛");
-                            work_state.task_cooldown = 2.0;
-                        }
+                        // 候选目标都与矮人处于同一连通区域，必然可达，实际路径交给异步寻路
+                        let chosen_idx = rng.gen_range(0..top_count);
+                        let (target_pos, new_task, _) = candidates.swap_remove(chosen_idx);
+
+                        debug_entity!("矮人找到工作目标: {:?}", new_task);
+                        reservations.reserve((target_pos.x, target_pos.y), entity);
+                        work_state.current_task = Some(new_task);
+                        work_state.cached_path.clear();
+                        work_state.path_index = 0;
+                        work_state.task_cooldown = 1.0;
+                        work_state.task_duration = 0.0;
                     } else {
                         // 不寻找工作，开始闲逛
                         debug_entity!("矮人选择闲逛而非工作");
@@ -117,24 +457,47 @@ pub fn dwarf_work_system(
                     if work_state.current_task == Some(Task::Idle) {
                         // 在附近随机选择闲逛目标（5-8格范围）
                         let wander_distance = rng.gen_range(5..=8);
-                        let target_x = (pos.x + rng.gen_range(-wander_distance..=wander_distance))
-                            .clamp(0, WORLD_WIDTH - 1);
-                        let target_y = (pos.y + rng.gen_range(-wander_distance..=wander_distance))
-                            .clamp(0, WORLD_HEIGHT - 1);
-                        
-                        // 检查闲逛目标是否可行走
-                        for (terrain_pos, terrain) in terrain_query.iter() {
-                            if terrain_pos.x == target_x && terrain_pos.y == target_y && terrain.walkable {
-                                work_state.current_task = Some(Task::Wandering(GridPosition {
-                                    x: target_x,
-                                    y: target_y,
-                                }));
-                                work_state.cached_path.clear();
-                                work_state.path_index = 0;
-                                work_state.task_cooldown = 3.0; // 闲逛后3秒再决定下一步
-                                work_state.task_duration = 0.0;
-                                break;
+                        let here = (pos.x, pos.y);
+                        let zone_tiles = |kind: ZoneKind| {
+                            zones.iter().filter(move |zone| zone.kind == kind).flat_map(|zone| zone.tiles.iter())
+                        };
+                        // 受伤的矮人去医院休养，饿了的矮人去餐厅，洞区成员在洞区内闲逛，其他矮人有时去会客区聚会
+                        let zone_target = if dwarf.health < HOSPITAL_HEALTH_THRESHOLD {
+                            random_tile_near(zone_tiles(ZoneKind::Hospital), here, wander_distance, &mut rng)
+                        } else {
+                            None
+                        }
+                        .or_else(|| {
+                            if dwarf.hunger < DINING_HALL_HUNGER {
+                                return None;
                             }
+                            let hall_tiles = rooms.dining_halls().flat_map(|room| room.tiles.iter());
+                            random_tile_near(hall_tiles, here, wander_distance, &mut rng)
+                        })
+                        .or_else(|| match &burrow {
+                            Some(tiles) => random_tile_near(tiles, here, wander_distance, &mut rng),
+                            None if rng.gen_bool(MEETING_AREA_CHANCE) => {
+                                random_tile_near(zone_tiles(ZoneKind::MeetingArea), here, wander_distance, &mut rng)
+                            }
+                            None => None,
+                        });
+                        let (target_x, target_y) = zone_target.unwrap_or_else(|| {
+                            (
+                                (pos.x + rng.gen_range(-wander_distance..=wander_distance)).clamp(0, WORLD_WIDTH - 1),
+                                (pos.y + rng.gen_range(-wander_distance..=wander_distance)).clamp(0, WORLD_HEIGHT - 1),
+                            )
+                        });
+                        
+                        // 检查闲逛目标是否可行走且可达
+                        if region_map.connected((pos.x, pos.y), (target_x, target_y)) {
+                            work_state.current_task = Some(Task::Wandering(GridPosition {
+                                x: target_x,
+                                y: target_y,
+                            }));
+                            work_state.cached_path.clear();
+                            work_state.path_index = 0;
+                            work_state.task_cooldown = 3.0; // 闲逛后3秒再决定下一步
+                            work_state.task_duration = 0.0;
                         }
                     }
                 }
@@ -169,132 +532,252 @@ pub fn dwarf_work_system(
                 }
 
                 // 使用与工作相同的寻路逻辑
-                let need_recalc = work_state.cached_path.is_empty()
-                    || work_state.path_index >= work_state.cached_path.len()
-                    || work_state.path_recalc_timer > 5.0;
-
-                if need_recalc {
-                    match find_path(current_pos, target_pos, &terrain_query) {
-                        Some(path) => {
-                            let simplified = simplify_path(path);
-                            work_state.cached_path = simplified;
-                            work_state.path_index = 0;
-                            work_state.path_recalc_timer = 0.0;
-                        }
-                        None => {
-                            // 找不到路径，放弃闲逛
-                            velocity.x = 0.0;
-                            velocity.y = 0.0;
-                            work_state.current_task = Some(Task::Idle);
-                            work_state.cached_path.clear();
-                            work_state.path_index = 0;
-                            work_state.task_cooldown = 1.0;
-                            work_state.task_duration = 0.0;
-                            continue;
-                        }
+                match poll_path(
+                    &mut commands,
+                    entity,
+                    &mut work_state,
+                    current_pos,
+                    target_pos,
+                    paths,
+                ) {
+                    PathStatus::Ready => {}
+                    PathStatus::Waiting => {
+                        // 思考中，原地等待路径
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                        continue;
+                    }
+                    PathStatus::Unreachable => {
+                        // 找不到路径，放弃闲逛
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                        work_state.current_task = Some(Task::Idle);
+                        work_state.cached_path.clear();
+                        work_state.path_index = 0;
+                        work_state.task_cooldown = 1.0;
+                        work_state.task_duration = 0.0;
+                        continue;
                     }
                 }
 
                 // 沿路径移动
-                if !work_state.cached_path.is_empty()
-                    && work_state.path_index < work_state.cached_path.len()
-                {
-                    let next_waypoint = work_state.cached_path[work_state.path_index];
-
-                    let dx = (current_pos.0 - next_waypoint.0).abs();
-                    let dy = (current_pos.1 - next_waypoint.1).abs();
-                    if dx == 0 && dy == 0 {
-                        work_state.path_index += 1;
-
-                        if work_state.path_index < work_state.cached_path.len() {
-                            let next = work_state.cached_path[work_state.path_index];
-                            let dx = next.0 - current_pos.0;
-                            let dy = next.1 - current_pos.1;
-                            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                steer_along_path(&mut work_state, current_pos, &mut velocity);
+            }
+            Some(Task::Sleep(target)) => {
+                // 睡觉：走到床边（或就地睡在地上）；入睡和起床由 sleep_schedule_system 负责
+                let current_pos = (pos.x, pos.y);
+                let target_pos = (target.x, target.y);
+                if current_pos == target_pos {
+                    velocity.x = 0.0;
+                    velocity.y = 0.0;
+                    work_state.cached_path.clear();
+                    work_state.path_index = 0;
+                    continue;
+                }
 
-                            if distance > 0.01 {
-                                velocity.x = (dx as f32 / distance).round();
-                                velocity.y = (dy as f32 / distance).round();
-                            } else {
-                                velocity.x = 0.0;
-                                velocity.y = 0.0;
-                            }
-                        } else {
-                            work_state.cached_path.clear();
-                            work_state.path_index = 0;
-                        }
-                    } else {
-                        let dx = next_waypoint.0 - current_pos.0;
-                        let dy = next_waypoint.1 - current_pos.1;
-                        let distance = ((dx * dx + dy * dy) as f32).sqrt();
-                        if distance > 0.01 {
-                            velocity.x = (dx as f32 / distance).round();
-                            velocity.y = (dy as f32 / distance).round();
-                        } else {
-                            velocity.x = 0.0;
-                            velocity.y = 0.0;
-                        }
+                match poll_path(
+                    &mut commands,
+                    entity,
+                    &mut work_state,
+                    current_pos,
+                    target_pos,
+                    paths,
+                ) {
+                    PathStatus::Ready => steer_along_path(&mut work_state, current_pos, &mut velocity),
+                    PathStatus::Waiting => {
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
                     }
-                } else {
+                    PathStatus::Unreachable => {
+                        // 走不到床边，就地睡在地上
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                        work_state.current_task = Some(Task::Sleep(pos.clone()));
+                        work_state.cached_path.clear();
+                        work_state.path_index = 0;
+                        work_state.task_duration = 0.0;
+                    }
+                }
+            }
+            Some(Task::Attack(target)) => {
+                // 追击：敌人会移动，不占用共享流场，沿异步寻路的路径追向敌人；
+                // 敌人离上次寻路的终点不远时沿用原路径，免得敌人每走一步都重新寻路
+                let current_pos = (pos.x, pos.y);
+                let Ok(enemy) = hostiles.get(*target) else {
+                    // 目标已经倒下，由 squad_order_system 结束命令
                     velocity.x = 0.0;
                     velocity.y = 0.0;
+                    continue;
+                };
+                let enemy_pos = (enemy.x, enemy.y);
+                if (current_pos.0 - enemy_pos.0).abs() <= 1 && (current_pos.1 - enemy_pos.1).abs() <= 1 {
+                    // 追上了，原地由 combat_system 出手
+                    velocity.x = 0.0;
+                    velocity.y = 0.0;
+                    work_state.cached_path.clear();
+                    work_state.path_index = 0;
+                    continue;
+                }
+                let target_pos = work_state
+                    .cached_path
+                    .last()
+                    .copied()
+                    .filter(|goal| (goal.0 - enemy_pos.0).abs() + (goal.1 - enemy_pos.1).abs() <= CHASE_REPATH_DISTANCE)
+                    .unwrap_or(enemy_pos);
+
+                match poll_path(
+                    &mut commands,
+                    entity,
+                    &mut work_state,
+                    current_pos,
+                    target_pos,
+                    paths,
+                ) {
+                    PathStatus::Ready => steer_along_path(&mut work_state, current_pos, &mut velocity),
+                    PathStatus::Waiting | PathStatus::Unreachable => {
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                    }
                 }
             }
-            Some(Task::Gathering(target)) | Some(Task::Mining(target)) => {
+            Some(Task::Rally(target)) => {
+                // 集合：方向由移动系统根据共享流场决定，这里只判断是否结束
                 let current_pos = (pos.x, pos.y);
                 let target_pos = (target.x, target.y);
+                let distance = (current_pos.0 - target_pos.0).abs() + (current_pos.1 - target_pos.1).abs();
 
-                // 任务超时检测（20秒后放弃）
-                if work_state.task_duration > 20.0 {
-                    debug_entity!("矮人任务超时，放弃目标 {:?}", target_pos);
+                // 到达集合点附近、集合点不可达或超时（30秒）后解散
+                if distance <= RALLY_ARRIVAL_RADIUS
+                    || work_state.task_duration > 30.0
+                    || !region_map.connected(current_pos, target_pos)
+                {
                     velocity.x = 0.0;
                     velocity.y = 0.0;
+                    commands.entity(entity).remove::<FollowFlowField>();
                     work_state.current_task = Some(Task::Idle);
+                    work_state.task_cooldown = 5.0; // 在集合点停留片刻
+                    work_state.task_duration = 0.0;
+                    continue;
+                }
+
+                if !following {
+                    commands.entity(entity).insert(FollowFlowField { goal: target_pos });
                     work_state.cached_path.clear();
                     work_state.path_index = 0;
-                    work_state.task_cooldown = 1.0; // 缩短冷却，快速寻找新目标
+                }
+            }
+            Some(Task::Hauling(target)) => {
+                // 搬运：沿通往仓库的共享流场前进，到达后由 hauling_system 卸货
+                let current_pos = (pos.x, pos.y);
+                let target_pos = (target.x, target.y);
+                let distance = (current_pos.0 - target_pos.0).abs() + (current_pos.1 - target_pos.1).abs();
+
+                if distance <= STOCKPILE_ARRIVAL_RADIUS {
+                    velocity.x = 0.0;
+                    velocity.y = 0.0;
+                    if following {
+                        commands.entity(entity).remove::<FollowFlowField>();
+                    }
+                    continue;
+                }
+
+                // 仓库不可达或超时则先放下搬运，稍后由 hauling_system 重新安排
+                if work_state.task_duration > HAULING_TIMEOUT || !region_map.connected(current_pos, target_pos) {
+                    velocity.x = 0.0;
+                    velocity.y = 0.0;
+                    commands.entity(entity).remove::<FollowFlowField>();
+                    work_state.current_task = Some(Task::Idle);
+                    work_state.task_cooldown = 1.0;
                     work_state.task_duration = 0.0;
                     continue;
                 }
 
+                if !following {
+                    commands.entity(entity).insert(FollowFlowField { goal: target_pos });
+                    work_state.cached_path.clear();
+                    work_state.path_index = 0;
+                }
+            }
+            Some(Task::Gathering(target))
+            | Some(Task::Mining(target))
+            | Some(Task::Building(target, _))
+            | Some(Task::Crafting(target, _))
+            | Some(Task::Fishing(target))
+            | Some(Task::PickUp(target)) => {
+                let current_pos = (pos.x, pos.y);
+                let site = (target.x, target.y);
+                let building = match &work_state.current_task {
+                    Some(Task::Building(_, building)) => Some(*building),
+                    _ => None,
+                };
+                let fishing = matches!(work_state.current_task, Some(Task::Fishing(_)));
+                // 建造时走到施工站位，钓鱼时走到岸边；站位暂时不可达时朝目标地块走，交给寻路判定
+                let target_pos = if fishing {
+                    adjacent_approach(site, current_pos, &nav_grid, &region_map)
+                } else {
+                    building.and_then(|building| build_approach(building, site, current_pos, &nav_grid, &region_map))
+                }
+                .unwrap_or(site);
+                let on_shore = fishing && (current_pos.0 - site.0).abs() + (current_pos.1 - site.1).abs() == 1;
+
                 // 检查是否已到达目标
-                if current_pos == target_pos {
+                if current_pos == target_pos
+                    || on_shore
+                    || building.is_some_and(|building| in_build_reach(building, current_pos, site))
+                {
                     // 到达目标，停止移动
                     velocity.x = 0.0;
                     velocity.y = 0.0;
                     work_state.cached_path.clear();
                     work_state.path_index = 0;
-                    // 工作进度在 resource_gathering_system 中累积
+                    // 超时只计算赶路的时间，工具差、心情差的矮人干得慢也不会被打断
+                    work_state.task_duration = 0.0;
+                    // 工作进度在 resource_gathering_system、building_system、workshop_system 和 fishing_system 中累积，捡拾由 hauling_system 完成
                     continue;
                 }
 
-                // 检查是否需要重新计算路径（延长重计算间隔）
-                let need_recalc = work_state.cached_path.is_empty()
-                    || work_state.path_index >= work_state.cached_path.len()
-                    || work_state.path_recalc_timer > 5.0; // 每5秒重新计算一次（原来是2秒）
-
-                if need_recalc {
-                    // 使用A*算法计算路径
-                    match find_path(current_pos, target_pos, &terrain_query) {
-                        Some(path) => {
-                            // 使用改进的路径简化算法（已验证相邻性和方向一致性）
-                            let simplified = simplify_path(path);
-                            work_state.cached_path = simplified;
-                            work_state.path_index = 0;
-                            work_state.path_recalc_timer = 0.0;
-                        }
-                        None => {
-                            // 找不到路径，放弃任务
-                            debug_entity!("矮人无法到达目标 {:?}，寻找新目标", target_pos);
-                            velocity.x = 0.0;
-                            velocity.y = 0.0;
-                            work_state.current_task = Some(Task::Idle);
-                            work_state.cached_path.clear();
-                            work_state.path_index = 0;
-                            work_state.task_cooldown = 0.5; // 快速寻找可达目标
-                            work_state.task_duration = 0.0;
-                            continue;
-                        }
+                // 赶路超时检测（20秒还没走到就放弃）
+                if work_state.task_duration > 20.0 {
+                    debug_entity!("矮人任务超时，放弃目标 {:?}", target_pos);
+                    reservations.release(entity);
+                    velocity.x = 0.0;
+                    velocity.y = 0.0;
+                    work_state.current_task = Some(Task::Idle);
+                    work_state.cached_path.clear();
+                    work_state.path_index = 0;
+                    work_state.task_cooldown = 1.0; // 缩短冷却，快速寻找新目标
+                    work_state.task_duration = 0.0;
+                    continue;
+                }
+
+                // 检查是否需要重新计算路径（由异步寻路完成，每5秒刷新一次）
+                match poll_path(
+                    &mut commands,
+                    entity,
+                    &mut work_state,
+                    current_pos,
+                    target_pos,
+                    paths,
+                ) {
+                    PathStatus::Ready => {}
+                    PathStatus::Waiting => {
+                        // 思考中，原地等待路径
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                        continue;
+                    }
+                    PathStatus::Unreachable => {
+                        // 找不到路径，放弃任务
+                        debug_entity!("矮人无法到达目标 {:?}，寻找新目标", target_pos);
+                        reservations.release(entity);
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                        work_state.current_task = Some(Task::Idle);
+                        work_state.cached_path.clear();
+                        work_state.path_index = 0;
+                        work_state.task_cooldown = 0.5; // 快速寻找可达目标
+                        work_state.task_duration = 0.0;
+                        continue;
                     }
                 }
 
@@ -305,15 +788,7 @@ pub fn dwarf_work_system(
                     let next_waypoint = work_state.cached_path[work_state.path_index];
 
                     // 检查下一个路径点是否仍然可行走
-                    let mut waypoint_walkable = false;
-                    for (terrain_pos, terrain) in terrain_query.iter() {
-                        if terrain_pos.x == next_waypoint.0 && terrain_pos.y == next_waypoint.1 {
-                            waypoint_walkable = terrain.walkable;
-                            break;
-                        }
-                    }
-
-                    if !waypoint_walkable {
+                    if !nav_grid.is_walkable(next_waypoint.0, next_waypoint.1) {
                         // 路径点变得不可行走（例如动态障碍），重新计算路径
                         work_state.cached_path.clear();
                         work_state.path_index = 0;
@@ -378,19 +853,38 @@ pub fn dwarf_work_system(
     }
 }
 
-/// 资源采集系统 - 改进版，基于工作进度和地形属性
+/// 资源采集系统 - 改进版，基于工作进度、地形属性、矮人技能和工具材质；砍树会把树砍倒，草地上有种子时播种收获谷物；
+/// 产出先装进矮人随身携带的货物，装满后由 hauling_system 安排运回仓库
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn resource_gathering_system(
     time: Res<Time>,
-    mut query: Query<(&mut WorkState, &GridPosition), With<Dwarf>>,
+    mut query: Query<(
+        &Dwarf,
+        &mut WorkState,
+        &mut Skills,
+        &Personality,
+        &mut Thoughts,
+        &mut Equipment,
+        &GridPosition,
+    )>,
     terrain_query: Query<(&GridPosition, &Terrain)>,
     mut inventory: ResMut<GlobalInventory>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    world_atlas: Res<crate::world_map_data::WorldAtlas>,
+    mut logger: ResMut<crate::logger::GameLogger>,
 ) {
     // 如果时间暂停,不采集资源
     if time.delta_secs() <= 0.0001 {
         return;
     }
 
-    for (mut work_state, pos) in query.iter_mut() {
+    let mut rng = rand::thread_rng();
+
+    for (dwarf, mut work_state, mut skills, personality, mut thoughts, mut equipment, pos) in query.iter_mut() {
+        // 性格和心情影响工作速度
+        let temperament = personality.work_speed() * MoodLevel::from_mood(dwarf.happiness).work_speed();
+
         // 先克隆当前任务以避免借用冲突
         let current_task = work_state.current_task.clone();
 
@@ -401,40 +895,81 @@ pub fn resource_gathering_system(
                     // 获取地形信息
                     let mut terrain_multiplier = 1.0;
                     let mut resource_richness = 1.0;
+                    let mut skill = SkillKind::Farming;
 
                     for (terrain_pos, terrain) in terrain_query.iter() {
                         if terrain_pos.x == pos.x && terrain_pos.y == pos.y {
                             terrain_multiplier = terrain.terrain_type.resource_multiplier();
                             resource_richness = terrain.resource_richness;
+                            skill = terrain.terrain_type.labour();
                             break;
                         }
                     }
 
-                    // 累积工作进度，考虑地形和资源丰富度
-                    let progress_speed = 0.2 * terrain_multiplier * resource_richness;
+                    // 累积工作进度，考虑地形、资源丰富度、技能等级和工具
+                    let progress_speed = 0.2
+                        * terrain_multiplier
+                        * resource_richness
+                        * skills.work_speed(skill)
+                        * equipment.tool_speed(skill)
+                        * temperament;
                     work_state.work_progress += time.delta_secs() * progress_speed;
 
                     // 完成采集
                     if work_state.work_progress >= 1.0 {
-                        // 根据地形类型添加资源
+                        // 根据地形类型添加资源，熟练的矮人产出更多
                         let base_amount = 1;
-                        let amount =
-                            (base_amount as f32 * terrain_multiplier * resource_richness) as u32;
+                        let amount = (base_amount as f32
+                            * terrain_multiplier
+                            * resource_richness
+                            * skills.yield_multiplier(skill)) as u32;
 
                         for (terrain_pos, terrain) in terrain_query.iter() {
                             if terrain_pos.x == pos.x && terrain_pos.y == pos.y {
-                                match terrain.terrain_type {
+                                // 随身装不下的部分留在脚下，等人来捡
+                                let here = (pos.x, pos.y);
+                                let material = match terrain.terrain_type {
                                     crate::components::TerrainType::Tree => {
-                                        inventory.wood += amount
+                                        // 树被砍倒，只留下泥土，等周围的树木重新播种
+                                        let overflow = equipment.stow_material(MaterialKind::Wood, amount * FELLED_TREE_WOOD);
+                                        if let Some(coord) = active_local.coord {
+                                            super::fell_tree(&mut map_registry, &world_atlas, coord, here, &mut rng);
+                                            map_registry.drop_material(coord, MaterialKind::Wood, overflow, here);
+                                        }
+                                        Some(MaterialKind::Wood)
                                     }
                                     crate::components::TerrainType::Stone => {
-                                        inventory.stone += amount
+                                        let overflow = equipment.stow_material(MaterialKind::Stone, amount);
+                                        if let Some(coord) = active_local.coord {
+                                            map_registry.drop_material(coord, MaterialKind::Stone, overflow, here);
+                                        }
+                                        Some(MaterialKind::Stone)
+                                    }
+                                    _ => {
+                                        // 有种子时播种收获谷物（收成时可能留下新种子），没有种子只能采些野果
+                                        let kind = if inventory.seeds > 0 {
+                                            inventory.seeds -= 1;
+                                            if rng.gen_bool(SEED_RETURN_CHANCE) {
+                                                inventory.seeds += 1;
+                                            }
+                                            FoodKind::Crop
+                                        } else {
+                                            FoodKind::Plant
+                                        };
+                                        let overflow = equipment.stow_food(kind, amount);
+                                        if let Some(coord) = active_local.coord {
+                                            map_registry.drop_food(coord, kind, overflow, here);
+                                        }
+                                        None
                                     }
-                                    _ => inventory.food += amount,
+                                };
+                                if material == Some(personality.favourite_material) {
+                                    thoughts.add(ThoughtKind::HandledFavouriteMaterial);
                                 }
                                 break;
                             }
                         }
+                        gain_experience(dwarf, &mut skills, &mut thoughts, skill, &mut logger);
 
                         work_state.work_progress = 0.0;
                         work_state.current_task = Some(Task::Idle);
@@ -458,16 +993,30 @@ pub fn resource_gathering_system(
                         }
                     }
 
-                    // 累积工作进度
-                    let progress_speed = 0.15 * terrain_multiplier * resource_richness;
+                    // 累积工作进度，考虑采矿技能和镐的材质
+                    let progress_speed = 0.15
+                        * terrain_multiplier
+                        * resource_richness
+                        * skills.work_speed(SkillKind::Mining)
+                        * equipment.tool_speed(SkillKind::Mining)
+                        * temperament;
                     work_state.work_progress += time.delta_secs() * progress_speed;
 
                     // 完成挖矿
                     if work_state.work_progress >= 1.0 {
                         let base_amount = 2;
-                        let amount =
-                            (base_amount as f32 * terrain_multiplier * resource_richness) as u32;
-                        inventory.metal += amount;
+                        let amount = (base_amount as f32
+                            * terrain_multiplier
+                            * resource_richness
+                            * skills.yield_multiplier(SkillKind::Mining)) as u32;
+                        let overflow = equipment.stow_material(MaterialKind::Metal, amount);
+                        if let Some(coord) = active_local.coord {
+                            map_registry.drop_material(coord, MaterialKind::Metal, overflow, (pos.x, pos.y));
+                        }
+                        if personality.favourite_material == MaterialKind::Metal {
+                            thoughts.add(ThoughtKind::HandledFavouriteMaterial);
+                        }
+                        gain_experience(dwarf, &mut skills, &mut thoughts, SkillKind::Mining, &mut logger);
 
                         work_state.work_progress = 0.0;
                         work_state.current_task = Some(Task::Idle);
@@ -481,7 +1030,148 @@ pub fn resource_gathering_system(
     }
 }
 
-/// 建筑系统占位
-pub fn building_system() {
-    // 建筑系统暂未实现
+/// 为完成的工作增加技能经验，升级时记录日志并留下"技艺精进"的想法
+pub fn gain_experience(
+    dwarf: &Dwarf,
+    skills: &mut Skills,
+    thoughts: &mut Thoughts,
+    skill: SkillKind,
+    logger: &mut crate::logger::GameLogger,
+) {
+    if skills.add_xp(skill, XP_PER_JOB) {
+        thoughts.add(ThoughtKind::SkillImproved);
+        logger.info(format!(
+            "{} 的{}技能提升到 {} 级",
+            dwarf.name,
+            skill.label(),
+            skills.level(skill)
+        ));
+    }
+}
+
+/// 建筑系统：到达施工站位的矮人按建造技能累积进度，完成后消耗库存材料摆放家具、建好作坊或建成建筑结构
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+pub fn building_system(
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut query: Query<(&Dwarf, &mut WorkState, &mut Skills, &Personality, &mut Thoughts, &GridPosition)>,
+    active_local: Res<ActiveLocalMap>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut inventory: ResMut<GlobalInventory>,
+    mut chronicle: ResMut<FortressChronicle>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    if time.delta_secs() <= 0.0001 {
+        return;
+    }
+    let Some(coord) = active_local.coord else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+    // 有人站着的地块上砌不了墙
+    let occupied: std::collections::HashSet<(i32, i32)> = query.iter().map(|(.., pos)| (pos.x, pos.y)).collect();
+
+    for (dwarf, mut work_state, mut skills, personality, mut thoughts, pos) in query.iter_mut() {
+        let Some(Task::Building(target, building)) = work_state.current_task.clone() else {
+            continue;
+        };
+        let target_pos = (target.x, target.y);
+        if !in_build_reach(building, (pos.x, pos.y), target_pos) {
+            continue;
+        }
+
+        // 订单已被取消
+        let Some(order) = map_registry
+            .local_build_orders(Some(coord))
+            .iter()
+            .find(|order| order.pos == target_pos)
+            .cloned()
+        else {
+            work_state.work_progress = 0.0;
+            work_state.current_task = Some(Task::Idle);
+            work_state.task_cooldown = 0.5;
+            work_state.task_duration = 0.0;
+            continue;
+        };
+
+        let temperament = personality.work_speed() * MoodLevel::from_mood(dwarf.happiness).work_speed();
+        work_state.work_progress +=
+            time.delta_secs() * BUILD_SPEED * skills.work_speed(SkillKind::Building) * temperament;
+        if work_state.work_progress < 1.0 {
+            continue;
+        }
+        if building == BuildingType::Construction(ConstructionKind::Wall) && occupied.contains(&target_pos) {
+            work_state.work_progress = 1.0;
+            continue;
+        }
+
+        work_state.work_progress = 0.0;
+        work_state.current_task = Some(Task::Idle);
+        work_state.task_duration = 0.0;
+        if !inventory.take_material(order.material, building.material_cost()) {
+            logger.info(format!(
+                "{} 缺少{}料，暂时造不了{}",
+                dwarf.name,
+                order.material.label(),
+                building.label()
+            ));
+            work_state.task_cooldown = 2.0;
+            continue;
+        }
+        work_state.task_cooldown = 0.5;
+
+        if let Some(orders) = map_registry.build_orders.get_mut(&coord) {
+            orders.retain(|existing| existing.pos != target_pos);
+        }
+        let built = match building {
+            BuildingType::Furniture(kind) => {
+                let artifact =
+                    skills.level(SkillKind::Building) >= ARTIFACT_MIN_LEVEL && rng.gen_bool(ARTIFACT_CHANCE);
+                map_registry.furniture.entry(coord).or_default().push(PlacedFurniture {
+                    kind,
+                    material: order.material,
+                    pos: target_pos,
+                    artifact,
+                });
+                if artifact {
+                    let made = format!("{} 倾注心血，造出了一件传世的{}{}", dwarf.name, order.material.label(), building.label());
+                    logger.info(made.clone());
+                    chronicle.record(&game_time, ChronicleKind::Artifact, made);
+                    None
+                } else {
+                    Some(format!("{} 造好了一件{}{}", dwarf.name, order.material.label(), building.label()))
+                }
+            }
+            BuildingType::Workshop(kind) => {
+                map_registry.workshops.entry(coord).or_default().push(PlacedWorkshop {
+                    kind,
+                    material: order.material,
+                    pos: target_pos,
+                });
+                Some(format!("{} 建好了一座{}{}", dwarf.name, order.material.label(), building.label()))
+            }
+            BuildingType::Construction(kind) => {
+                // 改写存储的地块，由建筑结构同步系统更新地图和寻路数据
+                if let Some(tile) = map_registry
+                    .maps
+                    .get_mut(&coord)
+                    .and_then(|tiles| tiles.iter_mut().find(|tile| (tile.x, tile.y) == target_pos))
+                {
+                    tile.construct(Construction {
+                        kind,
+                        material: order.material,
+                    });
+                }
+                Some(format!("{} 建好了一处{}{}", dwarf.name, order.material.label(), building.label()))
+            }
+            _ => None,
+        };
+        if let Some(built) = built {
+            logger.info(built.clone());
+            chronicle.record(&game_time, ChronicleKind::Building, built);
+        }
+        gain_experience(dwarf, &mut skills, &mut thoughts, SkillKind::Building, &mut logger);
+    }
 }
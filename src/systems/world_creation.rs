@@ -0,0 +1,540 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+
+use crate::resources::{ActiveLocalMap, GameState, WorldSeed};
+use crate::systems::log_history_summary;
+use crate::world_map_data::{AtlasSelection, WorldAtlas, WorldGenSettings};
+
+/// 世界预设文件路径
+const WORLD_PRESET_PATH: &str = "world_preset.json";
+/// 种子文本最大长度
+const MAX_SEED_TEXT_LEN: usize = 32;
+/// 预览区域尺寸（像素）
+const PREVIEW_WIDTH: f32 = 720.0;
+const PREVIEW_HEIGHT: f32 = 480.0;
+
+/// 创建世界界面状态
+#[derive(Resource, Default)]
+pub struct WorldCreationState {
+    pub seed_text: String,
+    /// 设置已修改，需要重新生成预览
+    pub dirty: bool,
+    pub status: String,
+}
+
+/// 可调节的世界生成参数
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorldGenField {
+    Width,
+    Height,
+    OceanPercent,
+    MountainFrequency,
+    TemperatureBias,
+    MoistureBias,
+    HistoryYears,
+}
+
+impl WorldGenField {
+    pub const ALL: [WorldGenField; 7] = [
+        WorldGenField::Width,
+        WorldGenField::Height,
+        WorldGenField::OceanPercent,
+        WorldGenField::MountainFrequency,
+        WorldGenField::TemperatureBias,
+        WorldGenField::MoistureBias,
+        WorldGenField::HistoryYears,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WorldGenField::Width => "地图宽度",
+            WorldGenField::Height => "地图高度",
+            WorldGenField::OceanPercent => "海洋比例",
+            WorldGenField::MountainFrequency => "山脉频率",
+            WorldGenField::TemperatureBias => "温度偏移",
+            WorldGenField::MoistureBias => "湿度偏移",
+            WorldGenField::HistoryYears => "历史长度",
+        }
+    }
+
+    fn format_value(&self, settings: &WorldGenSettings) -> String {
+        match self {
+            WorldGenField::Width => format!("{}", settings.width),
+            WorldGenField::Height => format!("{}", settings.height),
+            WorldGenField::OceanPercent => format!("{:.0}%", settings.ocean_percent),
+            WorldGenField::MountainFrequency => format!("{:.2}x", settings.mountain_frequency),
+            WorldGenField::TemperatureBias => format!("{:+.1}", settings.temperature_bias),
+            WorldGenField::MoistureBias => format!("{:+.1}", settings.moisture_bias),
+            WorldGenField::HistoryYears => format!("{}年", settings.history_years),
+        }
+    }
+
+    /// 按步长调整参数（direction 为 1 或 -1）
+    fn adjust(&self, settings: &mut WorldGenSettings, direction: i32) {
+        let step = direction as f32;
+        match self {
+            WorldGenField::Width => settings.width += direction * 2,
+            WorldGenField::Height => settings.height += direction * 2,
+            WorldGenField::OceanPercent => settings.ocean_percent += step * 5.0,
+            WorldGenField::MountainFrequency => settings.mountain_frequency += step * 0.25,
+            WorldGenField::TemperatureBias => settings.temperature_bias += step * 0.1,
+            WorldGenField::MoistureBias => settings.moisture_bias += step * 0.1,
+            WorldGenField::HistoryYears => {
+                settings.history_years = settings.history_years.saturating_add_signed(direction * 20)
+            }
+        }
+        // 避免浮点累加误差
+        settings.temperature_bias = (settings.temperature_bias * 10.0).round() / 10.0;
+        settings.moisture_bias = (settings.moisture_bias * 10.0).round() / 10.0;
+        *settings = settings.clamped();
+    }
+}
+
+/// 创建世界界面根节点
+#[derive(Component)]
+pub struct WorldCreationUI;
+
+/// 创建世界界面按钮
+#[derive(Component, Clone, Copy)]
+pub enum WorldCreationButton {
+    Adjust(WorldGenField, i32),
+    RandomSeed,
+    ExportPreset,
+    ImportPreset,
+    Create,
+    Back,
+}
+
+/// 创建世界界面中需要刷新的文本
+#[derive(Component, Clone, Copy)]
+pub enum WorldCreationText {
+    Seed,
+    Status,
+    Value(WorldGenField),
+}
+
+/// 预览图容器
+#[derive(Component)]
+pub struct WorldPreviewRoot;
+
+const BUTTON_COLOR: Color = Color::srgb(0.3, 0.4, 0.5);
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.4, 0.5, 0.6);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.2, 0.5, 0.3);
+
+/// 进入创建世界界面
+pub fn setup_world_creation(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<WorldGenSettings>,
+    mut state: ResMut<WorldCreationState>,
+    mut keyboard_events: ResMut<Messages<KeyboardInput>>,
+) {
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+
+    keyboard_events.clear();
+    state.seed_text = settings.seed.to_string();
+    state.dirty = true;
+    state.status = "键入种子（数字或任意文字） | Enter创建 | Esc返回".to_string();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.1, 0.15, 0.2)),
+            WorldCreationUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("◆ 创建世界 ◆"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.9, 0.5)),
+                Node {
+                    margin: UiRect::bottom(Val::Px(16.0)),
+                    ..default()
+                },
+            ));
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                })
+                .with_children(|body| {
+                    // 左侧：参数设置
+                    body.spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        width: Val::Px(420.0),
+                        margin: UiRect::right(Val::Px(20.0)),
+                        ..default()
+                    })
+                    .with_children(|column| {
+                        column
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                margin: UiRect::bottom(Val::Px(10.0)),
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                row.spawn((
+                                    Text::new(""),
+                                    TextFont {
+                                        font: font.clone(),
+                                        font_size: 20.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::srgb(0.9, 0.95, 1.0)),
+                                    Node {
+                                        width: Val::Px(300.0),
+                                        ..default()
+                                    },
+                                    WorldCreationText::Seed,
+                                ));
+                                spawn_button(row, &font, "随机", WorldCreationButton::RandomSeed);
+                            });
+
+                        for field in WorldGenField::ALL {
+                            column
+                                .spawn(Node {
+                                    flex_direction: FlexDirection::Row,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::bottom(Val::Px(4.0)),
+                                    ..default()
+                                })
+                                .with_children(|row| {
+                                    row.spawn((
+                                        Text::new(field.label()),
+                                        TextFont {
+                                            font: font.clone(),
+                                            font_size: 20.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::srgb(0.8, 0.8, 0.9)),
+                                        Node {
+                                            width: Val::Px(120.0),
+                                            ..default()
+                                        },
+                                    ));
+                                    spawn_button(row, &font, "-", WorldCreationButton::Adjust(field, -1));
+                                    row.spawn((
+                                        Text::new(""),
+                                        TextFont {
+                                            font: font.clone(),
+                                            font_size: 20.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                                        Node {
+                                            width: Val::Px(90.0),
+                                            justify_content: JustifyContent::Center,
+                                            ..default()
+                                        },
+                                        WorldCreationText::Value(field),
+                                    ));
+                                    spawn_button(row, &font, "+", WorldCreationButton::Adjust(field, 1));
+                                });
+                        }
+
+                        column
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Row,
+                                flex_wrap: FlexWrap::Wrap,
+                                margin: UiRect::top(Val::Px(16.0)),
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                spawn_button(row, &font, "创建世界", WorldCreationButton::Create);
+                                spawn_button(row, &font, "导出预设", WorldCreationButton::ExportPreset);
+                                spawn_button(row, &font, "导入预设", WorldCreationButton::ImportPreset);
+                                spawn_button(row, &font, "返回", WorldCreationButton::Back);
+                            });
+
+                        column.spawn((
+                            Text::new(""),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.7, 0.7, 0.8)),
+                            Node {
+                                margin: UiRect::top(Val::Px(12.0)),
+                                ..default()
+                            },
+                            WorldCreationText::Status,
+                        ));
+                    });
+
+                    // 右侧：实时预览
+                    body.spawn((
+                        Node {
+                            width: Val::Px(PREVIEW_WIDTH),
+                            height: Val::Px(PREVIEW_HEIGHT),
+                            flex_direction: FlexDirection::Column,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.05, 0.05, 0.08)),
+                        WorldPreviewRoot,
+                    ));
+                });
+        });
+}
+
+/// 离开创建世界界面
+pub fn cleanup_world_creation(mut commands: Commands, roots: Query<Entity, With<WorldCreationUI>>) {
+    for entity in roots.iter() {
+        if let Ok(mut entity_cmd) = commands.get_entity(entity) {
+            entity_cmd.despawn();
+        }
+    }
+}
+
+/// 种子输入与快捷键
+pub fn world_creation_keyboard_system(
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut state: ResMut<WorldCreationState>,
+    mut settings: ResMut<WorldGenSettings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Escape => next_state.set(GameState::MainMenu),
+            Key::Enter => next_state.set(GameState::WorldView),
+            Key::Backspace => {
+                state.seed_text.pop();
+                settings.seed = WorldGenSettings::seed_from_text(&state.seed_text);
+                state.dirty = true;
+            }
+            Key::Character(text) if state.seed_text.chars().count() < MAX_SEED_TEXT_LEN => {
+                state.seed_text.push_str(text.as_str());
+                settings.seed = WorldGenSettings::seed_from_text(&state.seed_text);
+                state.dirty = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 创建世界界面按钮交互
+pub fn world_creation_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &WorldCreationButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut state: ResMut<WorldCreationState>,
+    mut settings: ResMut<WorldGenSettings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    for (interaction, button, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(BUTTON_PRESSED_COLOR);
+                match *button {
+                    WorldCreationButton::Adjust(field, direction) => {
+                        field.adjust(&mut settings, direction);
+                        state.dirty = true;
+                    }
+                    WorldCreationButton::RandomSeed => {
+                        settings.seed = rand::random::<u64>();
+                        state.seed_text = settings.seed.to_string();
+                        state.dirty = true;
+                    }
+                    WorldCreationButton::ExportPreset => {
+                        state.status = match serde_json::to_string_pretty(&*settings)
+                            .map_err(|err| err.to_string())
+                            .and_then(|json| {
+                                std::fs::write(WORLD_PRESET_PATH, json).map_err(|err| err.to_string())
+                            }) {
+                            Ok(()) => {
+                                logger.info(format!("世界预设已导出到 {}", WORLD_PRESET_PATH));
+                                format!("预设已导出到 {}", WORLD_PRESET_PATH)
+                            }
+                            Err(err) => {
+                                logger.error(format!("导出世界预设失败: {}", err));
+                                format!("导出失败: {}", err)
+                            }
+                        };
+                    }
+                    WorldCreationButton::ImportPreset => {
+                        let loaded = std::fs::read_to_string(WORLD_PRESET_PATH)
+                            .map_err(|err| err.to_string())
+                            .and_then(|json| {
+                                serde_json::from_str::<WorldGenSettings>(&json)
+                                    .map_err(|err| err.to_string())
+                            });
+                        state.status = match loaded {
+                            Ok(preset) => {
+                                *settings = preset.clamped();
+                                state.seed_text = settings.seed.to_string();
+                                state.dirty = true;
+                                logger.info(format!("已导入世界预设 {}", WORLD_PRESET_PATH));
+                                format!("已导入 {}", WORLD_PRESET_PATH)
+                            }
+                            Err(err) => {
+                                logger.warning(format!("导入世界预设失败: {}", err));
+                                format!("导入失败: {}", err)
+                            }
+                        };
+                    }
+                    WorldCreationButton::Create => next_state.set(GameState::WorldView),
+                    WorldCreationButton::Back => next_state.set(GameState::MainMenu),
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(BUTTON_HOVER_COLOR);
+            }
+            Interaction::None => {
+                *color = BackgroundColor(BUTTON_COLOR);
+            }
+        }
+    }
+}
+
+/// 设置变化时重新生成世界并刷新预览
+pub fn world_creation_preview_system(
+    mut commands: Commands,
+    mut state: ResMut<WorldCreationState>,
+    settings: Res<WorldGenSettings>,
+    mut world_atlas: ResMut<WorldAtlas>,
+    preview_root: Query<Entity, With<WorldPreviewRoot>>,
+    mut texts: Query<(&mut Text, &WorldCreationText)>,
+) {
+    if !state.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for (mut text, kind) in texts.iter_mut() {
+        **text = match kind {
+            WorldCreationText::Seed => format!("种子: {}_", state.seed_text),
+            WorldCreationText::Status => state.status.clone(),
+            WorldCreationText::Value(field) => field.format_value(&settings),
+        };
+    }
+
+    if !state.dirty {
+        return;
+    }
+    state.dirty = false;
+
+    world_atlas.regenerate(&settings);
+
+    // 以色块绘制每个世界格子，聚落所在格子加亮显示
+    let Ok(root) = preview_root.single() else {
+        return;
+    };
+    let cell_size = (PREVIEW_WIDTH / world_atlas.width as f32)
+        .min(PREVIEW_HEIGHT / world_atlas.height as f32)
+        .floor();
+    commands.entity(root).despawn_related::<Children>();
+    commands.entity(root).with_children(|preview| {
+        // 大地图 y 轴向上，UI 自上而下排列，因此从最高的一行开始
+        for y in (0..world_atlas.height).rev() {
+            preview
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                })
+                .with_children(|row| {
+                    for x in 0..world_atlas.width {
+                        let Some(cell) = world_atlas.cell_at(IVec2::new(x, y)) else {
+                            continue;
+                        };
+                        let color = if cell.settlement.is_some() {
+                            Color::srgb(0.95, 0.85, 0.4)
+                        } else {
+                            cell.color()
+                        };
+                        row.spawn((
+                            Node {
+                                width: Val::Px(cell_size),
+                                height: Val::Px(cell_size),
+                                ..default()
+                            },
+                            BackgroundColor(color),
+                        ));
+                    }
+                });
+        }
+    });
+}
+
+/// 离开创建世界界面（确认创建或返回主菜单）时同步种子和生成设置并重置大地图选中状态，
+/// 预览时已经原地重新生成了世界，之后进入的大地图和局部地图都以它为准
+pub fn sync_world_creation(
+    settings: Res<WorldGenSettings>,
+    mut world_seed: ResMut<WorldSeed>,
+    mut selection: ResMut<AtlasSelection>,
+    mut active_local: ResMut<ActiveLocalMap>,
+) {
+    world_seed.seed = settings.seed;
+    selection.selected = None;
+    selection.hovered = None;
+    active_local.coord = None;
+}
+
+/// 确认创建世界：记录世界概况
+pub fn confirm_world_creation(
+    settings: Res<WorldGenSettings>,
+    world_atlas: Res<WorldAtlas>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    log_history_summary(&world_atlas, &mut logger);
+    logger.info(format!(
+        "创建世界: 种子 {} 尺寸 {}x{} 海洋 {:.0}% 山脉 {:.2}x 历史 {}年",
+        settings.seed,
+        world_atlas.width,
+        world_atlas.height,
+        settings.ocean_percent,
+        settings.mountain_frequency,
+        settings.history_years
+    ));
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    label: &str,
+    button: WorldCreationButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                margin: UiRect::all(Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+        });
+}
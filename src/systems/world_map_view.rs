@@ -4,25 +4,47 @@ use bevy::input::ButtonInput;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
-use crate::resources::{ActiveLocalMap, GameState, WorldSeed};
-use crate::world_map_data::{AtlasSelection, WorldAtlas, WorldCell, WORLD_ATLAS_TILE_SIZE, WORLD_ATLAS_DEFAULT_WIDTH, WORLD_ATLAS_DEFAULT_HEIGHT};
+use crate::resources::{ActiveLocalMap, GameState, GeneratedMapsRegistry, WorldSeed};
+use crate::world_history::WorldHistory;
+use crate::world_map_data::{AtlasSelection, WorldAtlas, WorldCell, WorldGenSettings, WORLD_ATLAS_TILE_SIZE, RIVER_FLOW_THRESHOLD};
 use crate::{debug_world_input, debug_world_selection};
 
-/// 初始化世界地图（使用世界种子）
+/// 初始化世界地图（使用世界种子与世界生成设置）
 pub fn init_world_atlas(
     mut commands: Commands,
     world_seed: Res<WorldSeed>,
+    mut settings: ResMut<WorldGenSettings>,
     mut logger: ResMut<crate::logger::GameLogger>,
 ) {
-    let atlas = WorldAtlas::generate(
-        world_seed.seed as u64,
-        WORLD_ATLAS_DEFAULT_WIDTH,
-        WORLD_ATLAS_DEFAULT_HEIGHT,
-    );
+    settings.seed = world_seed.seed;
+    let atlas = WorldAtlas::generate_with_settings(&settings);
+    log_history_summary(&atlas, &mut logger);
     commands.insert_resource(atlas);
     logger.info(format!("初始化世界地图，种子: {}", world_seed.seed));
 }
 
+/// 记录世界历史概况（文明数量与最近的几条事件）
+pub fn log_history_summary(atlas: &WorldAtlas, logger: &mut crate::logger::GameLogger) {
+    let history = &atlas.history;
+    let surviving = history
+        .civilizations
+        .iter()
+        .filter(|civ| civ.is_alive())
+        .count();
+    logger.info(format!(
+        "世界历史: {}年, {} 个文明({}个存续), {} 个聚落, {} 条事件",
+        history.years,
+        history.civilizations.len(),
+        surviving,
+        history.settlements.len(),
+        history.events.len()
+    ));
+
+    for event in history.events.iter().rev().take(5).rev() {
+        logger.debug(history.describe_event(event));
+    }
+}
+
 /// 世界地图场景根节点
 #[derive(Component)]
 pub struct AtlasViewRoot;
@@ -34,6 +56,18 @@ pub struct AtlasTileVisual {
     pub coord: IVec2,
 }
 
+/// 聚落标记实体
+#[derive(Component)]
+pub struct AtlasSettlementMarker;
+
+/// 领土边界实体
+#[derive(Component)]
+pub struct AtlasBorderSegment;
+
+/// 河流线段实体
+#[derive(Component)]
+pub struct AtlasRiverSegment;
+
 /// 选中高亮实体
 #[derive(Component)]
 pub struct AtlasSelectionHighlight;
@@ -86,7 +120,11 @@ pub fn setup_world_atlas_scene(
     if let Ok(mut camera_transform) = camera_query.single_mut() {
         camera_transform.translation.x = 0.0;
         camera_transform.translation.y = 0.0;
-        camera_transform.scale = Vec3::ONE;
+        // 大尺寸世界缩小视图，保证整个大地图和说明文字可见
+        let fit_scale = (world_atlas.width as f32 * WORLD_ATLAS_TILE_SIZE / 1300.0)
+            .max((world_atlas.height as f32 * WORLD_ATLAS_TILE_SIZE + 200.0) / 780.0)
+            .max(1.0);
+        camera_transform.scale = Vec3::splat(fit_scale);
     }
 
     let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
@@ -128,6 +166,107 @@ pub fn setup_world_atlas_scene(
             ));
         }
 
+        // 河流：从每个河流格子中心沿水流方向连向下游格子中心
+        for cell in &world_atlas.cells {
+            let Some(next) = cell.downstream else {
+                continue;
+            };
+            if cell.flow < RIVER_FLOW_THRESHOLD {
+                continue;
+            }
+            let start = tile_to_world(cell.coord, world_atlas.width, world_atlas.height);
+            let end = tile_to_world(next, world_atlas.width, world_atlas.height);
+            let thickness = (2.0 + (cell.flow / RIVER_FLOW_THRESHOLD).sqrt() * 1.5).min(8.0);
+            let size = if next.x != cell.coord.x {
+                Vec2::new(WORLD_ATLAS_TILE_SIZE + thickness, thickness)
+            } else {
+                Vec2::new(thickness, WORLD_ATLAS_TILE_SIZE + thickness)
+            };
+            let midpoint = (start + end) / 2.0;
+
+            parent.spawn((
+                Sprite {
+                    color: Color::srgba(0.3, 0.6, 1.0, 0.9),
+                    custom_size: Some(size),
+                    ..default()
+                },
+                Transform::from_xyz(midpoint.x, midpoint.y, 4.0),
+                Visibility::default(),
+                InheritedVisibility::default(),
+                AtlasRiverSegment,
+            ));
+        }
+
+        // 领土边界：在相邻格子归属不同的边上绘制文明颜色的线段
+        let history = &world_atlas.history;
+        for cell in &world_atlas.cells {
+            let Some(owner) = cell.owner else {
+                continue;
+            };
+            let Some(civ) = history.civilization(owner) else {
+                continue;
+            };
+            let center = tile_to_world(cell.coord, world_atlas.width, world_atlas.height);
+            let half = WORLD_ATLAS_TILE_SIZE / 2.0 - 3.0;
+
+            for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                let neighbour_owner = world_atlas
+                    .cell_at(cell.coord + offset)
+                    .and_then(|neighbour| neighbour.owner);
+                if neighbour_owner == Some(owner) {
+                    continue;
+                }
+
+                let (position, size) = if offset.x != 0 {
+                    (
+                        Vec3::new(center.x + offset.x as f32 * half, center.y, 5.0),
+                        Vec2::new(3.0, WORLD_ATLAS_TILE_SIZE - 4.0),
+                    )
+                } else {
+                    (
+                        Vec3::new(center.x, center.y + offset.y as f32 * half, 5.0),
+                        Vec2::new(WORLD_ATLAS_TILE_SIZE - 4.0, 3.0),
+                    )
+                };
+
+                parent.spawn((
+                    Sprite {
+                        color: civ.race.color().with_alpha(0.85),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    Transform::from_translation(position),
+                    Visibility::default(),
+                    InheritedVisibility::default(),
+                    AtlasBorderSegment,
+                ));
+            }
+        }
+
+        // 聚落标记：首都 ☼，其他聚落 ●
+        for site in &history.settlements {
+            let Some(civ) = history.civilization(site.owner) else {
+                continue;
+            };
+            let mut position = tile_to_world(site.coord, world_atlas.width, world_atlas.height);
+            position.z = 6.0;
+            let glyph = if site.is_capital { "☼" } else { "●" };
+
+            parent.spawn((
+                Text2d::new(glyph),
+                TextFont {
+                    font: font.clone(),
+                    font_size: if site.is_capital { 30.0 } else { 22.0 },
+                    ..default()
+                },
+                TextColor(civ.race.color()),
+                Transform::from_translation(position),
+                Visibility::default(),
+                InheritedVisibility::default(),
+                AtlasSettlementMarker,
+            ));
+        }
+
         // 选中高亮
         let highlight_position = selected_cell
             .map(|cell| tile_to_world(cell.coord, world_atlas.width, world_atlas.height))
@@ -164,7 +303,7 @@ pub fn setup_world_atlas_scene(
 
         // 选中信息文本
         parent.spawn((
-            Text2d::new(build_tile_info(preview_cell, selected_cell)),
+            Text2d::new(build_tile_info(preview_cell, selected_cell, &world_atlas.history)),
             TextFont {
                 font: font.clone(),
                 font_size: 24.0,
@@ -183,7 +322,7 @@ pub fn setup_world_atlas_scene(
 
         // 操作提示
         parent.spawn((
-            Text2d::new("操作: 鼠标左键选择 | Enter进入局部地图 | Esc返回主菜单"),
+            Text2d::new("操作: 鼠标左键选择 | Enter进入局部地图 | L查看传说 | Esc返回主菜单"),
             TextFont {
                 font,
                 font_size: 20.0,
@@ -225,6 +364,8 @@ pub fn world_atlas_input_system(
     keyboard: Res<ButtonInput<KeyCode>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
     mut world_seed: ResMut<WorldSeed>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    mut legends_browser: ResMut<crate::systems::LegendsBrowser>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     let hovered_coord = windows
@@ -259,13 +400,23 @@ pub fn world_atlas_input_system(
     if keyboard.just_pressed(KeyCode::Enter) || keyboard.just_pressed(KeyCode::NumpadEnter) {
         if let Some(coord) = selection.selected {
             if let Some(cell) = world_atlas.cell_at(coord) {
-                world_seed.seed = cell.local_seed;
+                world_seed.seed = cell.local_seed as u64;
                 active_local.coord = Some(coord);
-                next_state.set(GameState::LocalView);
+                // 首次进入局部地图前先进行出发准备
+                if map_registry.spawn_location.is_none() {
+                    next_state.set(GameState::Embark);
+                } else {
+                    next_state.set(GameState::LocalView);
+                }
             }
         }
     }
 
+    if keyboard.just_pressed(KeyCode::KeyL) {
+        legends_browser.return_state = GameState::WorldView;
+        next_state.set(GameState::Legends);
+    }
+
     if keyboard.just_pressed(KeyCode::Escape) {
         next_state.set(GameState::MainMenu);
     }
@@ -339,7 +490,7 @@ pub fn world_atlas_selection_system(
 
     // 每帧强制更新信息文本
     if let Ok(mut text) = info_text_query.single_mut() {
-        **text = build_tile_info(hovered_cell, selected_cell);
+        **text = build_tile_info(hovered_cell, selected_cell, &world_atlas.history);
     }
 }
 
@@ -382,7 +533,11 @@ fn world_to_tile(world_pos: Vec2, width: i32, height: i32) -> Option<IVec2> {
     }
 }
 
-fn build_tile_info(hover: Option<&WorldCell>, selected: Option<&WorldCell>) -> String {
+fn build_tile_info(
+    hover: Option<&WorldCell>,
+    selected: Option<&WorldCell>,
+    history: &WorldHistory,
+) -> String {
     match (hover, selected) {
         (Some(hover), Some(selected)) => {
             let suffix = if hover.coord == selected.coord {
@@ -391,14 +546,24 @@ fn build_tile_info(hover: Option<&WorldCell>, selected: Option<&WorldCell>) -> S
                 ""
             };
             format!(
-                "悬停: {}\n选中: {}{}",
+                "悬停: {}{}\n选中: {}{}{}",
                 format_cell_line_core(hover),
+                format_cell_owner(hover, history),
                 format_cell_line_core(selected),
+                format_cell_owner(selected, history),
                 suffix
             )
         }
-        (Some(hover), None) => format!("悬停: {}", format_cell_line_core(hover)),
-        (None, Some(selected)) => format!("选中: {}", format_cell_line_core(selected)),
+        (Some(hover), None) => format!(
+            "悬停: {}{}",
+            format_cell_line_core(hover),
+            format_cell_owner(hover, history)
+        ),
+        (None, Some(selected)) => format!(
+            "选中: {}{}",
+            format_cell_line_core(selected),
+            format_cell_owner(selected, history)
+        ),
         (None, None) => "未选择地块".to_string(),
     }
 }
@@ -415,3 +580,36 @@ fn format_cell_line_core(cell: &WorldCell) -> String {
         cell.local_seed
     )
 }
+
+fn format_cell_owner(cell: &WorldCell, history: &WorldHistory) -> String {
+    let mut parts = Vec::new();
+
+    if cell.has_river() {
+        parts.push(format!("水系:河流 (流量{:.1})", cell.flow));
+    }
+
+    if let Some(site) = cell.settlement.and_then(|id| history.settlement(id)) {
+        let kind = if site.is_capital { "首都" } else { "聚落" };
+        let mut line = format!(
+            "{}:{} (人口{}, 建于第{}年",
+            kind, site.name, site.population, site.founded_year
+        );
+        if site.founder != site.owner {
+            if let Some(founder) = history.civilization(site.founder) {
+                line.push_str(&format!(", 原属{}", founder.name));
+            }
+        }
+        line.push(')');
+        parts.push(line);
+    }
+
+    if let Some(civ) = cell.owner.and_then(|id| history.civilization(id)) {
+        parts.push(format!("领土:{} [{}]", civ.name, civ.race.label()));
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" | {}", parts.join(" "))
+    }
+}
@@ -0,0 +1,335 @@
+use crate::components::*;
+use crate::resources::*;
+use crate::ui_framework::*;
+use bevy::prelude::*;
+
+/// 普通按钮颜色
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.3, 0.4);
+/// 正在使用的画笔按钮颜色
+const BRUSH_COLOR: Color = Color::srgb(0.6, 0.5, 0.15);
+/// 删除按钮颜色
+const DELETE_COLOR: Color = Color::srgb(0.5, 0.15, 0.15);
+
+/// 区域面板中区域列表的容器
+#[derive(Component)]
+pub struct ZonePanelRows;
+
+/// 区域面板按钮
+#[derive(Component, Clone, Copy)]
+pub enum ZoneButton {
+    /// 新建某类区域
+    Create(ZoneKind),
+    /// 选中区域并切换涂抹/擦除画笔
+    Brush(usize, ZoneBrush),
+    /// 删除区域
+    Delete(usize),
+    /// 把选中的矮人加入洞区
+    AddMember(usize),
+    /// 把选中的矮人移出洞区
+    RemoveMember(usize),
+    /// 指定或取消警戒时的避难洞区
+    Refuge(usize),
+}
+
+/// 设置区域面板（初始隐藏，Z键切换）
+pub fn setup_zone_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<UITheme>,
+) {
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let mut builder = PanelBuilder::new(commands.reborrow(), font.clone(), theme.clone());
+
+    // 创建区域面板（右侧中间）
+    let zone_config = PanelConfig {
+        anchor: PanelAnchor::MiddleRight,
+        offset: Vec2::new(15.0, 0.0),
+        min_width: 460.0,
+        min_height: 140.0,
+        background_color: Color::srgba(0.06, 0.08, 0.1, 0.94),
+        border_color: Some(Color::srgba(0.4, 0.7, 0.9, 0.7)),
+        padding: 12.0,
+    };
+
+    let zone_panel = builder.create_hidden_panel("zone_panel", zone_config, ZonePanel);
+    builder.add_title(zone_panel, "◆ 区域 ◆");
+    builder.add_small_text(
+        zone_panel,
+        "选择涂抹或擦除后按住左键在地图上拖动；洞区成员只在洞区内工作和闲逛",
+    );
+
+    let rows = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::top(Val::Px(8.0)),
+                ..default()
+            },
+            ZonePanelRows,
+        ))
+        .id();
+    commands.entity(zone_panel).add_child(rows);
+}
+
+/// 切换区域面板显示/隐藏（Z键），隐藏时收起画笔
+pub fn toggle_zone_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<(&mut UIPanel, &mut Node)>,
+    mut editor: ResMut<ZoneEditor>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyZ) {
+        toggle_panel_visibility(&mut panel_query, "zone_panel");
+        if editor.brush.is_some() {
+            editor.brush = None;
+        }
+    }
+}
+
+/// 处理区域面板按钮点击
+pub fn zone_button_system(
+    interaction_query: Query<(&Interaction, &ZoneButton), Changed<Interaction>>,
+    selected: Res<SelectedDwarf>,
+    dwarves: Query<&Dwarf>,
+    active_local: Res<ActiveLocalMap>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut editor: ResMut<ZoneEditor>,
+    mut logger: ResMut<crate::logger::GameLogger>,
+) {
+    let Some(coord) = active_local.coord else {
+        return;
+    };
+
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let selected_dwarf = selected.entity.and_then(|entity| dwarves.get(entity).ok());
+        let zones = map_registry.zones.entry(coord).or_default();
+        match *button {
+            ZoneButton::Create(kind) => {
+                if zones.len() >= MAX_ZONES {
+                    logger.warning(format!("每个地块最多划定 {} 个区域", MAX_ZONES));
+                    continue;
+                }
+                // 同类区域按序号命名，跳过已被占用的名字
+                let name = (1..)
+                    .map(|n| format!("{}{}", kind.label(), n))
+                    .find(|name| zones.iter().all(|zone| zone.name != *name))
+                    .unwrap_or_default();
+                logger.info(format!("划定了{}", name));
+                zones.push(Zone {
+                    name,
+                    kind,
+                    tiles: Default::default(),
+                    members: Vec::new(),
+                    refuge: false,
+                });
+                editor.selected = Some(zones.len() - 1);
+                editor.brush = Some(ZoneBrush::Paint);
+            }
+            ZoneButton::Brush(index, brush) => {
+                // 再次点击同一画笔收起画笔
+                if editor.selected == Some(index) && editor.brush == Some(brush) {
+                    editor.brush = None;
+                } else {
+                    editor.selected = Some(index);
+                    editor.brush = Some(brush);
+                }
+            }
+            ZoneButton::Delete(index) => {
+                if index < zones.len() {
+                    let zone = zones.remove(index);
+                    logger.info(format!("撤销了{}", zone.name));
+                    *editor = ZoneEditor::default();
+                }
+            }
+            ZoneButton::AddMember(index) => {
+                let Some(dwarf) = selected_dwarf else {
+                    logger.warning("请先选中一名矮人".to_string());
+                    continue;
+                };
+                if let Some(zone) = zones.get_mut(index) {
+                    if !zone.members.contains(&dwarf.name) {
+                        zone.members.push(dwarf.name.clone());
+                        logger.info(format!("{} 被分配到{}", dwarf.name, zone.name));
+                    }
+                }
+            }
+            ZoneButton::RemoveMember(index) => {
+                let Some(dwarf) = selected_dwarf else {
+                    logger.warning("请先选中一名矮人".to_string());
+                    continue;
+                };
+                if let Some(zone) = zones.get_mut(index) {
+                    let before = zone.members.len();
+                    zone.members.retain(|member| *member != dwarf.name);
+                    if zone.members.len() != before {
+                        logger.info(format!("{} 离开了{}", dwarf.name, zone.name));
+                    }
+                }
+            }
+            ZoneButton::Refuge(index) => {
+                let Some(refuge) = zones.get(index).map(|zone| !zone.refuge) else {
+                    continue;
+                };
+                // 每个地块只有一个避难洞区
+                for (i, zone) in zones.iter_mut().enumerate() {
+                    zone.refuge = refuge && i == index;
+                }
+                let name = &zones[index].name;
+                if refuge {
+                    logger.info(format!("{}被指定为避难所，警戒时平民撤到这里", name));
+                } else {
+                    logger.info(format!("{}不再是避难所", name));
+                }
+            }
+        }
+    }
+}
+
+/// 面板可见时，在区域、编辑状态或选中矮人变化后重建区域列表
+#[allow(clippy::too_many_arguments)]
+pub fn zone_panel_refresh_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    panel_query: Query<Ref<UIPanel>, With<ZonePanel>>,
+    rows_query: Query<Entity, With<ZonePanelRows>>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    editor: Res<ZoneEditor>,
+    selected: Res<SelectedDwarf>,
+) {
+    let Ok(panel) = panel_query.single() else {
+        return;
+    };
+    if panel.state != PanelState::Visible {
+        return;
+    }
+    if !panel.is_changed() && !map_registry.is_changed() && !editor.is_changed() && !selected.is_changed() {
+        return;
+    }
+
+    let Ok(rows) = rows_query.single() else {
+        return;
+    };
+    let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let zones = map_registry.local_zones(active_local.coord);
+
+    commands.entity(rows).despawn_related::<Children>();
+    commands.entity(rows).with_children(|parent| {
+        spawn_row(parent, |row| {
+            for kind in ZoneKind::ALL {
+                spawn_button(row, &font, &format!("新建{}", kind.label()), BUTTON_COLOR, ZoneButton::Create(kind));
+            }
+            spawn_label(row, &font, &format!("{}/{}", zones.len(), MAX_ZONES));
+        });
+        if let (Some(index), Some(brush)) = (editor.selected, editor.brush) {
+            if let Some(zone) = zones.get(index) {
+                let action = match brush {
+                    ZoneBrush::Paint => "涂抹",
+                    ZoneBrush::Erase => "擦除",
+                };
+                spawn_row(parent, |row| {
+                    spawn_label(row, &font, &format!("按住左键在地图上{}{}", action, zone.name));
+                });
+            }
+        }
+
+        for (index, zone) in zones.iter().enumerate() {
+            let brush_color = |brush: ZoneBrush| {
+                if editor.selected == Some(index) && editor.brush == Some(brush) {
+                    BRUSH_COLOR
+                } else {
+                    BUTTON_COLOR
+                }
+            };
+            spawn_row(parent, |row| {
+                spawn_label(row, &font, &format!("━━ {}（{} 格）", zone.name, zone.tiles.len()));
+                spawn_button(row, &font, "涂抹", brush_color(ZoneBrush::Paint), ZoneButton::Brush(index, ZoneBrush::Paint));
+                spawn_button(row, &font, "擦除", brush_color(ZoneBrush::Erase), ZoneButton::Brush(index, ZoneBrush::Erase));
+                spawn_button(row, &font, "删除", DELETE_COLOR, ZoneButton::Delete(index));
+            });
+            if zone.kind == ZoneKind::Burrow {
+                spawn_row(parent, |row| {
+                    let members = if zone.members.is_empty() {
+                        "暂无成员（不限制任何矮人）".to_string()
+                    } else {
+                        zone.members.join("、")
+                    };
+                    spawn_label(row, &font, &members);
+                });
+                spawn_row(parent, |row| {
+                    spawn_button(row, &font, "加入选中", BUTTON_COLOR, ZoneButton::AddMember(index));
+                    spawn_button(row, &font, "移出选中", BUTTON_COLOR, ZoneButton::RemoveMember(index));
+                    let (label, color) = if zone.refuge {
+                        ("避难所 ✔", BRUSH_COLOR)
+                    } else {
+                        ("设为避难所", BUTTON_COLOR)
+                    };
+                    spawn_button(row, &font, label, color, ZoneButton::Refuge(index));
+                });
+            }
+        }
+    });
+}
+
+fn spawn_row(parent: &mut ChildSpawnerCommands, build: impl FnOnce(&mut ChildSpawnerCommands)) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            margin: UiRect::top(Val::Px(2.0)),
+            ..default()
+        })
+        .with_children(build);
+}
+
+fn spawn_label(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, text: &str) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font: font.clone(),
+            font_size: 15.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.85, 0.92, 0.95)),
+        Node {
+            margin: UiRect::horizontal(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_button(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    label: &str,
+    color: Color,
+    button: ZoneButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                margin: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(color),
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+        });
+}
@@ -0,0 +1,125 @@
+use crate::resources::*;
+use crate::world::*;
+use bevy::prelude::*;
+
+/// 区域覆盖层的高度：在地形字符和网格线之上、矮人之下
+const OVERLAY_Z: f32 = 0.15;
+/// 选中区域的覆盖层加深到的透明度
+const SELECTED_OVERLAY_ALPHA: f32 = 0.45;
+
+/// 地图上的区域覆盖层方块
+#[derive(Component)]
+pub struct ZoneOverlay;
+
+/// 在地图上涂抹或擦除选中区域的地块：按住左键拖动即可连续涂抹。
+/// 需在矮人选择系统之前运行，涂抹消耗掉这次左键点击
+#[allow(clippy::too_many_arguments)]
+pub fn zone_paint_system(
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    editor: Res<ZoneEditor>,
+    active_local: Res<ActiveLocalMap>,
+    mut map_registry: ResMut<GeneratedMapsRegistry>,
+) {
+    let (Some(index), Some(brush), Some(coord)) = (editor.selected, editor.brush, active_local.coord) else {
+        return;
+    };
+    if !mouse_button.pressed(MouseButton::Left) {
+        return;
+    }
+    // 点在界面按钮上的不算
+    if interactions.iter().any(|interaction| *interaction != Interaction::None) {
+        return;
+    }
+
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+    let Ok(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
+        return;
+    };
+
+    // 转换为网格坐标
+    let grid_x = ((world_position.x + (WORLD_WIDTH as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+    let grid_y = ((world_position.y + (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0)) / TILE_SIZE) as i32;
+    if !(0..WORLD_WIDTH).contains(&grid_x) || !(0..WORLD_HEIGHT).contains(&grid_y) {
+        return;
+    }
+    mouse_button.clear_just_pressed(MouseButton::Left);
+
+    // 地块没有变化时不修改注册表，避免每帧重建覆盖层
+    let tile = (grid_x, grid_y);
+    let Some(zone) = map_registry.local_zones(Some(coord)).get(index) else {
+        return;
+    };
+    let unchanged = match brush {
+        ZoneBrush::Paint => zone.tiles.contains(&tile),
+        ZoneBrush::Erase => !zone.tiles.contains(&tile),
+    };
+    if unchanged {
+        return;
+    }
+    if let Some(zone) = map_registry.zones.get_mut(&coord).and_then(|zones| zones.get_mut(index)) {
+        match brush {
+            ZoneBrush::Paint => zone.tiles.insert(tile),
+            ZoneBrush::Erase => zone.tiles.remove(&tile),
+        };
+    }
+}
+
+/// 区域或选中状态变化后重建地图上的区域覆盖层
+pub fn zone_overlay_system(
+    mut commands: Commands,
+    overlays: Query<Entity, With<ZoneOverlay>>,
+    map_registry: Res<GeneratedMapsRegistry>,
+    active_local: Res<ActiveLocalMap>,
+    editor: Res<ZoneEditor>,
+) {
+    if !map_registry.is_changed() && !active_local.is_changed() && !editor.is_changed() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for (index, zone) in map_registry.local_zones(active_local.coord).iter().enumerate() {
+        let mut color = zone.kind.overlay_color();
+        if editor.selected == Some(index) {
+            color.set_alpha(SELECTED_OVERLAY_ALPHA);
+        }
+        for &(x, y) in zone.tiles.iter() {
+            let pos_x = x as f32 * TILE_SIZE - (WORLD_WIDTH as f32 * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0);
+            let pos_y = y as f32 * TILE_SIZE - (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0);
+            commands.spawn((
+                Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(pos_x, pos_y, OVERLAY_Z),
+                ZoneOverlay,
+            ));
+        }
+    }
+}
+
+/// 离开局部地图时移除区域覆盖层并退出编辑状态（区域数据保留在注册表中）
+pub fn cleanup_zone_overlays(
+    mut commands: Commands,
+    overlays: Query<Entity, With<ZoneOverlay>>,
+    mut editor: ResMut<ZoneEditor>,
+) {
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+    *editor = ZoneEditor::default();
+}
@@ -182,6 +182,18 @@ pub struct NotificationPanel;
 #[derive(Component)]
 pub struct DebugPanel;
 
+/// 劳动管理面板
+#[derive(Component)]
+pub struct LabourPanel;
+
+/// 军事面板
+#[derive(Component)]
+pub struct MilitaryPanel;
+
+/// 区域面板
+#[derive(Component)]
+pub struct ZonePanel;
+
 // ============ UI构建器 ============
 
 /// UI面板构建器 - 提供流式API构建面板
@@ -1,6 +1,11 @@
 use crate::components::*;
-use crate::resources::{ActiveLocalMap, WorldSeed, GeneratedMapsRegistry, StoredMapTile, StoredDwarf};
-use crate::world_map_data::{WorldAtlas, WorldBiome, WorldCell};
+use crate::connectivity::{is_walkable_terrain, repair_connectivity, RegionMap};
+use crate::pathfinding::NavGrid;
+use crate::resources::{
+    ActiveLocalMap, ChronicleKind, EmbarkPlan, FortressChronicle, GameTime,
+    GeneratedMapsRegistry, StoredDwarf, StoredMapTile, WorldSeed,
+};
+use crate::world_map_data::{fold_seed, WorldAtlas, WorldBiome, WorldCell, RIVER_FLOW_THRESHOLD};
 use bevy::prelude::*;
 use noise::{NoiseFn, Perlin};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
@@ -28,12 +33,9 @@ impl Default for GameWorld {
     }
 }
 
-/// 地形生成器 - 使用多层噪声创建自然地形
-struct TerrainGenerator {
-    elevation: Perlin,   // 高度噪声
-    moisture: Perlin,    // 湿度噪声
-    temperature: Perlin, // 温度噪声
-    detail: Perlin,      // 细节噪声
+/// 单个世界格子对局部地形的影响参数，可在相邻格子之间插值
+#[derive(Clone, Copy)]
+struct BiomeParams {
     elevation_bias: f64,
     moisture_bias: f64,
     temperature_bias: f64,
@@ -43,16 +45,11 @@ struct TerrainGenerator {
     tree_min_elevation: f64,
     stone_moisture_threshold: f64,
     river_band_multiplier: f64,
-    biome: Option<WorldBiome>,
 }
 
-impl TerrainGenerator {
-    fn new(seed: u32, context: Option<&WorldCell>) -> Self {
-        let mut generator = Self {
-            elevation: Perlin::new(seed),
-            moisture: Perlin::new(seed + 1),
-            temperature: Perlin::new(seed + 2),
-            detail: Perlin::new(seed + 3),
+impl Default for BiomeParams {
+    fn default() -> Self {
+        Self {
             elevation_bias: 0.0,
             moisture_bias: 0.0,
             temperature_bias: 0.0,
@@ -62,77 +59,50 @@ impl TerrainGenerator {
             tree_min_elevation: -0.1,
             stone_moisture_threshold: -0.2,
             river_band_multiplier: 1.0,
-            biome: context.map(|cell| cell.biome),
-        };
-
-        if let Some(cell) = context {
-            generator.elevation_bias = (cell.elevation as f64) * 0.35;
-            generator.moisture_bias = (cell.moisture as f64) * 0.45;
-            generator.temperature_bias = (cell.temperature as f64) * 0.3;
-            generator.apply_biome_modifiers(cell.biome);
         }
-
-        generator.clamp_biases();
-        generator
     }
+}
 
-    /// 获取指定位置的地形类型
-    fn get_terrain(&self, x: i32, y: i32) -> TerrainType {
-        let scale = 0.1; // 噪声缩放因子，值越小地形变化越平缓
-
-        // 多层噪声采样
-        let elevation = self.elevation.get([x as f64 * scale, y as f64 * scale]);
-        let moisture = self
-            .moisture
-            .get([x as f64 * scale * 0.8, y as f64 * scale * 0.8]);
-        let temperature = self
-            .temperature
-            .get([x as f64 * scale * 1.2, y as f64 * scale * 1.2]);
-        let detail = self
-            .detail
-            .get([x as f64 * scale * 3.0, y as f64 * scale * 3.0])
-            * 0.1;
-
-        let final_elevation = (elevation + detail + self.elevation_bias).clamp(-1.0, 1.0);
-        let final_moisture = (moisture + detail * 0.5 + self.moisture_bias).clamp(-1.0, 1.0);
-        let final_temperature = (temperature + self.temperature_bias).clamp(-1.0, 1.0);
-
-        let base = if final_elevation < self.water_cutoff {
-            TerrainType::Water
-        } else if final_elevation > self.mountain_cutoff {
-            if final_temperature > 0.3 {
-                TerrainType::Mountain
-            } else {
-                TerrainType::Stone
-            }
-        } else if final_moisture > self.tree_moisture_threshold
-            && final_elevation > self.tree_min_elevation
-        {
-            TerrainType::Tree
-        } else if final_moisture < self.stone_moisture_threshold {
-            TerrainType::Stone
-        } else {
-            TerrainType::Grass
-        };
-
-        self.adjust_for_biome(base, final_elevation, final_moisture, final_temperature)
+impl BiomeParams {
+    fn for_cell(cell: Option<&WorldCell>) -> Self {
+        let mut params = Self::default();
+        if let Some(cell) = cell {
+            params.elevation_bias = (cell.elevation as f64) * 0.35;
+            params.moisture_bias = (cell.moisture as f64) * 0.45;
+            params.temperature_bias = (cell.temperature as f64) * 0.3;
+            params.apply_biome_modifiers(cell.biome);
+        }
+        params.clamp_biases();
+        params
     }
 
-    /// 检查是否应该生成河流
-    fn is_river(&self, x: i32, y: i32) -> bool {
-        if matches!(self.biome, Some(WorldBiome::Ocean)) {
-            return false;
+    /// 按权重混合多个格子的参数
+    fn blend(samples: &[(BiomeParams, f64)]) -> Self {
+        let total: f64 = samples.iter().map(|(_, weight)| weight).sum();
+        let mut blended = Self {
+            elevation_bias: 0.0,
+            moisture_bias: 0.0,
+            temperature_bias: 0.0,
+            water_cutoff: 0.0,
+            mountain_cutoff: 0.0,
+            tree_moisture_threshold: 0.0,
+            tree_min_elevation: 0.0,
+            stone_moisture_threshold: 0.0,
+            river_band_multiplier: 0.0,
+        };
+        for (params, weight) in samples {
+            let w = weight / total.max(f64::EPSILON);
+            blended.elevation_bias += params.elevation_bias * w;
+            blended.moisture_bias += params.moisture_bias * w;
+            blended.temperature_bias += params.temperature_bias * w;
+            blended.water_cutoff += params.water_cutoff * w;
+            blended.mountain_cutoff += params.mountain_cutoff * w;
+            blended.tree_moisture_threshold += params.tree_moisture_threshold * w;
+            blended.tree_min_elevation += params.tree_min_elevation * w;
+            blended.stone_moisture_threshold += params.stone_moisture_threshold * w;
+            blended.river_band_multiplier += params.river_band_multiplier * w;
         }
-
-        let river_scale = 0.05;
-        let river_noise = self
-            .moisture
-            .get([x as f64 * river_scale, y as f64 * river_scale]);
-        let band = 0.05 * self.river_band_multiplier;
-        let elevation_sample =
-            self.elevation.get([x as f64 * 0.1, y as f64 * 0.1]) + self.elevation_bias;
-
-        (river_noise.abs() < band) && (elevation_sample < self.mountain_cutoff - 0.1)
+        blended
     }
 
     fn apply_biome_modifiers(&mut self, biome: WorldBiome) {
@@ -180,6 +150,14 @@ impl TerrainGenerator {
                 self.elevation_bias -= 0.25;
                 self.river_band_multiplier = 1.5;
             }
+            WorldBiome::Lake => {
+                self.water_cutoff = -0.05;
+                self.tree_moisture_threshold = 0.2;
+                self.stone_moisture_threshold = -0.4;
+                self.moisture_bias += 0.3;
+                self.elevation_bias -= 0.2;
+                self.river_band_multiplier = 1.2;
+            }
             WorldBiome::River => {
                 self.water_cutoff = -0.25;
                 self.tree_moisture_threshold = 0.1;
@@ -198,14 +176,230 @@ impl TerrainGenerator {
         }
     }
 
+    fn clamp_biases(&mut self) {
+        self.elevation_bias = self.elevation_bias.clamp(-0.9, 0.9);
+        self.moisture_bias = self.moisture_bias.clamp(-0.9, 0.9);
+        self.temperature_bias = self.temperature_bias.clamp(-0.9, 0.9);
+    }
+}
+
+
+/// 地形生成器 - 在世界全局坐标中采样多层噪声，并在相邻格子之间混合群落参数，
+/// 保证相邻局部地图在共享边缘处的地形、山脉与河流能够衔接
+struct TerrainGenerator {
+    elevation: Perlin,   // 高度噪声
+    moisture: Perlin,    // 湿度噪声
+    temperature: Perlin, // 温度噪声
+    detail: Perlin,      // 细节噪声
+    /// 局部地图左下角在世界全局坐标中的位置
+    origin: IVec2,
+    /// 当前格子及其周围3x3格子的群落参数（索引为 (dy + 1) * 3 + (dx + 1)）
+    neighbourhood: [(BiomeParams, Option<WorldBiome>); 9],
+    /// 大地图河流在本地图内的河道
+    river_channels: Vec<RiverChannel>,
+    biome: Option<WorldBiome>,
+}
+
+/// 大地图河流在局部地图中的河道：从边缘穿越点经内折点通向地图中心
+struct RiverChannel {
+    crossing: Vec2,
+    inset: Vec2,
+    half_width: f32,
+}
+
+/// 河道最小半宽（格）
+const RIVER_CHANNEL_HALF_WIDTH: f32 = 1.0;
+/// 河道最大半宽（格）
+const RIVER_CHANNEL_MAX_HALF_WIDTH: f32 = 3.0;
+/// 河道在边缘处垂直延伸的长度（格），保证两侧地图的河道完全对齐
+const RIVER_CHANNEL_INSET: f32 = 4.0;
+
+impl TerrainGenerator {
+    fn new(noise_seed: u32, atlas: &WorldAtlas, coord: IVec2) -> Self {
+        let biome = atlas.cell_at(coord).map(|cell| cell.biome);
+
+        // 超出大地图范围的邻居沿用当前格子的参数
+        let mut neighbourhood = [(BiomeParams::default(), biome); 9];
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let neighbour = atlas
+                    .cell_at(coord + IVec2::new(dx, dy))
+                    .or_else(|| atlas.cell_at(coord));
+                neighbourhood[((dy + 1) * 3 + (dx + 1)) as usize] = (
+                    BiomeParams::for_cell(neighbour),
+                    neighbour.map(|cell| cell.biome),
+                );
+            }
+        }
+
+        Self {
+            elevation: Perlin::new(noise_seed),
+            moisture: Perlin::new(noise_seed.wrapping_add(1)),
+            temperature: Perlin::new(noise_seed.wrapping_add(2)),
+            detail: Perlin::new(noise_seed.wrapping_add(3)),
+            origin: IVec2::new(coord.x * WORLD_WIDTH, coord.y * WORLD_HEIGHT),
+            neighbourhood,
+            river_channels: river_channels(noise_seed, atlas, coord),
+            biome,
+        }
+    }
+
+    /// 局部坐标转换为世界全局噪声坐标
+    fn global(&self, x: i32, y: i32) -> (f64, f64) {
+        ((self.origin.x + x) as f64, (self.origin.y + y) as f64)
+    }
+
+    /// 该格在相邻世界格子之间的混合权重（以格子中心为插值点）
+    fn blend_weights(&self, x: i32, y: i32) -> [(usize, f64); 4] {
+        let u = (x as f64 + 0.5) / WORLD_WIDTH as f64 - 0.5;
+        let v = (y as f64 + 0.5) / WORLD_HEIGHT as f64 - 0.5;
+        let (ix, tx) = (u.floor() as i32, smoothstep(u - u.floor()));
+        let (iy, ty) = (v.floor() as i32, smoothstep(v - v.floor()));
+        let index = |dx: i32, dy: i32| ((dy + 1) * 3 + (dx + 1)) as usize;
+
+        [
+            (index(ix, iy), (1.0 - tx) * (1.0 - ty)),
+            (index(ix + 1, iy), tx * (1.0 - ty)),
+            (index(ix, iy + 1), (1.0 - tx) * ty),
+            (index(ix + 1, iy + 1), tx * ty),
+        ]
+    }
+
+    /// 混合后的群落参数
+    fn params_at(&self, x: i32, y: i32) -> BiomeParams {
+        let samples = self
+            .blend_weights(x, y)
+            .map(|(index, weight)| (self.neighbourhood[index].0, weight));
+        BiomeParams::blend(&samples)
+    }
+
+    /// 该格的主导群落（加入细节噪声扰动，避免格子交界处出现直线）
+    fn biome_at(&self, x: i32, y: i32) -> Option<WorldBiome> {
+        let (gx, gy) = self.global(x, y);
+        let jitter = self.detail.get([gx * 0.15, gy * 0.15]) * 0.2;
+
+        let mut totals: Vec<(Option<WorldBiome>, f64)> = Vec::with_capacity(4);
+        for (index, weight) in self.blend_weights(x, y) {
+            let biome = self.neighbourhood[index].1;
+            match totals.iter_mut().find(|(existing, _)| *existing == biome) {
+                Some((_, total)) => *total += weight,
+                None => totals.push((biome, weight)),
+            }
+        }
+        if let Some(first) = totals.first_mut() {
+            first.1 += jitter;
+        }
+
+        totals
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .and_then(|(biome, _)| biome)
+            .or(self.biome)
+    }
+
+    /// 获取指定位置的地形类型
+    fn get_terrain(&self, x: i32, y: i32) -> TerrainType {
+        let scale = 0.1; // 噪声缩放因子，值越小地形变化越平缓
+        let params = self.params_at(x, y);
+        let (gx, gy) = self.global(x, y);
+
+        // 多层噪声采样
+        let elevation = self.elevation.get([gx * scale, gy * scale]);
+        let moisture = self.moisture.get([gx * scale * 0.8, gy * scale * 0.8]);
+        let temperature = self.temperature.get([gx * scale * 1.2, gy * scale * 1.2]);
+        let detail = self.detail.get([gx * scale * 3.0, gy * scale * 3.0]) * 0.1;
+
+        let final_elevation = (elevation + detail + params.elevation_bias).clamp(-1.0, 1.0);
+        let final_moisture = (moisture + detail * 0.5 + params.moisture_bias).clamp(-1.0, 1.0);
+        let final_temperature = (temperature + params.temperature_bias).clamp(-1.0, 1.0);
+
+        let base = if final_elevation < params.water_cutoff {
+            TerrainType::Water
+        } else if final_elevation > params.mountain_cutoff {
+            if final_temperature > 0.3 {
+                TerrainType::Mountain
+            } else {
+                TerrainType::Stone
+            }
+        } else if final_moisture > params.tree_moisture_threshold
+            && final_elevation > params.tree_min_elevation
+        {
+            TerrainType::Tree
+        } else if final_moisture < params.stone_moisture_threshold {
+            TerrainType::Stone
+        } else {
+            TerrainType::Grass
+        };
+
+        Self::adjust_for_biome(
+            self.biome_at(x, y),
+            base,
+            final_elevation,
+            final_moisture,
+            final_temperature,
+        )
+    }
+
+    /// 最终地形（叠加河流覆盖，山脉不会被河流切开）
+    fn terrain_at(&self, x: i32, y: i32) -> TerrainType {
+        let terrain_type = self.get_terrain(x, y);
+        if self.is_river(x, y) && terrain_type != TerrainType::Mountain {
+            TerrainType::Water
+        } else {
+            terrain_type
+        }
+    }
+
+    /// 检查是否应该生成河流
+    fn is_river(&self, x: i32, y: i32) -> bool {
+        if self.on_river_channel(x, y) {
+            return true;
+        }
+        if matches!(self.biome_at(x, y), Some(WorldBiome::Ocean | WorldBiome::Lake)) {
+            return false;
+        }
+
+        let params = self.params_at(x, y);
+        let (gx, gy) = self.global(x, y);
+        let river_scale = 0.05;
+        let river_noise = self.moisture.get([gx * river_scale, gy * river_scale]);
+        let band = 0.05 * params.river_band_multiplier;
+        let elevation_sample = self.elevation.get([gx * 0.1, gy * 0.1]) + params.elevation_bias;
+
+        (river_noise.abs() < band) && (elevation_sample < params.mountain_cutoff - 0.1)
+    }
+
+    /// 检查是否位于贯穿大地图河流的河道上
+    fn on_river_channel(&self, x: i32, y: i32) -> bool {
+        let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+        let (gx, gy) = self.global(x, y);
+        let center = Vec2::new(WORLD_WIDTH as f32 / 2.0, WORLD_HEIGHT as f32 / 2.0);
+
+        self.river_channels.iter().any(|channel| {
+            let (crossing, inset) = (channel.crossing, channel.inset);
+            // 边缘段保持笔直，保证与相邻地图对齐
+            if distance_to_segment(point, crossing, inset) <= channel.half_width {
+                return true;
+            }
+            // 内段随噪声蜿蜒，两端的摆动为零
+            let t = segment_param(point, inset, center);
+            let meander = (std::f32::consts::PI * t).sin()
+                * self.detail.get([gx * 0.05, gy * 0.05]) as f32
+                * 6.0;
+            let direction = (center - inset).normalize_or_zero();
+            let normal = Vec2::new(-direction.y, direction.x);
+            distance_to_segment(point - normal * meander, inset, center) <= channel.half_width
+        })
+    }
+
     fn adjust_for_biome(
-        &self,
+        biome: Option<WorldBiome>,
         terrain: TerrainType,
         elevation: f64,
         moisture: f64,
         temperature: f64,
     ) -> TerrainType {
-        match self.biome {
+        match biome {
             Some(WorldBiome::Desert) => match terrain {
                 TerrainType::Tree if moisture < 0.6 => TerrainType::Grass,
                 TerrainType::Grass if temperature > 0.2 => TerrainType::Stone,
@@ -234,6 +428,11 @@ impl TerrainGenerator {
                 TerrainType::Stone if elevation < 0.02 => TerrainType::Water,
                 _ => terrain,
             },
+            Some(WorldBiome::Lake) => match terrain {
+                TerrainType::Grass if elevation < -0.1 => TerrainType::Water,
+                TerrainType::Stone if moisture > 0.2 => TerrainType::Grass,
+                _ => terrain,
+            },
             Some(WorldBiome::River) => match terrain {
                 TerrainType::Grass if moisture > 0.0 => TerrainType::Tree,
                 TerrainType::Stone if moisture > 0.15 => TerrainType::Grass,
@@ -249,11 +448,196 @@ impl TerrainGenerator {
         }
     }
 
-    fn clamp_biases(&mut self) {
-        self.elevation_bias = self.elevation_bias.clamp(-0.9, 0.9);
-        self.moisture_bias = self.moisture_bias.clamp(-0.9, 0.9);
-        self.temperature_bias = self.temperature_bias.clamp(-0.9, 0.9);
+}
+
+/// 平滑插值曲线，使格子中心附近保持原群落特征
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// 点在线段上的投影参数（0..1）
+fn segment_param(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let length_sq = segment.length_squared();
+    if length_sq <= f32::EPSILON {
+        return 0.0;
+    }
+    ((point - start).dot(segment) / length_sq).clamp(0.0, 1.0)
+}
+
+/// 点到线段的距离
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let t = segment_param(point, start, end);
+    point.distance(start + (end - start) * t)
+}
+
+/// 两个相邻世界格子的共享边上流过的河流（对两侧对称），返回上游格子的流量
+fn river_across_edge(a: &WorldCell, b: &WorldCell) -> Option<f32> {
+    let flows = |from: &WorldCell, to: &WorldCell| {
+        from.downstream == Some(to.coord) && from.flow >= RIVER_FLOW_THRESHOLD
+    };
+    if flows(a, b) {
+        Some(a.flow)
+    } else if flows(b, a) {
+        Some(b.flow)
+    } else {
+        None
+    }
+}
+
+/// 河流在共享边上的穿越位置，仅由种子与边本身决定，两侧地图计算结果一致
+fn river_crossing_offset(noise_seed: u32, a: IVec2, b: IVec2, length: i32) -> i32 {
+    let low = a.min(b);
+    let axis = if a.x != b.x { 0u64 } else { 1u64 };
+    let key = ((low.x as u64) << 40) ^ ((low.y as u64) << 20) ^ axis;
+    let mut rng = SmallRng::seed_from_u64(noise_seed as u64 ^ key ^ 0x5249_5645_5253);
+    rng.gen_range(length / 4..length * 3 / 4)
+}
+
+/// 当前格子的河道列表：沿大地图水流方向与相邻格子相连
+fn river_channels(noise_seed: u32, atlas: &WorldAtlas, coord: IVec2) -> Vec<RiverChannel> {
+    let Some(cell) = atlas.cell_at(coord) else {
+        return Vec::new();
+    };
+    let width = WORLD_WIDTH as f32;
+    let height = WORLD_HEIGHT as f32;
+    let mut channels = Vec::new();
+
+    for direction in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+        let neighbour_coord = coord + direction;
+        let Some(neighbour) = atlas.cell_at(neighbour_coord) else {
+            continue;
+        };
+        let Some(flow) = river_across_edge(cell, neighbour) else {
+            continue;
+        };
+        // 流量越大河道越宽
+        let half_width = (RIVER_CHANNEL_HALF_WIDTH + (flow / RIVER_FLOW_THRESHOLD).sqrt() * 0.4)
+            .min(RIVER_CHANNEL_MAX_HALF_WIDTH);
+
+        let length = if direction.x != 0 { WORLD_HEIGHT } else { WORLD_WIDTH };
+        let offset = river_crossing_offset(noise_seed, coord, neighbour_coord, length) as f32 + 0.5;
+        let (crossing, inset) = match (direction.x, direction.y) {
+            (1, _) => (Vec2::new(width, offset), Vec2::new(width - RIVER_CHANNEL_INSET, offset)),
+            (-1, _) => (Vec2::new(0.0, offset), Vec2::new(RIVER_CHANNEL_INSET, offset)),
+            (_, 1) => (Vec2::new(offset, height), Vec2::new(offset, height - RIVER_CHANNEL_INSET)),
+            _ => (Vec2::new(offset, 0.0), Vec2::new(offset, RIVER_CHANNEL_INSET)),
+        };
+        channels.push(RiverChannel {
+            crossing,
+            inset,
+            half_width,
+        });
+    }
+    channels
+}
+
+/// 整个世界共用的地形噪声种子
+fn atlas_noise_seed(atlas: &WorldAtlas) -> u32 {
+    fold_seed(atlas.seed)
+}
+
+/// 出发前对候选地块的地形勘察结果
+#[derive(Clone, Debug, Default)]
+pub struct SiteSurvey {
+    pub grass: usize,
+    pub stone: usize,
+    pub tree: usize,
+    pub water: usize,
+    pub mountain: usize,
+    /// 最大连通可行走区域的格数
+    pub largest_region: usize,
+    /// 主区域足够大，适合出发
+    pub playable: bool,
+}
+
+impl SiteSurvey {
+    /// 某类地形占整张局部地图的百分比
+    pub fn percent(&self, count: usize) -> f32 {
+        count as f32 * 100.0 / (WORLD_WIDTH * WORLD_HEIGHT) as f32
+    }
+}
+
+/// 生成整张局部地图的地形网格（索引为 y * WORLD_WIDTH + x），
+/// 并修复主区域过小的连通性。返回地形网格和被修复的格子数
+fn generate_terrain_grid(generator: &TerrainGenerator) -> (Vec<TerrainType>, usize) {
+    let mut terrain = Vec::with_capacity((WORLD_WIDTH * WORLD_HEIGHT) as usize);
+    for y in 0..WORLD_HEIGHT {
+        for x in 0..WORLD_WIDTH {
+            terrain.push(generator.terrain_at(x, y));
+        }
+    }
+    let repaired = repair_connectivity(&mut terrain);
+    (terrain, repaired)
+}
+
+/// 勘察地块：按与 setup_world 相同的方式生成地形并统计（不生成实体）
+pub fn survey_site(atlas: &WorldAtlas, coord: IVec2) -> SiteSurvey {
+    let generator = TerrainGenerator::new(atlas_noise_seed(atlas), atlas, coord);
+    let (terrain, _) = generate_terrain_grid(&generator);
+    let mut survey = SiteSurvey::default();
+
+    for terrain_type in terrain.iter() {
+        match terrain_type {
+            TerrainType::Grass | TerrainType::Dirt => survey.grass += 1,
+            TerrainType::Stone => survey.stone += 1,
+            TerrainType::Tree | TerrainType::Sapling => survey.tree += 1,
+            TerrainType::Water => survey.water += 1,
+            TerrainType::Mountain => survey.mountain += 1,
+        }
     }
+
+    let regions = RegionMap::from_terrain(&terrain);
+    survey.largest_region = regions
+        .largest_region()
+        .map(|id| regions.region_size(id))
+        .unwrap_or(0);
+    survey.playable = regions.is_playable();
+    survey
+}
+
+/// 在最大连通区域中为每名矮人挑选互不重复的出生格：
+/// 以靠近区域重心且周围3x3都可行走的格子为锚点，按距离由近到远分配
+fn pick_spawn_tiles(regions: &RegionMap, count: usize) -> Vec<(i32, i32)> {
+    let Some(main) = regions.largest_region() else {
+        return Vec::new();
+    };
+    let region = regions.tiles_in(main);
+    if region.is_empty() {
+        return Vec::new();
+    }
+
+    let (sum_x, sum_y) = region
+        .iter()
+        .fold((0i64, 0i64), |(sx, sy), (x, y)| (sx + *x as i64, sy + *y as i64));
+    let centroid = Vec2::new(
+        sum_x as f32 / region.len() as f32,
+        sum_y as f32 / region.len() as f32,
+    );
+
+    let open_area = |(x, y): (i32, i32)| {
+        (-1..=1).all(|dy| (-1..=1).all(|dx| regions.region_at(x + dx, y + dy) == Some(main)))
+    };
+    let distance = |from: Vec2, (x, y): (i32, i32)| from.distance_squared(Vec2::new(x as f32, y as f32));
+
+    let anchor = region
+        .iter()
+        .copied()
+        .filter(|tile| open_area(*tile))
+        .min_by(|a, b| distance(centroid, *a).total_cmp(&distance(centroid, *b)))
+        .or_else(|| {
+            region
+                .iter()
+                .copied()
+                .min_by(|a, b| distance(centroid, *a).total_cmp(&distance(centroid, *b)))
+        })
+        .unwrap_or(region[0]);
+    let anchor = Vec2::new(anchor.0 as f32, anchor.1 as f32);
+
+    let mut tiles = region;
+    tiles.sort_by(|a, b| distance(anchor, *a).total_cmp(&distance(anchor, *b)));
+    tiles.truncate(count);
+    tiles
 }
 
 /// 从存储中恢复地图
@@ -281,6 +665,7 @@ fn restore_map_from_storage(
                 terrain_type: tile.terrain_type,
                 walkable: tile.walkable,
                 resource_richness: tile.resource_richness,
+                construction: tile.construction,
             },
             GridPosition { x, y },
         ));
@@ -329,6 +714,7 @@ fn restore_map_from_storage(
 }
 
 /// 生成世界地形 - 改进版，使用噪声生成并支持地图持久化
+#[allow(clippy::too_many_arguments)]
 pub fn setup_world(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -336,6 +722,8 @@ pub fn setup_world(
     active_local: Res<ActiveLocalMap>,
     world_atlas: Res<WorldAtlas>,
     mut map_registry: ResMut<GeneratedMapsRegistry>,
+    mut region_map: ResMut<RegionMap>,
+    mut nav_grid: ResMut<NavGrid>,
     mut logger: ResMut<crate::logger::GameLogger>,
 ) {
     let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
@@ -354,45 +742,59 @@ pub fn setup_world(
         // 地图已存在，从存储中恢复
         logger.info(format!("恢复已生成的地图: {:?}", current_coord));
         restore_map_from_storage(&mut commands, &font, stored_map, current_coord);
+        let mut terrain = vec![TerrainType::Mountain; (WORLD_WIDTH * WORLD_HEIGHT) as usize];
+        for tile in stored_map.iter() {
+            terrain[(tile.y * WORLD_WIDTH + tile.x) as usize] = tile.terrain_type;
+        }
+        *region_map = RegionMap::from_terrain(&terrain);
+        *nav_grid = NavGrid::from_terrain(&terrain);
         return;
     }
     
     // 地图不存在，生成新地图
     logger.info(format!("生成新地图: {:?}", current_coord));
     
-    let mut rng = SmallRng::seed_from_u64(world_seed.seed as u64 + 
-        (current_coord.x as u64 * 1000 + current_coord.y as u64)); // 每个地块有不同的种子偏移
+    let mut rng = SmallRng::seed_from_u64(world_seed.seed.wrapping_add(
+        current_coord.x as u64 * 1000 + current_coord.y as u64)); // 每个地块有不同的种子偏移
     
-    let selected_cell = world_atlas.cell_at(current_coord).cloned();
-
-    // 创建地形生成器（使用资源中的种子及选中世界格子上下文）
-    let generator = TerrainGenerator::new(world_seed.seed, selected_cell.as_ref());
-    let biome = generator.biome;
-    let richness_bias = match biome {
+    // 创建地形生成器（噪声种子取自整个世界，使相邻地图在边缘处衔接）
+    let generator = TerrainGenerator::new(atlas_noise_seed(&world_atlas), &world_atlas, current_coord);
+    let richness_bias = match generator.biome {
         Some(WorldBiome::Forest) => 1.1,
         Some(WorldBiome::Desert) => 0.85,
         Some(WorldBiome::Mountain) => 1.2,
         Some(WorldBiome::Swamp) => 1.05,
         Some(WorldBiome::River) => 1.05,
         Some(WorldBiome::Ocean) => 0.9,
+        Some(WorldBiome::Lake) => 1.0,
         Some(WorldBiome::Tundra) => 0.9,
         _ => 1.0,
     };
     
+    // 先生成整张地形网格，确保主区域连通
+    let (terrain_grid, repaired) = generate_terrain_grid(&generator);
+    if repaired > 0 {
+        logger.info(format!("连通性修复: 开辟通道改动了 {} 个格子", repaired));
+    }
+    *region_map = RegionMap::from_terrain(&terrain_grid);
+    *nav_grid = NavGrid::from_terrain(&terrain_grid);
+    if !region_map.is_playable() {
+        logger.warning(format!(
+            "地块 {:?} 的主区域仅占 {:.0}%，可活动空间很小",
+            current_coord,
+            region_map.main_region_fraction() * 100.0
+        ));
+    }
+
     // 用于存储生成的地图数据（一维数组）
     let mut stored_tiles = Vec::with_capacity((WORLD_WIDTH * WORLD_HEIGHT) as usize);
 
     for x in 0..WORLD_WIDTH {
         for y in 0..WORLD_HEIGHT {
-            // 使用噪声生成地形
-            let mut terrain_type = generator.get_terrain(x, y);
-
-            // 检查河流覆盖
-            if generator.is_river(x, y) && terrain_type != TerrainType::Mountain {
-                terrain_type = TerrainType::Water;
-            }
-
-            let walkable = !matches!(terrain_type, TerrainType::Water | TerrainType::Mountain);
+            // 使用噪声生成地形，群落在格子交界处混合
+            let biome = generator.biome_at(x, y);
+            let terrain_type = terrain_grid[(y * WORLD_WIDTH + x) as usize];
+            let walkable = is_walkable_terrain(terrain_type);
 
             // 更好看的颜色和随机变化
             let color_variation = rng.gen_range(-0.05..0.05);
@@ -406,7 +808,8 @@ pub fn setup_world(
                 y as f32 * TILE_SIZE - (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0);
 
             // 计算资源丰富度（基于细节噪声并结合世界格子偏好）
-            let detail_noise = generator.detail.get([x as f64 * 0.3, y as f64 * 0.3]);
+            let (gx, gy) = generator.global(x, y);
+            let detail_noise = generator.detail.get([gx * 0.3, gy * 0.3]);
             let base_richness = 0.8 + (detail_noise as f32 + 1.0) * 0.35;
             let mut resource_richness = (base_richness * richness_bias).clamp(0.4, 1.8);
 
@@ -424,6 +827,7 @@ pub fn setup_world(
                     (WorldBiome::River, TerrainType::Water) => 1.25,
                     (WorldBiome::River, TerrainType::Grass) => 1.1,
                     (WorldBiome::Ocean, TerrainType::Water) => 1.15,
+                    (WorldBiome::Lake, TerrainType::Water) => 1.2,
                     (WorldBiome::Tundra, TerrainType::Grass) => 0.85,
                     (WorldBiome::Tundra, TerrainType::Stone) => 1.1,
                     _ => 1.0,
@@ -451,6 +855,7 @@ pub fn setup_world(
                     terrain_type,
                     walkable,
                     resource_richness,
+                    construction: None,
                 },
                 GridPosition { x, y },
             ));
@@ -516,6 +921,8 @@ pub fn setup_world(
                 has_tree_sway,
                 water_phase,
                 tree_offset,
+                construction: None,
+                growth_hours: 0,
             });
         }
     }
@@ -530,8 +937,8 @@ pub fn setup_world(
     }
 }
 
-/// 恢复保存的矮人
-fn restore_dwarf(commands: &mut Commands, font: &Handle<Font>, stored: &StoredDwarf) {
+/// 根据存储数据生成矮人实体（恢复存档、移民和出生共用）
+pub fn spawn_stored_dwarf(commands: &mut Commands, font: &Handle<Font>, stored: &StoredDwarf) {
     let pos_x = stored.grid_x as f32 * TILE_SIZE - (WORLD_WIDTH as f32 * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0);
     let pos_y = stored.grid_y as f32 * TILE_SIZE - (WORLD_HEIGHT as f32 * TILE_SIZE / 2.0) + (TILE_SIZE / 2.0);
     
@@ -549,7 +956,18 @@ fn restore_dwarf(commands: &mut Commands, font: &Handle<Font>, stored: &StoredDw
                 health: stored.health,
                 hunger: stored.hunger,
                 happiness: stored.happiness,
+                fatigue: stored.fatigue,
+                thirst: stored.thirst,
             },
+            stored.skills.clone(),
+            stored.labours.clone(),
+            stored.personality.clone(),
+            stored.thoughts.clone(),
+            stored.relationships.clone(),
+            stored.age.clone(),
+            stored.identity.clone(),
+            stored.equipment.clone(),
+            stored.shift,
             GridPosition {
                 x: stored.grid_x,
                 y: stored.grid_y,
@@ -626,12 +1044,16 @@ fn restore_dwarf(commands: &mut Commands, font: &Handle<Font>, stored: &StoredDw
 }
 
 /// 生成矮人 - 改进版，只在出生点生成矮人，支持恢复已保存的矮人
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_dwarves(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    terrain_query: Query<(&GridPosition, &Terrain)>,
+    region_map: Res<RegionMap>,
     mut map_registry: ResMut<GeneratedMapsRegistry>,
     active_local: Res<ActiveLocalMap>,
+    game_time: Res<GameTime>,
+    embark_plan: Res<EmbarkPlan>,
+    mut chronicle: ResMut<FortressChronicle>,
     mut logger: ResMut<crate::logger::GameLogger>,
 ) {
     // 获取当前地块坐标
@@ -647,7 +1069,7 @@ pub fn spawn_dwarves(
         let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
         
         for stored in stored_dwarves.clone() {
-            restore_dwarf(&mut commands, &font, &stored);
+            spawn_stored_dwarf(&mut commands, &font, &stored);
         }
         return;
     }
@@ -668,127 +1090,49 @@ pub fn spawn_dwarves(
         return;
     }
     
-    logger.info(format!("在出生点 {:?} 生成 {} 个矮人", spawn_coord, 7));
+    // 只在最大连通区域内挑选出生位置，避免矮人被困在孤岛上
+    let spawn_tiles = pick_spawn_tiles(&region_map, embark_plan.dwarves.len());
+    if spawn_tiles.len() < embark_plan.dwarves.len() {
+        logger.warning(format!(
+            "最大可行走区域只能容纳 {} 个矮人（计划 {} 个）",
+            spawn_tiles.len(),
+            embark_plan.dwarves.len()
+        ));
+    }
+    if spawn_tiles.is_empty() {
+        logger.error("地图上没有任何可行走位置！跳过矮人生成".to_string());
+        return;
+    }
+
+    logger.info(format!("在出生点 {:?} 生成 {} 个矮人", spawn_coord, spawn_tiles.len()));
     
-    // 标记矮人已生成
+    // 标记矮人已生成，第一个矮人的落脚处作为要塞仓库
     map_registry.dwarves_spawned = true;
+    map_registry.stockpile = spawn_tiles.first().copied();
+    chronicle.record(
+        &game_time,
+        ChronicleKind::Founded,
+        format!("要塞在地块 ({}, {}) 建立", spawn_coord.x, spawn_coord.y),
+    );
     
-    let dwarf_names = vec!["乌里克", "索林", "巴林", "朵莉", "芬恩", "格洛因", "诺力"];
     let font = asset_server.load("fonts/sarasa-gothic-sc-regular.ttf");
+    let founder_relationships = founder_relationships(&embark_plan);
 
-    let mut rng = rand::thread_rng();
-
-    // 寻找世界中心附近的可行走位置
-    let center_x = WORLD_WIDTH / 2;
-    let center_y = WORLD_HEIGHT / 2;
-
-    // 为每个矮人单独寻找生成位置
-    for (_i, name) in dwarf_names.iter().enumerate() {
-        let mut grid_x = center_x;
-        let mut grid_y = center_y;
-        let mut found_safe_spot = false;
-
-        // 尝试多次随机寻找安全位置
-        for _ in 0..200 {
-            // 在中心附近随机选择位置
-            let test_x = center_x + rng.gen_range(-20..20);
-            let test_y = center_y + rng.gen_range(-20..20);
-
-            // 边界检查
-            if test_x < 2 || test_x >= WORLD_WIDTH - 2 || test_y < 2 || test_y >= WORLD_HEIGHT - 2 {
-                continue;
-            }
-
-            // 检查该位置及周围3x3区域是否全部可行走（查询实际地形）
-            let mut all_safe = true;
-            for dy in -1..=1 {
-                for dx in -1..=1 {
-                    let check_x = test_x + dx;
-                    let check_y = test_y + dy;
-
-                    let mut is_walkable = false;
-                    for (terrain_pos, terrain) in terrain_query.iter() {
-                        if terrain_pos.x == check_x && terrain_pos.y == check_y {
-                            is_walkable = terrain.walkable;
-                            break;
-                        }
-                    }
-
-                    if !is_walkable {
-                        all_safe = false;
-                        break;
-                    }
-                }
-                if !all_safe {
-                    break;
-                }
-            }
-
-            if all_safe {
-                grid_x = test_x;
-                grid_y = test_y;
-                found_safe_spot = true;
-                break;
-            }
-        }
-
-        // 如果没找到，螺旋搜索（查询实际地形）
-        if !found_safe_spot {
-            'spiral: for radius in 1..40 {
-                for angle_step in 0..(radius * 8) {
-                    let angle =
-                        (angle_step as f32 / (radius * 8) as f32) * std::f32::consts::PI * 2.0;
-                    let test_x = center_x + (angle.cos() * radius as f32) as i32;
-                    let test_y = center_y + (angle.sin() * radius as f32) as i32;
-
-                    // 边界检查
-                    if test_x < 0 || test_x >= WORLD_WIDTH || test_y < 0 || test_y >= WORLD_HEIGHT {
-                        continue;
-                    }
-
-                    // 查询该位置的实际地形
-                    let mut is_walkable = false;
-                    for (terrain_pos, terrain) in terrain_query.iter() {
-                        if terrain_pos.x == test_x && terrain_pos.y == test_y {
-                            is_walkable = terrain.walkable;
-                            break;
-                        }
-                    }
-
-                    if is_walkable {
-                        grid_x = test_x;
-                        grid_y = test_y;
-                        found_safe_spot = true;
-                        break 'spiral;
-                    }
-                }
-            }
-        }
+    for ((embark_dwarf, (grid_x, grid_y)), relationships) in embark_plan
+        .dwarves
+        .iter()
+        .zip(spawn_tiles)
+        .zip(founder_relationships)
+    {
+        let name = embark_dwarf.identity.full_name();
+        let mut skills = Skills::default();
+        skills.set_level(embark_dwarf.skill, embark_dwarf.level);
 
-        // 最终后备：全局搜索第一个可行走位置
-        if !found_safe_spot {
-            logger.warning(format!("矮人 {} 无法在中心附近找到位置，使用全局搜索", name));
-            'global: for search_x in 0..WORLD_WIDTH {
-                for search_y in 0..WORLD_HEIGHT {
-                    for (terrain_pos, terrain) in terrain_query.iter() {
-                        if terrain_pos.x == search_x
-                            && terrain_pos.y == search_y
-                            && terrain.walkable
-                        {
-                            grid_x = search_x;
-                            grid_y = search_y;
-                            found_safe_spot = true;
-                            break 'global;
-                        }
-                    }
-                }
-            }
-        }
-
-        if !found_safe_spot {
-            logger.error(format!("矮人 {} 无法找到任何可行走位置！跳过生成", name));
-            continue;
-        }
+        chronicle.record(
+            &game_time,
+            ChronicleKind::Migrant,
+            format!("{} 随首批移民抵达要塞", name),
+        );
 
         // 根据网格位置计算世界坐标（与地形对齐）
         let x_pos =
@@ -805,7 +1149,16 @@ pub fn spawn_dwarves(
                     ..default()
                 },
                 Transform::from_xyz(x_pos, y_pos, 2.0),
-                Dwarf::new(name.to_string()),
+                Dwarf::new(name),
+                skills,
+                Labours::default(),
+                Personality::random(&mut rand::thread_rng()),
+                Thoughts::default(),
+                relationships,
+                embark_dwarf.identity.age_on(game_time.day),
+                embark_dwarf.identity.clone(),
+                Equipment::for_specialty(embark_dwarf.skill),
+                Shift::default(),
                 GridPosition {
                     x: grid_x,
                     y: grid_y,
@@ -882,8 +1235,53 @@ pub fn spawn_dwarves(
     }
 }
 
+/// 首批移民一路同行，彼此已经相识；其中偶尔有兄弟姐妹
+fn founder_relationships(embark_plan: &EmbarkPlan) -> Vec<Relationships> {
+    let mut rng = rand::thread_rng();
+    let names: Vec<String> = embark_plan.dwarves.iter().map(|d| d.identity.full_name()).collect();
+    let mut relationships = vec![Relationships::default(); names.len()];
+    for i in 0..names.len() {
+        for j in (i + 1)..names.len() {
+            let affinity = rng.gen_range(0.0..20.0);
+            relationships[i].adjust(&names[j], affinity);
+            relationships[j].adjust(&names[i], affinity);
+            if rng.gen_bool(0.1) {
+                relationships[i].set_bond(&names[j], Bond::Sibling);
+                relationships[j].set_bond(&names[i], Bond::Sibling);
+            }
+        }
+    }
+    relationships
+}
+
+/// 建筑结构的外观 (背景色, 字符, 字符颜色)：颜色取自材料，每种结构有自己的字符
+pub fn construction_visual(construction: Construction) -> (Color, char, Color) {
+    let (base, glyph_color) = match construction.material {
+        ItemMaterial::Wood => ((0.42, 0.3, 0.18), Color::srgb(0.85, 0.62, 0.35)),
+        _ => ((0.4, 0.4, 0.42), Color::srgb(0.82, 0.82, 0.85)),
+    };
+    let (shade, glyph) = match construction.kind {
+        ConstructionKind::Wall => (0.8, '█'),
+        ConstructionKind::Floor => (1.0, '·'),
+        ConstructionKind::Road => (1.15, '═'),
+        ConstructionKind::Bridge => (0.9, '≡'),
+        ConstructionKind::Door => (1.0, '+'),
+    };
+    (
+        Color::srgb(base.0 * shade, base.1 * shade, base.2 * shade),
+        glyph,
+        glyph_color,
+    )
+}
+
+/// 地形变化（砍树、长草、长出树苗）后重新挑选地块的底色和字符
+pub fn tile_visual(rng: &mut impl Rng, terrain: TerrainType, biome: Option<WorldBiome>) -> (Color, char, Color) {
+    let variation = rng.gen_range(-0.05..0.05);
+    pick_tile_visual(rng, terrain, biome, variation)
+}
+
 fn pick_tile_visual(
-    rng: &mut SmallRng,
+    rng: &mut impl Rng,
     terrain: TerrainType,
     biome: Option<WorldBiome>,
     variation: f32,
@@ -1013,6 +1411,23 @@ fn pick_tile_visual(
                 Color::srgba(0.25, 0.15, 0.1, 0.6),
             ),
         },
+        TerrainType::Dirt => match biome {
+            Some(WorldBiome::Desert) => (
+                color_from_base((0.6, 0.5, 0.34), variation, (0.4, 0.3, 0.2)),
+                '.',
+                Color::srgba(0.4, 0.3, 0.18, 0.4),
+            ),
+            _ => (
+                color_from_base((0.45, 0.35, 0.22), variation, (0.8, 0.6, 0.4)),
+                if rng.gen_ratio(1, 6) { ':' } else { '.' },
+                Color::srgba(0.28, 0.2, 0.12, 0.45),
+            ),
+        },
+        TerrainType::Sapling => (
+            color_from_base((0.28, 0.6, 0.25), variation, (1.0, 1.4, 1.0)),
+            'τ',
+            Color::srgba(0.1, 0.4, 0.12, 0.8),
+        ),
     }
 }
 
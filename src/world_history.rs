@@ -0,0 +1,858 @@
+use bevy::prelude::*;
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::world_map_data::{WorldBiome, WorldCell};
+
+/// 默认模拟的历史年数
+pub const WORLD_HISTORY_DEFAULT_YEARS: u32 = 120;
+
+/// 文明种族
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CivilizationRace {
+    Dwarf,
+    Human,
+    Elf,
+    Goblin,
+}
+
+impl CivilizationRace {
+    pub const ALL: [CivilizationRace; 4] = [
+        CivilizationRace::Dwarf,
+        CivilizationRace::Human,
+        CivilizationRace::Elf,
+        CivilizationRace::Goblin,
+    ];
+
+    /// 种族名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            CivilizationRace::Dwarf => "矮人",
+            CivilizationRace::Human => "人类",
+            CivilizationRace::Elf => "精灵",
+            CivilizationRace::Goblin => "哥布林",
+        }
+    }
+
+    /// 领土边界与聚落的渲染颜色
+    pub fn color(&self) -> Color {
+        match self {
+            CivilizationRace::Dwarf => Color::srgb(0.95, 0.65, 0.2),
+            CivilizationRace::Human => Color::srgb(0.9, 0.9, 0.95),
+            CivilizationRace::Elf => Color::srgb(0.45, 0.95, 0.55),
+            CivilizationRace::Goblin => Color::srgb(0.85, 0.2, 0.25),
+        }
+    }
+
+    /// 种族对群落的偏好评分（越高越愿意定居扩张）
+    fn biome_preference(&self, biome: WorldBiome) -> f32 {
+        match (self, biome) {
+            (_, WorldBiome::Ocean | WorldBiome::Lake) => 0.0,
+            (CivilizationRace::Dwarf, WorldBiome::Mountain) => 3.0,
+            (CivilizationRace::Dwarf, WorldBiome::Tundra) => 1.5,
+            (CivilizationRace::Dwarf, WorldBiome::Grassland) => 1.0,
+            (CivilizationRace::Human, WorldBiome::Grassland) => 3.0,
+            (CivilizationRace::Human, WorldBiome::River) => 2.5,
+            (CivilizationRace::Human, WorldBiome::Forest) => 1.2,
+            (CivilizationRace::Elf, WorldBiome::Forest) => 3.0,
+            (CivilizationRace::Elf, WorldBiome::Swamp) => 1.5,
+            (CivilizationRace::Elf, WorldBiome::River) => 1.5,
+            (CivilizationRace::Goblin, WorldBiome::Desert) => 2.5,
+            (CivilizationRace::Goblin, WorldBiome::Swamp) => 2.0,
+            (CivilizationRace::Goblin, WorldBiome::Tundra) => 1.5,
+            _ => 0.6,
+        }
+    }
+
+    /// 每年向外扩张的概率
+    fn expansion_rate(&self) -> f64 {
+        match self {
+            CivilizationRace::Dwarf => 0.25,
+            CivilizationRace::Human => 0.45,
+            CivilizationRace::Elf => 0.2,
+            CivilizationRace::Goblin => 0.35,
+        }
+    }
+
+    /// 与邻国开战的倾向
+    fn aggression(&self) -> f64 {
+        match self {
+            CivilizationRace::Dwarf => 0.02,
+            CivilizationRace::Human => 0.03,
+            CivilizationRace::Elf => 0.01,
+            CivilizationRace::Goblin => 0.08,
+        }
+    }
+
+    /// 地名音节表（前缀, 后缀）
+    fn name_syllables(&self) -> (&'static [&'static str], &'static [&'static str]) {
+        match self {
+            CivilizationRace::Dwarf => (
+                &["铁", "石", "深", "锤", "铜", "炉", "金", "岩"],
+                &["堡", "厅", "渊", "炉", "门", "窟"],
+            ),
+            CivilizationRace::Human => (
+                &["白", "河", "麦", "橡", "鹰", "晨", "王", "灰"],
+                &["镇", "城", "港", "村", "桥", "集"],
+            ),
+            CivilizationRace::Elf => (
+                &["月", "叶", "星", "银", "露", "翠", "风", "歌"],
+                &["林", "庭", "荫", "泉", "苑", "谷"],
+            ),
+            CivilizationRace::Goblin => (
+                &["骨", "血", "毒", "黑", "裂", "腐", "刺", "獠"],
+                &["巢", "坑", "寨", "穴", "营", "沼"],
+            ),
+        }
+    }
+
+    /// 人名音节表（名, 姓）
+    fn person_syllables(&self) -> (&'static [&'static str], &'static [&'static str]) {
+        match self {
+            CivilizationRace::Dwarf => (
+                &["乌", "索", "巴", "朵", "格", "诺", "杜", "卡", "林", "波"],
+                &["铁须", "石拳", "深锤", "铜盔", "炉心", "岩骨"],
+            ),
+            CivilizationRace::Human => (
+                &["艾", "罗", "威", "玛", "亨", "伊", "查", "莉", "托", "安"],
+                &["白河", "麦田", "橡木", "鹰翼", "灰岭", "晨光"],
+            ),
+            CivilizationRace::Elf => (
+                &["阿", "瑟", "伊", "菲", "洛", "艾", "芙", "希", "薇", "塔"],
+                &["月歌", "叶语", "星泉", "银露", "翠风", "夜荫"],
+            ),
+            CivilizationRace::Goblin => (
+                &["格", "兹", "克", "斯", "拉", "布", "古", "札", "奎", "莫"],
+                &["碎骨", "血牙", "毒刺", "黑爪", "裂喉", "腐眼"],
+            ),
+        }
+    }
+
+    /// 文明名称模板
+    fn civilization_title(&self) -> &'static str {
+        match self {
+            CivilizationRace::Dwarf => "王国",
+            CivilizationRace::Human => "公国",
+            CivilizationRace::Elf => "议会",
+            CivilizationRace::Goblin => "部落",
+        }
+    }
+}
+
+/// 文明
+#[derive(Clone, Debug)]
+pub struct Civilization {
+    pub id: usize,
+    pub name: String,
+    pub race: CivilizationRace,
+    pub capital: Option<usize>,
+    /// 当前统治者（历史人物ID）
+    pub ruler: Option<usize>,
+    pub founded_year: u32,
+    /// 灭亡年份（仍存续时为None）
+    pub fallen_year: Option<u32>,
+    /// 当前交战的文明
+    pub at_war_with: Vec<usize>,
+}
+
+impl Civilization {
+    pub fn is_alive(&self) -> bool {
+        self.fallen_year.is_none()
+    }
+}
+
+/// 聚落
+#[derive(Clone, Debug)]
+pub struct Settlement {
+    pub id: usize,
+    pub name: String,
+    pub coord: IVec2,
+    /// 当前归属的文明
+    pub owner: usize,
+    /// 建立者（被征服后与 owner 不同）
+    pub founder: usize,
+    pub founded_year: u32,
+    pub population: u32,
+    pub is_capital: bool,
+}
+
+/// 历史人物身份
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FigureRole {
+    Ruler,
+    General,
+    Defender,
+}
+
+impl FigureRole {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FigureRole::Ruler => "统治者",
+            FigureRole::General => "将军",
+            FigureRole::Defender => "守卫者",
+        }
+    }
+}
+
+/// 历史人物
+#[derive(Clone, Debug)]
+pub struct HistoricalFigure {
+    pub id: usize,
+    pub name: String,
+    pub race: CivilizationRace,
+    pub civ: usize,
+    pub role: FigureRole,
+    /// 出生年份（可能早于历史开始，为负数）
+    pub birth_year: i32,
+    pub death_year: Option<u32>,
+    /// 杀死该人物的历史人物
+    pub slain_by: Option<usize>,
+}
+
+impl HistoricalFigure {
+    pub fn is_alive(&self) -> bool {
+        self.death_year.is_none()
+    }
+}
+
+/// 历史事件类型
+#[derive(Clone, Debug)]
+pub enum HistoricalEventKind {
+    CivilizationFounded { civ: usize },
+    SettlementFounded { civ: usize, settlement: usize },
+    WarDeclared { attacker: usize, defender: usize },
+    PeaceMade { civ_a: usize, civ_b: usize },
+    SettlementConquered {
+        settlement: usize,
+        attacker: usize,
+        defender: usize,
+        general: Option<usize>,
+    },
+    CivilizationFell { civ: usize, conqueror: Option<usize> },
+    RulerCrowned { civ: usize, figure: usize },
+    FigureDied { figure: usize, killer: Option<usize>, site: Option<usize> },
+}
+
+impl HistoricalEventKind {
+    /// 事件涉及的文明
+    pub fn civilizations(&self) -> Vec<usize> {
+        match self {
+            HistoricalEventKind::CivilizationFounded { civ }
+            | HistoricalEventKind::SettlementFounded { civ, .. }
+            | HistoricalEventKind::RulerCrowned { civ, .. } => vec![*civ],
+            HistoricalEventKind::WarDeclared { attacker, defender }
+            | HistoricalEventKind::SettlementConquered {
+                attacker, defender, ..
+            } => vec![*attacker, *defender],
+            HistoricalEventKind::PeaceMade { civ_a, civ_b } => vec![*civ_a, *civ_b],
+            HistoricalEventKind::CivilizationFell { civ, conqueror } => {
+                std::iter::once(*civ).chain(*conqueror).collect()
+            }
+            HistoricalEventKind::FigureDied { .. } => Vec::new(),
+        }
+    }
+
+    /// 事件涉及的聚落
+    pub fn settlements(&self) -> Vec<usize> {
+        match self {
+            HistoricalEventKind::SettlementFounded { settlement, .. }
+            | HistoricalEventKind::SettlementConquered { settlement, .. } => vec![*settlement],
+            HistoricalEventKind::FigureDied { site, .. } => site.iter().copied().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 事件涉及的历史人物
+    pub fn figures(&self) -> Vec<usize> {
+        match self {
+            HistoricalEventKind::RulerCrowned { figure, .. } => vec![*figure],
+            HistoricalEventKind::FigureDied { figure, killer, .. } => {
+                std::iter::once(*figure).chain(*killer).collect()
+            }
+            HistoricalEventKind::SettlementConquered { general, .. } => {
+                general.iter().copied().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 历史事件
+#[derive(Clone, Debug)]
+pub struct HistoricalEvent {
+    pub year: u32,
+    pub kind: HistoricalEventKind,
+}
+
+/// 世界历史 - 在地形生成后模拟得到的文明、聚落与事件
+#[derive(Clone, Debug, Default)]
+pub struct WorldHistory {
+    pub years: u32,
+    pub civilizations: Vec<Civilization>,
+    pub settlements: Vec<Settlement>,
+    pub figures: Vec<HistoricalFigure>,
+    pub events: Vec<HistoricalEvent>,
+}
+
+impl WorldHistory {
+    pub fn civilization(&self, id: usize) -> Option<&Civilization> {
+        self.civilizations.get(id)
+    }
+
+    pub fn settlement(&self, id: usize) -> Option<&Settlement> {
+        self.settlements.get(id)
+    }
+
+    pub fn figure(&self, id: usize) -> Option<&HistoricalFigure> {
+        self.figures.get(id)
+    }
+
+    /// 将事件描述为一行文本
+    pub fn describe_event(&self, event: &HistoricalEvent) -> String {
+        let civ_name = |id: usize| {
+            self.civilization(id)
+                .map(|civ| civ.name.clone())
+                .unwrap_or_else(|| "未知文明".to_string())
+        };
+        let site_name = |id: usize| {
+            self.settlement(id)
+                .map(|site| site.name.clone())
+                .unwrap_or_else(|| "未知聚落".to_string())
+        };
+        let figure_name = |id: usize| {
+            self.figure(id)
+                .map(|figure| figure.name.clone())
+                .unwrap_or_else(|| "无名氏".to_string())
+        };
+
+        let text = match &event.kind {
+            HistoricalEventKind::CivilizationFounded { civ } => {
+                format!("{}建立", civ_name(*civ))
+            }
+            HistoricalEventKind::SettlementFounded { civ, settlement } => {
+                format!("{}建立了{}", civ_name(*civ), site_name(*settlement))
+            }
+            HistoricalEventKind::WarDeclared { attacker, defender } => {
+                format!("{}向{}宣战", civ_name(*attacker), civ_name(*defender))
+            }
+            HistoricalEventKind::PeaceMade { civ_a, civ_b } => {
+                format!("{}与{}议和", civ_name(*civ_a), civ_name(*civ_b))
+            }
+            HistoricalEventKind::SettlementConquered {
+                settlement,
+                attacker,
+                defender,
+                general,
+            } => match general {
+                Some(general) => format!(
+                    "{}的{}率军从{}手中夺取了{}",
+                    civ_name(*attacker),
+                    figure_name(*general),
+                    civ_name(*defender),
+                    site_name(*settlement)
+                ),
+                None => format!(
+                    "{}从{}手中夺取了{}",
+                    civ_name(*attacker),
+                    civ_name(*defender),
+                    site_name(*settlement)
+                ),
+            },
+            HistoricalEventKind::RulerCrowned { civ, figure } => {
+                format!("{}成为{}的统治者", figure_name(*figure), civ_name(*civ))
+            }
+            HistoricalEventKind::FigureDied { figure, killer, site } => {
+                let place = site
+                    .map(|site| format!("于{}", site_name(site)))
+                    .unwrap_or_default();
+                match killer {
+                    Some(killer) => format!(
+                        "{}{}被{}杀死",
+                        figure_name(*figure),
+                        place,
+                        figure_name(*killer)
+                    ),
+                    None => format!("{}{}逝世", figure_name(*figure), place),
+                }
+            }
+            HistoricalEventKind::CivilizationFell { civ, conqueror } => match conqueror {
+                Some(conqueror) => format!("{}被{}灭亡", civ_name(*civ), civ_name(*conqueror)),
+                None => format!("{}消亡", civ_name(*civ)),
+            },
+        };
+
+        format!("第{}年: {}", event.year, text)
+    }
+}
+
+/// 聚落之间的最小间距（世界格子）
+const MIN_SETTLEMENT_SPACING: i32 = 3;
+
+/// 在已生成地形的世界格子上模拟历史，并写入领土归属
+pub fn generate_history(
+    cells: &mut [WorldCell],
+    width: i32,
+    height: i32,
+    seed: u64,
+    years: u32,
+) -> WorldHistory {
+    let mut rng = SmallRng::seed_from_u64(seed ^ 0x4849_5354_4f52_5921);
+    let mut history = WorldHistory {
+        years,
+        ..Default::default()
+    };
+
+    for cell in cells.iter_mut() {
+        cell.owner = None;
+        cell.settlement = None;
+    }
+
+    // 每个种族根据世界大小生成1-2个文明
+    let civs_per_race = if width * height >= 300 { 2 } else { 1 };
+    for race in CivilizationRace::ALL {
+        for _ in 0..civs_per_race {
+            let Some(coord) = pick_homeland(cells, width, height, race, &history, &mut rng) else {
+                continue;
+            };
+            let civ_id = history.civilizations.len();
+            let name = format!("{}{}", place_name(race, &mut rng), race.civilization_title());
+            history.civilizations.push(Civilization {
+                id: civ_id,
+                name,
+                race,
+                capital: None,
+                ruler: None,
+                founded_year: 0,
+                fallen_year: None,
+                at_war_with: Vec::new(),
+            });
+            history.events.push(HistoricalEvent {
+                year: 0,
+                kind: HistoricalEventKind::CivilizationFounded { civ: civ_id },
+            });
+
+            let site = found_settlement(cells, width, &mut history, civ_id, coord, 0, &mut rng);
+            history.settlements[site].is_capital = true;
+            history.civilizations[civ_id].capital = Some(site);
+            crown_ruler(&mut history, civ_id, 0, &mut rng);
+        }
+    }
+
+    for year in 1..=years {
+        simulate_year(cells, width, height, &mut history, year, &mut rng);
+    }
+
+    history
+}
+
+fn simulate_year(
+    cells: &mut [WorldCell],
+    width: i32,
+    height: i32,
+    history: &mut WorldHistory,
+    year: u32,
+    rng: &mut SmallRng,
+) {
+    let civ_count = history.civilizations.len();
+
+    for civ_id in 0..civ_count {
+        if !history.civilizations[civ_id].is_alive() {
+            continue;
+        }
+        let race = history.civilizations[civ_id].race;
+
+        // 统治者年老逝世后由新统治者继位
+        if let Some(ruler) = history.civilizations[civ_id].ruler {
+            let age = year as i32 - history.figures[ruler].birth_year;
+            if age > 55 && rng.gen_bool(0.08) {
+                kill_figure(history, ruler, None, None, year);
+                crown_ruler(history, civ_id, year, rng);
+            }
+        }
+
+        // 人口增长
+        for site in history.settlements.iter_mut().filter(|s| s.owner == civ_id) {
+            let growth = rng.gen_range(0..=(site.population / 20).max(2));
+            site.population = (site.population + growth).min(20_000);
+        }
+
+        // 和平扩张：吞并相邻的无主格子
+        if rng.gen_bool(race.expansion_rate()) {
+            let frontier = frontier_cells(cells, width, height, civ_id, |owner| owner.is_none());
+            // 每个边境格子只抽一次随机分，比较结果才前后一致
+            if let Some((index, _)) = frontier
+                .iter()
+                .map(|&index| (index, race.biome_preference(cells[index].biome) + rng.gen::<f32>()))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            {
+                if race.biome_preference(cells[index].biome) > 0.0 {
+                    cells[index].owner = Some(civ_id);
+
+                    // 领土足够远时建立新聚落
+                    let coord = cells[index].coord;
+                    if far_from_settlements(history, coord) && rng.gen_bool(0.3) {
+                        found_settlement(cells, width, history, civ_id, coord, year, rng);
+                    }
+                }
+            }
+        }
+
+        // 宣战：与接壤的文明
+        if rng.gen_bool(race.aggression()) {
+            let neighbours = bordering_civilizations(cells, width, height, &history.civilizations, civ_id);
+            if let Some(&target) = neighbours.choose(rng) {
+                if !history.civilizations[civ_id].at_war_with.contains(&target) {
+                    history.civilizations[civ_id].at_war_with.push(target);
+                    history.civilizations[target].at_war_with.push(civ_id);
+                    history.events.push(HistoricalEvent {
+                        year,
+                        kind: HistoricalEventKind::WarDeclared {
+                            attacker: civ_id,
+                            defender: target,
+                        },
+                    });
+                }
+            }
+        }
+
+        // 战争：夺取敌方边境格子
+        let enemies = history.civilizations[civ_id].at_war_with.clone();
+        for enemy in enemies {
+            // 已经灭亡的文明不再是战争对象
+            if !history.civilizations[enemy].is_alive() || !rng.gen_bool(0.35) {
+                continue;
+            }
+            let contested = frontier_cells(cells, width, height, civ_id, |owner| owner == Some(enemy));
+            let Some(&index) = contested.choose(rng) else {
+                continue;
+            };
+            cells[index].owner = Some(civ_id);
+
+            if let Some(site_id) = cells[index].settlement {
+                let site = &mut history.settlements[site_id];
+                site.owner = civ_id;
+                site.is_capital = false;
+                site.population = (site.population as f32 * rng.gen_range(0.4..0.8)) as u32;
+
+                let general = find_or_create_general(history, civ_id, year, rng);
+                history.events.push(HistoricalEvent {
+                    year,
+                    kind: HistoricalEventKind::SettlementConquered {
+                        settlement: site_id,
+                        attacker: civ_id,
+                        defender: enemy,
+                        general: Some(general),
+                    },
+                });
+
+                // 守城者战死
+                if rng.gen_bool(0.4) {
+                    let defender = create_figure(history, enemy, FigureRole::Defender, year, rng);
+                    kill_figure(history, defender, Some(general), Some(site_id), year);
+                }
+                if history.civilizations[enemy].capital == Some(site_id) {
+                    history.civilizations[enemy].capital = None;
+                }
+            }
+
+            check_civilization_fall(cells, history, enemy, Some(civ_id), year);
+        }
+
+        // 议和：长期战争有概率结束
+        let wars = history.civilizations[civ_id].at_war_with.clone();
+        for enemy in wars {
+            if rng.gen_bool(0.08) {
+                end_war(history, civ_id, enemy);
+                history.events.push(HistoricalEvent {
+                    year,
+                    kind: HistoricalEventKind::PeaceMade {
+                        civ_a: civ_id,
+                        civ_b: enemy,
+                    },
+                });
+            }
+        }
+    }
+
+    // 首都陷落的文明选择新首都
+    for civ_id in 0..civ_count {
+        let civ = &history.civilizations[civ_id];
+        if !civ.is_alive() || civ.capital.is_some() {
+            continue;
+        }
+        if let Some(site) = history
+            .settlements
+            .iter_mut()
+            .filter(|s| s.owner == civ_id)
+            .max_by_key(|s| s.population)
+        {
+            site.is_capital = true;
+            let site_id = site.id;
+            history.civilizations[civ_id].capital = Some(site_id);
+        }
+    }
+}
+
+/// 失去全部领土或聚落的文明灭亡：剩下的领土和聚落归征服者所有，没有征服者时领土变为无主
+fn check_civilization_fall(
+    cells: &mut [WorldCell],
+    history: &mut WorldHistory,
+    civ_id: usize,
+    conqueror: Option<usize>,
+    year: u32,
+) {
+    if !history.civilizations[civ_id].is_alive() {
+        return;
+    }
+    let has_land = cells.iter().any(|cell| cell.owner == Some(civ_id));
+    let has_site = history.settlements.iter().any(|s| s.owner == civ_id);
+    if has_land && has_site {
+        return;
+    }
+
+    history.civilizations[civ_id].fallen_year = Some(year);
+    history.civilizations[civ_id].capital = None;
+    if let Some(ruler) = history.civilizations[civ_id].ruler.take() {
+        kill_figure(history, ruler, None, None, year);
+    }
+    let enemies = history.civilizations[civ_id].at_war_with.clone();
+    for enemy in enemies {
+        end_war(history, civ_id, enemy);
+    }
+    for cell in cells.iter_mut().filter(|cell| cell.owner == Some(civ_id)) {
+        cell.owner = conqueror;
+    }
+    if let Some(conqueror) = conqueror {
+        for site in history.settlements.iter_mut().filter(|s| s.owner == civ_id) {
+            site.owner = conqueror;
+            site.is_capital = false;
+        }
+    }
+    history.events.push(HistoricalEvent {
+        year,
+        kind: HistoricalEventKind::CivilizationFell {
+            civ: civ_id,
+            conqueror,
+        },
+    });
+}
+
+fn create_figure(
+    history: &mut WorldHistory,
+    civ_id: usize,
+    role: FigureRole,
+    year: u32,
+    rng: &mut SmallRng,
+) -> usize {
+    let race = history.civilizations[civ_id].race;
+    let id = history.figures.len();
+    history.figures.push(HistoricalFigure {
+        id,
+        name: person_name(race, rng),
+        race,
+        civ: civ_id,
+        role,
+        birth_year: year as i32 - rng.gen_range(18..45),
+        death_year: None,
+        slain_by: None,
+    });
+    id
+}
+
+fn crown_ruler(history: &mut WorldHistory, civ_id: usize, year: u32, rng: &mut SmallRng) {
+    let ruler = create_figure(history, civ_id, FigureRole::Ruler, year, rng);
+    history.civilizations[civ_id].ruler = Some(ruler);
+    history.events.push(HistoricalEvent {
+        year,
+        kind: HistoricalEventKind::RulerCrowned {
+            civ: civ_id,
+            figure: ruler,
+        },
+    });
+}
+
+/// 沿用文明现存的将军，没有时任命新将军
+fn find_or_create_general(
+    history: &mut WorldHistory,
+    civ_id: usize,
+    year: u32,
+    rng: &mut SmallRng,
+) -> usize {
+    let existing: Vec<usize> = history
+        .figures
+        .iter()
+        .filter(|f| f.civ == civ_id && f.role == FigureRole::General && f.is_alive())
+        .map(|f| f.id)
+        .collect();
+    match existing.choose(rng) {
+        Some(&general) if rng.gen_bool(0.7) => general,
+        _ => create_figure(history, civ_id, FigureRole::General, year, rng),
+    }
+}
+
+fn kill_figure(
+    history: &mut WorldHistory,
+    figure: usize,
+    killer: Option<usize>,
+    site: Option<usize>,
+    year: u32,
+) {
+    history.figures[figure].death_year = Some(year);
+    history.figures[figure].slain_by = killer;
+    history.events.push(HistoricalEvent {
+        year,
+        kind: HistoricalEventKind::FigureDied {
+            figure,
+            killer,
+            site,
+        },
+    });
+}
+
+fn end_war(history: &mut WorldHistory, civ_a: usize, civ_b: usize) {
+    history.civilizations[civ_a].at_war_with.retain(|&id| id != civ_b);
+    history.civilizations[civ_b].at_war_with.retain(|&id| id != civ_a);
+}
+
+fn found_settlement(
+    cells: &mut [WorldCell],
+    width: i32,
+    history: &mut WorldHistory,
+    civ_id: usize,
+    coord: IVec2,
+    year: u32,
+    rng: &mut SmallRng,
+) -> usize {
+    let race = history.civilizations[civ_id].race;
+    let id = history.settlements.len();
+    history.settlements.push(Settlement {
+        id,
+        name: place_name(race, rng),
+        coord,
+        owner: civ_id,
+        founder: civ_id,
+        founded_year: year,
+        population: rng.gen_range(40..160),
+        is_capital: false,
+    });
+
+    let index = (coord.y * width + coord.x) as usize;
+    cells[index].owner = Some(civ_id);
+    cells[index].settlement = Some(id);
+
+    if year > 0 {
+        history.events.push(HistoricalEvent {
+            year,
+            kind: HistoricalEventKind::SettlementFounded {
+                civ: civ_id,
+                settlement: id,
+            },
+        });
+    }
+    id
+}
+
+/// 为文明挑选起源地：偏好的群落、无主且远离其他聚落
+fn pick_homeland(
+    cells: &[WorldCell],
+    width: i32,
+    height: i32,
+    race: CivilizationRace,
+    history: &WorldHistory,
+    rng: &mut SmallRng,
+) -> Option<IVec2> {
+    let mut best: Option<(f32, IVec2)> = None;
+    for _ in 0..200 {
+        let coord = IVec2::new(rng.gen_range(0..width), rng.gen_range(0..height));
+        let cell = &cells[(coord.y * width + coord.x) as usize];
+        if cell.owner.is_some() || !far_from_settlements(history, coord) {
+            continue;
+        }
+        let score = race.biome_preference(cell.biome) + rng.gen::<f32>() * 0.5;
+        if score <= 0.0 {
+            continue;
+        }
+        if best.is_none_or(|(best_score, _)| score > best_score) {
+            best = Some((score, coord));
+        }
+    }
+    best.map(|(_, coord)| coord)
+}
+
+fn far_from_settlements(history: &WorldHistory, coord: IVec2) -> bool {
+    history.settlements.iter().all(|site| {
+        let delta = (site.coord - coord).abs();
+        delta.x.max(delta.y) >= MIN_SETTLEMENT_SPACING
+    })
+}
+
+/// 与文明领土相邻、且满足归属条件的格子索引
+fn frontier_cells(
+    cells: &[WorldCell],
+    width: i32,
+    height: i32,
+    civ_id: usize,
+    accept: impl Fn(Option<usize>) -> bool,
+) -> Vec<usize> {
+    let mut result = Vec::new();
+    for (index, cell) in cells.iter().enumerate() {
+        if cell.owner == Some(civ_id) || !accept(cell.owner) {
+            continue;
+        }
+        let borders_civ = neighbour_indices(cell.coord, width, height)
+            .any(|neighbour| cells[neighbour].owner == Some(civ_id));
+        if borders_civ {
+            result.push(index);
+        }
+    }
+    result
+}
+
+/// 与文明领土接壤、仍然存续的其他文明
+fn bordering_civilizations(
+    cells: &[WorldCell],
+    width: i32,
+    height: i32,
+    civilizations: &[Civilization],
+    civ_id: usize,
+) -> Vec<usize> {
+    let mut result = Vec::new();
+    for cell in cells.iter().filter(|cell| cell.owner == Some(civ_id)) {
+        for neighbour in neighbour_indices(cell.coord, width, height) {
+            if let Some(other) = cells[neighbour].owner {
+                if other != civ_id && civilizations[other].is_alive() && !result.contains(&other) {
+                    result.push(other);
+                }
+            }
+        }
+    }
+    result
+}
+
+fn neighbour_indices(coord: IVec2, width: i32, height: i32) -> impl Iterator<Item = usize> {
+    [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+        .into_iter()
+        .map(move |offset| coord + offset)
+        .filter(move |c| c.x >= 0 && c.x < width && c.y >= 0 && c.y < height)
+        .map(move |c| (c.y * width + c.x) as usize)
+}
+
+fn place_name(race: CivilizationRace, rng: &mut SmallRng) -> String {
+    let (prefixes, suffixes) = race.name_syllables();
+    format!(
+        "{}{}",
+        prefixes.choose(rng).copied().unwrap_or("无"),
+        suffixes.choose(rng).copied().unwrap_or("地")
+    )
+}
+
+fn person_name(race: CivilizationRace, rng: &mut SmallRng) -> String {
+    let (given, family) = race.person_syllables();
+    let first = given.choose(rng).copied().unwrap_or("无");
+    let second = given.choose(rng).copied().unwrap_or("名");
+    format!(
+        "{}{}·{}",
+        first,
+        second,
+        family.choose(rng).copied().unwrap_or("氏")
+    )
+}
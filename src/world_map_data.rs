@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 use noise::{NoiseFn, Perlin};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BinaryHeap;
+
+use crate::world_history::{generate_history, WorldHistory, WORLD_HISTORY_DEFAULT_YEARS};
 
 /// 默认世界地图宽度
 pub const WORLD_ATLAS_DEFAULT_WIDTH: i32 = 20;
@@ -14,43 +18,111 @@ pub const WORLD_ATLAS_TILE_SIZE: f32 = 48.0;
 pub struct WorldAtlas {
     pub width: i32,
     pub height: i32,
-    #[allow(dead_code)]
     pub seed: u64,
     pub cells: Vec<WorldCell>,
+    /// 地形生成后模拟的世界历史
+    pub history: WorldHistory,
 }
 
 impl Default for WorldAtlas {
     fn default() -> Self {
-        let seed = rand::random();
-        Self::generate(seed, WORLD_ATLAS_DEFAULT_WIDTH, WORLD_ATLAS_DEFAULT_HEIGHT)
+        Self::generate_with_settings(&WorldGenSettings::default())
     }
 }
 
 impl WorldAtlas {
-    /// 重新生成宏观世界地图
-    #[allow(dead_code)]
-    pub fn regenerate(&mut self, seed: u64, width: i32, height: i32) {
-        *self = Self::generate(seed, width, height);
+    /// 按设置重新生成宏观世界地图（用于创建世界界面的实时预览）
+    pub fn regenerate(&mut self, settings: &WorldGenSettings) {
+        *self = Self::generate_with_settings(settings);
     }
 
-    /// 根据种子与尺寸生成宏观世界地图
+    /// 根据种子与尺寸生成宏观世界地图（其余参数使用默认值）
+    #[allow(dead_code)] // 保留用于快速生成
     pub fn generate(seed: u64, width: i32, height: i32) -> Self {
-        let elevation_noise = Perlin::new(seed as u32);
-        let moisture_noise = Perlin::new((seed as u32).wrapping_add(1));
-        let temperature_noise = Perlin::new((seed as u32).wrapping_add(2));
-        let mut cells = Vec::with_capacity((width * height) as usize);
+        Self::generate_with_settings(&WorldGenSettings {
+            seed,
+            width,
+            height,
+            ..default()
+        })
+    }
 
+    /// 按世界生成设置生成宏观世界地图，并在地形之后模拟历史
+    ///
+    /// 流程：海拔 → 纬度温度 → 盛行风湿度（含雨影） → 水系（填洼成湖、沿下坡汇流成河） → 群落 → 历史
+    pub fn generate_with_settings(settings: &WorldGenSettings) -> Self {
+        let settings = settings.clamped();
+        let WorldGenSettings {
+            seed,
+            width,
+            height,
+            history_years,
+            ..
+        } = settings;
+
+        let noise_seed = fold_seed(seed);
+        let elevation_noise = Perlin::new(noise_seed);
+        let moisture_noise = Perlin::new(noise_seed.wrapping_add(1));
+        let temperature_noise = Perlin::new(noise_seed.wrapping_add(2));
+        let ridge_noise = Perlin::new(noise_seed.wrapping_add(3));
+
+        let sample = |noise: &Perlin, x: i32, y: i32, scale: f64| {
+            let nx = x as f64 / width as f64 - 0.5;
+            let ny = y as f64 / height as f64 - 0.5;
+            noise.get([nx * scale, ny * scale]) as f32
+        };
+
+        let mut elevations = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let elevation = sample(&elevation_noise, x, y, 1.5)
+                    + sample(&ridge_noise, x, y, 4.0) * 0.25;
+                elevations.push(elevation.clamp(-1.0, 1.0));
+            }
+        }
+
+        // 海平面按目标海洋比例取海拔分位数，山脉高度随山脉频率降低
+        let mut sorted = elevations.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let ocean_index = ((sorted.len() as f32 * settings.ocean_percent / 100.0) as usize)
+            .min(sorted.len() - 1);
+        let levels = ElevationLevels {
+            sea: if settings.ocean_percent <= 0.0 {
+                f32::NEG_INFINITY
+            } else {
+                sorted[ocean_index]
+            },
+            mountain: sorted[ocean_index] + 0.8 / settings.mountain_frequency,
+        };
+
+        let mut moisture_base = simulate_moisture(&elevations, width, height, levels.sea);
+        for moisture in moisture_base.iter_mut() {
+            *moisture = (*moisture + settings.moisture_bias).clamp(-1.0, 1.0);
+        }
+        let hydrology = route_water(&elevations, &moisture_base, width, height, levels.sea);
+
+        let mut cells = Vec::with_capacity((width * height) as usize);
         for y in 0..height {
             for x in 0..width {
                 let coord = IVec2::new(x, y);
-                let nx = x as f64 / width as f64 - 0.5;
-                let ny = y as f64 / height as f64 - 0.5;
+                let index = (y * width + x) as usize;
+                let elevation = elevations[index];
 
-                let elevation = elevation_noise.get([nx * 1.5, ny * 1.5]) as f32;
-                let moisture = moisture_noise.get([nx * 1.8, ny * 1.8]) as f32;
-                let temperature = temperature_noise.get([nx * 1.2, ny * 1.2]) as f32;
+                // 温度：赤道最热、两极最冷，海拔越高越冷
+                let temperature = (0.9 - latitude(y, height) * 1.8
+                    - (elevation - levels.sea.max(-1.0)).max(0.0) * 0.7
+                    + sample(&temperature_noise, x, y, 1.2) * 0.15
+                    + settings.temperature_bias)
+                    .clamp(-1.0, 1.0);
+                let moisture =
+                    (moisture_base[index] + sample(&moisture_noise, x, y, 1.8) * 0.2).clamp(-1.0, 1.0);
 
-                let biome = classify_biome(elevation, temperature, moisture);
+                let flow = hydrology.flow[index];
+                let biome = if hydrology.lake[index] {
+                    WorldBiome::Lake
+                } else {
+                    classify_biome(elevation, temperature, moisture, flow >= RIVER_FLOW_THRESHOLD, levels)
+                };
                 let mut rng = SmallRng::seed_from_u64(seed ^ ((x as u64) << 32) ^ y as u64);
                 let local_seed = rng.gen::<u32>();
 
@@ -61,15 +133,23 @@ impl WorldAtlas {
                     moisture,
                     temperature,
                     local_seed,
+                    downstream: hydrology.downstream[index]
+                        .map(|next| IVec2::new(next as i32 % width, next as i32 / width)),
+                    flow,
+                    owner: None,
+                    settlement: None,
                 });
             }
         }
 
+        let history = generate_history(&mut cells, width, height, seed, history_years);
+
         Self {
             width,
             height,
             seed,
             cells,
+            history,
         }
     }
 
@@ -83,6 +163,84 @@ impl WorldAtlas {
     }
 }
 
+/// 世界生成设置，可导出为预设文件以完全复现同一个世界
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorldGenSettings {
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    /// 目标海洋比例（百分比）
+    pub ocean_percent: f32,
+    /// 山脉频率倍率，越大山脉越多
+    pub mountain_frequency: f32,
+    pub temperature_bias: f32,
+    pub moisture_bias: f32,
+    pub history_years: u32,
+}
+
+impl Default for WorldGenSettings {
+    fn default() -> Self {
+        Self {
+            seed: rand::random::<u64>(),
+            width: WORLD_ATLAS_DEFAULT_WIDTH,
+            height: WORLD_ATLAS_DEFAULT_HEIGHT,
+            ocean_percent: 30.0,
+            mountain_frequency: 1.0,
+            temperature_bias: 0.0,
+            moisture_bias: 0.0,
+            history_years: WORLD_HISTORY_DEFAULT_YEARS,
+        }
+    }
+}
+
+impl WorldGenSettings {
+    pub const MIN_WIDTH: i32 = 10;
+    pub const MAX_WIDTH: i32 = 48;
+    pub const MIN_HEIGHT: i32 = 6;
+    pub const MAX_HEIGHT: i32 = 32;
+    pub const MAX_OCEAN_PERCENT: f32 = 90.0;
+    pub const MIN_MOUNTAIN_FREQUENCY: f32 = 0.25;
+    pub const MAX_MOUNTAIN_FREQUENCY: f32 = 3.0;
+    pub const MAX_CLIMATE_BIAS: f32 = 0.5;
+    pub const MAX_HISTORY_YEARS: u32 = 500;
+
+    /// 将所有参数限制在允许范围内（导入的预设也会经过这里）
+    pub fn clamped(&self) -> Self {
+        Self {
+            seed: self.seed,
+            width: self.width.clamp(Self::MIN_WIDTH, Self::MAX_WIDTH),
+            height: self.height.clamp(Self::MIN_HEIGHT, Self::MAX_HEIGHT),
+            ocean_percent: self.ocean_percent.clamp(0.0, Self::MAX_OCEAN_PERCENT),
+            mountain_frequency: self
+                .mountain_frequency
+                .clamp(Self::MIN_MOUNTAIN_FREQUENCY, Self::MAX_MOUNTAIN_FREQUENCY),
+            temperature_bias: self
+                .temperature_bias
+                .clamp(-Self::MAX_CLIMATE_BIAS, Self::MAX_CLIMATE_BIAS),
+            moisture_bias: self
+                .moisture_bias
+                .clamp(-Self::MAX_CLIMATE_BIAS, Self::MAX_CLIMATE_BIAS),
+            history_years: self.history_years.min(Self::MAX_HISTORY_YEARS),
+        }
+    }
+
+    /// 将种子文本转换为数值种子：纯数字直接使用，否则对文本做稳定哈希（FNV-1a）
+    pub fn seed_from_text(text: &str) -> u64 {
+        let text = text.trim();
+        if let Ok(seed) = text.parse::<u64>() {
+            return seed;
+        }
+        text.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+}
+
+/// 把 64 位世界种子折叠成噪声用的 32 位种子，高 32 位也参与，只有高位不同的种子不会生成同样的地形
+pub fn fold_seed(seed: u64) -> u32 {
+    (seed ^ (seed >> 32)) as u32
+}
+
 /// 宏观世界地图中的单元格
 #[derive(Clone)]
 pub struct WorldCell {
@@ -92,9 +250,22 @@ pub struct WorldCell {
     pub moisture: f32,
     pub temperature: f32,
     pub local_seed: u32,
+    /// 水流流向的相邻格子（海洋和地图边缘为出口，没有去向）
+    pub downstream: Option<IVec2>,
+    /// 从上游汇集的径流量
+    pub flow: f32,
+    /// 领土归属的文明ID
+    pub owner: Option<usize>,
+    /// 位于该格子的聚落ID
+    pub settlement: Option<usize>,
 }
 
 impl WorldCell {
+    /// 是否有河流流经（流量足够且不是海洋或湖泊）
+    pub fn has_river(&self) -> bool {
+        self.flow >= RIVER_FLOW_THRESHOLD && !matches!(self.biome, WorldBiome::Ocean | WorldBiome::Lake)
+    }
+
     /// 渲染颜色
     pub fn color(&self) -> Color {
         match self.biome {
@@ -105,6 +276,7 @@ impl WorldCell {
             WorldBiome::Tundra => Color::srgb(0.7, 0.75, 0.8),
             WorldBiome::Ocean => Color::srgb(0.15, 0.35, 0.7),
             WorldBiome::River => Color::srgb(0.2, 0.5, 0.85),
+            WorldBiome::Lake => Color::srgb(0.25, 0.55, 0.8),
             WorldBiome::Swamp => Color::srgb(0.25, 0.45, 0.3),
         }
     }
@@ -119,6 +291,7 @@ impl WorldCell {
             WorldBiome::Tundra => "冻原",
             WorldBiome::Ocean => "海洋",
             WorldBiome::River => "河流",
+            WorldBiome::Lake => "湖泊",
             WorldBiome::Swamp => "沼泽",
         }
     }
@@ -132,7 +305,7 @@ pub struct AtlasSelection {
 }
 
 /// 宏观世界地图支持的生物群落
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum WorldBiome {
     Grassland,
     Forest,
@@ -141,24 +314,41 @@ pub enum WorldBiome {
     Tundra,
     Ocean,
     River,
+    Lake,
     Swamp,
 }
 
-fn classify_biome(elevation: f32, temperature: f32, moisture: f32) -> WorldBiome {
-    if elevation < -0.2 {
-        if moisture > 0.2 {
-            WorldBiome::Swamp
-        } else {
-            WorldBiome::Ocean
-        }
-    } else if (elevation.abs() < 0.15) && moisture > 0.55 {
-        WorldBiome::River
-    } else if elevation > 0.6 {
+/// 由世界生成设置推导出的海平面与山脉海拔
+#[derive(Clone, Copy)]
+struct ElevationLevels {
+    sea: f32,
+    mountain: f32,
+}
+
+/// 形成河流所需的最小流量
+pub const RIVER_FLOW_THRESHOLD: f32 = 4.0;
+/// 洼地填平深度超过该值时形成湖泊
+const LAKE_MIN_DEPTH: f32 = 0.05;
+
+fn classify_biome(
+    elevation: f32,
+    temperature: f32,
+    moisture: f32,
+    river: bool,
+    levels: ElevationLevels,
+) -> WorldBiome {
+    if elevation < levels.sea {
+        WorldBiome::Ocean
+    } else if elevation > levels.mountain {
         if temperature < -0.1 {
             WorldBiome::Tundra
         } else {
             WorldBiome::Mountain
         }
+    } else if river {
+        WorldBiome::River
+    } else if elevation < levels.sea + 0.1 && moisture > 0.3 {
+        WorldBiome::Swamp
     } else if moisture < -0.3 {
         WorldBiome::Desert
     } else if moisture > 0.3 {
@@ -173,3 +363,163 @@ fn classify_biome(elevation: f32, temperature: f32, moisture: f32) -> WorldBiome
         WorldBiome::Grassland
     }
 }
+
+/// 纬度：赤道（地图中线）为0，两极（上下边缘）为1
+fn latitude(y: i32, height: i32) -> f32 {
+    ((y as f32 + 0.5) / height as f32 * 2.0 - 1.0).abs()
+}
+
+/// 盛行风方向：中纬度为西风（向东吹），低纬度与高纬度为东风
+fn prevailing_wind(y: i32, height: i32) -> i32 {
+    if (0.33..0.66).contains(&latitude(y, height)) {
+        1
+    } else {
+        -1
+    }
+}
+
+/// 沿盛行风输送水汽：海面补充水汽，陆地逐渐降水，
+/// 山脉迎风坡因地形抬升大量降水，背风坡形成雨影
+fn simulate_moisture(elevations: &[f32], width: i32, height: i32, sea_level: f32) -> Vec<f32> {
+    let mut moisture = vec![0.0; elevations.len()];
+
+    for y in 0..height {
+        let columns: Vec<i32> = if prevailing_wind(y, height) > 0 {
+            (0..width).collect()
+        } else {
+            (0..width).rev().collect()
+        };
+
+        // 从地图外的海洋吹来的水汽
+        let mut carried: f32 = 0.7;
+        let mut previous_elevation = sea_level.max(-1.0);
+
+        for x in columns {
+            let index = (y * width + x) as usize;
+            let elevation = elevations[index];
+
+            if elevation < sea_level {
+                carried = (carried + 0.3).min(1.0);
+                moisture[index] = 1.0;
+            } else {
+                let lift = (elevation - previous_elevation).max(0.0);
+                let rain = carried * (0.08 + lift * 1.5).min(0.85);
+                carried = (carried - rain).max(0.0);
+                moisture[index] = ((carried * 0.8 + rain * 1.5) * 2.0 - 1.0).clamp(-1.0, 1.0);
+            }
+            previous_elevation = elevation.max(sea_level);
+        }
+    }
+
+    moisture
+}
+
+/// 水系模拟结果
+struct Hydrology {
+    downstream: Vec<Option<usize>>,
+    flow: Vec<f32>,
+    lake: Vec<bool>,
+}
+
+/// 优先队列中的格子（按填平后的海拔从低到高出队）
+#[derive(PartialEq)]
+struct FloodCell {
+    elevation: f32,
+    index: usize,
+}
+
+impl Eq for FloodCell {}
+
+impl Ord for FloodCell {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .elevation
+            .total_cmp(&self.elevation)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for FloodCell {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 从海洋和地图边缘向内做优先级洪水填充：
+/// 洼地被填平为湖泊，每个陆地格子的水流都指向更靠近出口的相邻格子，
+/// 再按降水量从上游向下游累积径流
+fn route_water(
+    elevations: &[f32],
+    moisture: &[f32],
+    width: i32,
+    height: i32,
+    sea_level: f32,
+) -> Hydrology {
+    let count = elevations.len();
+    let mut filled = elevations.to_vec();
+    let mut downstream = vec![None; count];
+    let mut visited = vec![false; count];
+    let mut order = Vec::with_capacity(count);
+    let mut queue = BinaryHeap::new();
+
+    for (index, &elevation) in elevations.iter().enumerate() {
+        let x = index as i32 % width;
+        let y = index as i32 / width;
+        let on_edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+        if elevation < sea_level || on_edge {
+            visited[index] = true;
+            queue.push(FloodCell { elevation, index });
+        }
+    }
+
+    while let Some(cell) = queue.pop() {
+        order.push(cell.index);
+        let x = cell.index as i32 % width;
+        let y = cell.index as i32 / width;
+
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                continue;
+            }
+            let neighbour = (ny * width + nx) as usize;
+            if visited[neighbour] {
+                continue;
+            }
+            visited[neighbour] = true;
+            // 洼地填平到出水口高度，并保留微小坡度使水能继续流出
+            filled[neighbour] = elevations[neighbour].max(filled[cell.index] + 1e-4);
+            downstream[neighbour] = Some(cell.index);
+            queue.push(FloodCell {
+                elevation: filled[neighbour],
+                index: neighbour,
+            });
+        }
+    }
+
+    // 降水越多的格子产生越多径流
+    let mut flow: Vec<f32> = (0..count)
+        .map(|index| {
+            if elevations[index] < sea_level {
+                0.0
+            } else {
+                0.25 + (moisture[index] + 1.0) * 0.5
+            }
+        })
+        .collect();
+    for &index in order.iter().rev() {
+        if let Some(next) = downstream[index] {
+            flow[next] += flow[index];
+        }
+    }
+
+    let lake = (0..count)
+        .map(|index| elevations[index] >= sea_level && filled[index] - elevations[index] > LAKE_MIN_DEPTH)
+        .collect();
+
+    Hydrology {
+        downstream,
+        flow,
+        lake,
+    }
+}